@@ -0,0 +1,174 @@
+use std::env;
+use std::error::Error;
+
+use crate::manager::task::TaskStatus;
+use crate::manager::{TaskManager, TaskQuery};
+
+pub const DEFAULT_WIDTH: usize = 80;
+const MIN_COLUMN_WIDTH: usize = 16;
+const SEPARATOR: &str = " │ ";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardColumn {
+    pub status: TaskStatus,
+    pub wip_limit: Option<u32>,
+    pub tasks: Vec<String>,
+}
+
+impl BoardColumn {
+    pub fn is_over_limit(&self) -> bool {
+        self.wip_limit.is_some_and(|limit| self.tasks.len() > limit as usize)
+    }
+
+    fn header(&self) -> String {
+        match (self.wip_limit, self.is_over_limit()) {
+            (Some(limit), true) => format!("{} ({}/{limit})!", self.status, self.tasks.len()),
+            (Some(limit), false) => format!("{} ({}/{limit})", self.status, self.tasks.len()),
+            (None, _) => format!("{} ({})", self.status, self.tasks.len()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    pub columns: Vec<BoardColumn>,
+}
+
+impl Board {
+    pub fn build(manager: &TaskManager, include_cancelled: bool) -> Result<Board, Box<dyn Error>> {
+        let workflow = manager.workflow();
+
+        let mut columns = Vec::new();
+        for definition in workflow.get_statuses()? {
+            if definition.status == TaskStatus::Cancelled && !include_cancelled {
+                continue;
+            }
+            columns.push(BoardColumn {
+                wip_limit: workflow.get_wip_limit(&definition.status)?,
+                status: definition.status,
+                tasks: Vec::new(),
+            });
+        }
+
+        let query = TaskQuery { include_cancelled, ..Default::default() };
+        for task in manager.query_tasks(&query)? {
+            let status = task.get_status()?;
+
+            // tasks whose status isn't part of the workflow anymore still get their column
+            let index = match columns.iter().position(|column| column.status == status) {
+                Some(index) => index,
+                None => {
+                    columns.push(BoardColumn { status, wip_limit: None, tasks: Vec::new() });
+                    columns.len() - 1
+                }
+            };
+            columns[index].tasks.push(task.get_title()?);
+        }
+
+        Ok(Board { columns })
+    }
+
+    pub fn to_text(&self, width: usize) -> String {
+        if self.columns.is_empty() {
+            return String::new();
+        }
+
+        // as many columns side by side as the width allows, the others below them
+        let separator = SEPARATOR.chars().count();
+        let per_row = ((width + separator) / (MIN_COLUMN_WIDTH + separator)).clamp(1, self.columns.len());
+        let column_width = (width.saturating_sub((per_row - 1) * separator) / per_row).max(1);
+
+        self.columns
+            .chunks(per_row)
+            .map(|columns| render_row(columns, column_width))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+pub fn terminal_width() -> usize {
+    match crossterm::terminal::size() {
+        Ok((columns, _)) if columns > 0 => columns as usize,
+        _ => env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+            .unwrap_or(DEFAULT_WIDTH),
+    }
+}
+
+fn render_row(columns: &[BoardColumn], width: usize) -> String {
+    let height = columns.iter().map(|column| column.tasks.len()).max().unwrap_or(0);
+    let join = |cells: Vec<String>| cells.join(SEPARATOR).trim_end().to_string();
+
+    let mut lines = vec![
+        join(columns.iter().map(|column| fit(&column.header(), width)).collect()),
+        join(columns.iter().map(|_| "─".repeat(width)).collect()),
+    ];
+    for line in 0..height {
+        lines.push(join(columns.iter()
+            .map(|column| fit(column.tasks.get(line).map(String::as_str).unwrap_or(""), width))
+            .collect()));
+    }
+
+    lines.join("\n")
+}
+
+fn fit(text: &str, width: usize) -> String {
+    let length = text.chars().count();
+    match length > width {
+        true => text.chars().take(width.saturating_sub(1)).chain(['…']).collect(),
+        false => format!("{text}{}", " ".repeat(width - length)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::manager::{task::TaskStatus, TaskManager};
+
+    use super::{fit, Board};
+
+    fn set_up_manager() -> TaskManager {
+        let manager = TaskManager::open_in_memory()
+            .expect("unable to create in-memory database for testing purpuses");
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.add_new_task("task C", "desc C");
+        let _ = manager.get_task("task A").unwrap().set_status(&TaskStatus::Underway);
+        let _ = manager.get_task("task C").unwrap().cancel(None);
+        manager
+    }
+
+    #[test]
+    fn test_build() {
+        let manager = set_up_manager();
+        let _ = manager.workflow().set_wip_limit(&TaskStatus::Underway, Some(0));
+
+        let board = Board::build(&manager, false).unwrap();
+        let statuses: Vec<String> = board.columns.iter().map(|column| column.status.to_string()).collect();
+        assert_eq!(vec!["underway", "undone", "done"], statuses);
+        assert_eq!(vec!["task A"], board.columns[0].tasks);
+        assert!(board.columns[0].is_over_limit());
+
+        assert_eq!(4, Board::build(&manager, true).unwrap().columns.len());
+    }
+
+    #[test]
+    fn test_to_text() {
+        let manager = set_up_manager();
+        let board = Board::build(&manager, false).unwrap();
+
+        // three columns side by side when wide enough, one per row otherwise
+        let wide = board.to_text(80);
+        assert!(wide.lines().next().unwrap().contains("underway (1)"));
+        assert!(wide.lines().next().unwrap().contains("done (0)"));
+        assert!(wide.lines().all(|line| line.chars().count() <= 80));
+        assert_eq!(3, board.to_text(20).split("\n\n").count());
+    }
+
+    #[test]
+    fn test_fit() {
+        assert_eq!("task  ", fit("task", 6));
+        assert_eq!("a lo…", fit("a long title", 5));
+    }
+}
@@ -0,0 +1,187 @@
+use std::error::Error;
+
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate, NaiveTime};
+
+use crate::dates;
+use crate::manager::{TaskManager, TaskQuery};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DueTask {
+    pub title: String,
+    pub due: DateTime<Local>,
+    pub overdue: bool,
+}
+
+pub fn get_due_tasks(manager: &TaskManager, from: Option<NaiveDate>, to: NaiveDate, now: DateTime<Local>) -> Result<Vec<DueTask>, Box<dyn Error>> {
+    let query = TaskQuery {
+        only_open: true,
+        due_from: from.map(|from| dates::from_naive(from.and_time(NaiveTime::MIN))).transpose()?,
+        due_to: Some(dates::from_naive((to + Days::new(1)).and_time(NaiveTime::MIN))?),
+        ..Default::default()
+    };
+
+    let mut tasks = Vec::new();
+    for task in manager.query_tasks(&query)? {
+        if let Some(due) = task.get_due()? {
+            tasks.push(DueTask { title: task.get_title()?, due, overdue: task.is_overdue(now)? });
+        }
+    }
+    tasks.sort_by_key(|task| task.due);
+
+    Ok(tasks)
+}
+
+pub fn month_to_text(manager: &TaskManager, month: NaiveDate, titles: bool, now: DateTime<Local>) -> Result<String, Box<dyn Error>> {
+    let first = month.with_day(1).unwrap_or(month);
+    let last = first + Months::new(1) - Days::new(1);
+    let tasks = get_due_tasks(manager, Some(first), last, now)?;
+
+    let mut text = format!("{:^55}", first.format("%B %Y").to_string()).trim_end().to_string() + "\n";
+    text += &WEEKDAYS.iter().map(|weekday| format!("{weekday:<8}")).collect::<String>();
+    text = text.trim_end().to_string() + "\n";
+
+    // each day shows how many tasks are due on it, with a '!' if some of them are late
+    let mut line = "        ".repeat(first.weekday().num_days_from_monday() as usize);
+    let mut day = first;
+    while day <= last {
+        let due: Vec<&DueTask> = tasks.iter().filter(|task| task.due.date_naive() == day).collect();
+        let cell = match (due.len(), due.iter().any(|task| task.overdue)) {
+            (0, _) => format!("{:>2}", day.day()),
+            (count, false) => format!("{:>2} ({count})", day.day()),
+            (count, true) => format!("{:>2} ({count})!", day.day()),
+        };
+        line += &format!("{cell:<8}");
+
+        if day.weekday().num_days_from_monday() == 6 {
+            text += line.trim_end();
+            text += "\n";
+            line.clear();
+        }
+        day = day + Days::new(1);
+    }
+    if !line.is_empty() {
+        text += line.trim_end();
+        text += "\n";
+    }
+
+    if titles && !tasks.is_empty() {
+        text += "\n";
+        text += &list_by_day(&tasks);
+    }
+
+    Ok(text.trim_end().to_string())
+}
+
+pub fn agenda_to_text(manager: &TaskManager, days: u64, now: DateTime<Local>) -> Result<String, Box<dyn Error>> {
+    let today = now.date_naive();
+    let last = today + Days::new(days.max(1) - 1);
+    let tasks = get_due_tasks(manager, None, last, now)?;
+
+    let mut text = String::new();
+    let overdue: Vec<DueTask> = tasks.iter().filter(|task| task.overdue).cloned().collect();
+    if !overdue.is_empty() {
+        text += "Overdue\n";
+        for task in &overdue {
+            text += &format!("  {}  {}\n", dates::format_timestamp(task.due.timestamp()), task.title);
+        }
+        text += "\n";
+    }
+
+    // every day is listed, even when nothing is due, to give an idea of the free ones
+    let mut day = today;
+    while day <= last {
+        match day == today {
+            true => text += &format!("{} (today)\n", day.format("%a %Y-%m-%d")),
+            false => text += &format!("{}\n", day.format("%a %Y-%m-%d")),
+        }
+        tasks.iter()
+            .filter(|task| !task.overdue && task.due.date_naive() == day)
+            .for_each(|task| match task.due.time() == NaiveTime::MIN {
+                true => text += &format!("  {}\n", task.title),
+                false => text += &format!("  {}  {}\n", task.due.format("%H:%M"), task.title),
+            });
+        day = day + Days::new(1);
+    }
+
+    Ok(text.trim_end().to_string())
+}
+
+fn list_by_day(tasks: &[DueTask]) -> String {
+    let mut text = String::new();
+    let mut current: Option<NaiveDate> = None;
+
+    for task in tasks {
+        let day = task.due.date_naive();
+        if current != Some(day) {
+            text += &format!("{}\n", day.format("%a %Y-%m-%d"));
+            current = Some(day);
+        }
+        match task.overdue {
+            true => text += &format!("  {} (overdue)\n", task.title),
+            false => text += &format!("  {}\n", task.title),
+        }
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local, NaiveDate};
+
+    use crate::manager::{task::TaskStatus, TaskManager};
+
+    use super::{agenda_to_text, get_due_tasks, month_to_text};
+
+    fn set_up_manager() -> TaskManager {
+        let manager = TaskManager::open_in_memory()
+            .expect("unable to create in-memory database for testing purpuses");
+        let now = Local::now();
+        let _ = manager.add_new_task("late task", "desc");
+        let _ = manager.add_new_task("soon task", "desc");
+        let _ = manager.add_new_task("done task", "desc");
+        let _ = manager.add_new_task("no due task", "desc");
+        let _ = manager.get_task("late task").unwrap().set_due(Some(now - Duration::days(2)));
+        let _ = manager.get_task("soon task").unwrap().set_due(Some(now + Duration::days(2)));
+        let _ = manager.get_task("done task").unwrap().set_due(Some(now + Duration::days(1)));
+        let _ = manager.get_task("done task").unwrap().set_status(&TaskStatus::Done);
+        manager
+    }
+
+    #[test]
+    fn test_get_due_tasks() {
+        let manager = set_up_manager();
+        let now = Local::now();
+
+        // only the open tasks with a due date, the late ones first
+        let tasks = get_due_tasks(&manager, None, now.date_naive() + chrono::Days::new(7), now).unwrap();
+        let titles: Vec<&str> = tasks.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(vec!["late task", "soon task"], titles);
+        assert!(tasks[0].overdue);
+        assert!(!tasks[1].overdue);
+    }
+
+    #[test]
+    fn test_agenda_to_text() {
+        let manager = set_up_manager();
+        let text = agenda_to_text(&manager, 7, Local::now()).unwrap();
+        assert!(text.starts_with("Overdue\n"));
+        assert!(text.contains("soon task"));
+        assert!(!text.contains("done task"));
+        assert_eq!(1, text.matches("(today)").count());
+    }
+
+    #[test]
+    fn test_month_to_text() {
+        let manager = set_up_manager();
+
+        // november 2026 starts on a sunday, and has 30 days
+        let text = month_to_text(&manager, NaiveDate::from_ymd_opt(2026, 11, 1).unwrap(), false, Local::now()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!("November 2026", lines[0].trim());
+        assert_eq!(" 1", &lines[2][48..]);
+        assert!(lines.last().unwrap().starts_with("30"));
+    }
+}
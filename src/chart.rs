@@ -0,0 +1,313 @@
+use std::error::Error;
+
+use chrono::{DateTime, Days, Local, NaiveDate, NaiveTime};
+
+use crate::dates;
+use crate::manager::history::StatusChange;
+use crate::manager::task::TaskStatus;
+use crate::manager::workflow::StatusDefinition;
+use crate::manager::{TaskManager, TaskQuery};
+
+const TEXT_WIDTH: usize = 40;
+const SVG_WIDTH: f64 = 640.0;
+const SVG_HEIGHT: f64 = 320.0;
+const SVG_MARGIN: f64 = 40.0;
+const SVG_COLORS: [&str; 8] = ["#4c72b0", "#dd8452", "#55a868", "#c44e52", "#8172b3", "#937860", "#da8bc3", "#8c8c8c"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartStyle {
+    Unicode,
+    Ascii,
+}
+
+impl ChartStyle {
+    fn fills(&self) -> &'static [char] {
+        match self {
+            ChartStyle::Unicode => &['█', '▓', '▒', '░', '▞', '▚', '▖', '▗'],
+            ChartStyle::Ascii => &['#', '=', '+', '-', '.', '*', 'o', 'x'],
+        }
+    }
+
+    fn marker(&self) -> char {
+        match self {
+            ChartStyle::Unicode => '┊',
+            ChartStyle::Ascii => '|',
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowChart {
+    // the project the chart is limited to, if any
+    pub project: Option<String>,
+    pub statuses: Vec<StatusDefinition>,
+    pub days: Vec<(NaiveDate, Vec<usize>)>,
+}
+
+impl FlowChart {
+    pub fn build(manager: &TaskManager, from: NaiveDate, to: NaiveDate, project: Option<&str>) -> Result<FlowChart, Box<dyn Error>> {
+        if to < from {
+            return Err(Box::<dyn std::error::Error>::from("the chart can't end before it starts"))
+        }
+
+        let statuses = manager.workflow().get_statuses()?;
+        let changes = manager.status_history().get_all_changes()?;
+
+        let mut tasks = Vec::new();
+        let query = TaskQuery { project: project.map(str::to_string), ..TaskQuery::everything() };
+        for task in manager.query_tasks(&query)? {
+            let task_changes: Vec<&StatusChange> = changes.iter().filter(|change| change.task_id == task.get_id()).collect();
+            tasks.push((task.get_created_at()?, task.get_status()?, task_changes));
+        }
+
+        // counting, at the end of every day, the tasks in each status
+        let mut days = Vec::new();
+        let mut day = from;
+        while day <= to {
+            let end = dates::from_naive((day + Days::new(1)).and_time(NaiveTime::MIN))?;
+            let mut counts = vec![0; statuses.len()];
+
+            for (created_at, current, task_changes) in &tasks {
+                if let Some(status) = status_at(*created_at, current, task_changes, end) {
+                    if let Some(index) = statuses.iter().position(|definition| definition.status == status) {
+                        counts[index] += 1;
+                    }
+                }
+            }
+
+            days.push((day, counts));
+            day = day + Days::new(1);
+        }
+
+        Ok(FlowChart { project: project.map(str::to_string), statuses, days })
+    }
+
+    pub fn remaining(&self) -> Vec<usize> {
+        self.days
+            .iter()
+            .map(|(_, counts)| counts.iter()
+                .zip(&self.statuses)
+                .filter(|(_, definition)| !definition.closed)
+                .map(|(count, _)| count)
+                .sum())
+            .collect()
+    }
+
+    // the straight line from the work remaining on the first day down to nothing on the last one
+    pub fn ideal(&self) -> Vec<f64> {
+        let first = self.remaining().first().copied().unwrap_or(0) as f64;
+        let last_index = self.days.len().saturating_sub(1).max(1) as f64;
+        (0..self.days.len()).map(|index| first * (1.0 - index as f64 / last_index)).collect()
+    }
+
+    pub fn burndown_to_text(&self, style: ChartStyle) -> String {
+        let remaining = self.remaining();
+        let ideal = self.ideal();
+        let max = remaining.iter().copied().max().unwrap_or(0).max(1) as f64;
+
+        let mut text = format!("{}\n\n", self.title("Burndown"));
+        for (((day, _), remaining), ideal) in self.days.iter().zip(&remaining).zip(&ideal) {
+            let mut bar = [' '; TEXT_WIDTH + 1];
+            let length = scale(*remaining as f64, max, TEXT_WIDTH);
+            bar.iter_mut().take(length).for_each(|cell| *cell = style.fills()[0]);
+            bar[scale(*ideal, max, TEXT_WIDTH).min(TEXT_WIDTH)] = style.marker();
+
+            text += &format!("{} {} {:>4}\n", day.format("%a %Y-%m-%d"), bar.iter().collect::<String>(), remaining);
+        }
+
+        text += &format!("\n{} open tasks   {} ideal", style.fills()[0], style.marker());
+        text
+    }
+
+    pub fn cfd_to_text(&self, style: ChartStyle) -> String {
+        let order = self.stacking_order();
+        let max = self.days.iter().map(|(_, counts)| counts.iter().sum::<usize>()).max().unwrap_or(0).max(1) as f64;
+        let fill = |position: usize| style.fills()[position % style.fills().len()];
+
+        let mut text = format!("{}\n\n", self.title("Cumulative flow"));
+        for (day, counts) in &self.days {
+            // drawing each status from where the previous one ended, so that rounding doesn't add up
+            let mut bar = String::new();
+            let mut cumulative = 0;
+            for (position, index) in order.iter().enumerate() {
+                let start = scale(cumulative as f64, max, TEXT_WIDTH);
+                cumulative += counts[*index];
+                let end = scale(cumulative as f64, max, TEXT_WIDTH);
+                bar.extend(std::iter::repeat_n(fill(position), end - start));
+            }
+
+            text += &format!("{} {:<width$} {:>4}\n", day.format("%a %Y-%m-%d"), bar, cumulative, width = TEXT_WIDTH);
+        }
+
+        let legend: Vec<String> = order.iter()
+            .enumerate()
+            .map(|(position, index)| format!("{} {}", fill(position), self.statuses[*index].status))
+            .collect();
+        text += &format!("\n{}", legend.join("   "));
+        text
+    }
+
+    pub fn burndown_to_svg(&self) -> String {
+        let remaining: Vec<f64> = self.remaining().into_iter().map(|count| count as f64).collect();
+        let max = remaining.iter().copied().fold(1.0, f64::max);
+
+        let mut svg = self.svg_frame(max);
+        svg += &format!(
+            "  <polyline fill=\"none\" stroke=\"#8c8c8c\" stroke-dasharray=\"6 4\" points=\"{}\"/>\n",
+            self.svg_points(&self.ideal(), max),
+        );
+        svg += &format!(
+            "  <polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" points=\"{}\"/>\n",
+            SVG_COLORS[0],
+            self.svg_points(&remaining, max),
+        );
+        svg += &format!("  <text x=\"{SVG_MARGIN}\" y=\"20\">{}</text>\n</svg>\n", self.title("Burndown"));
+        svg
+    }
+
+    pub fn cfd_to_svg(&self) -> String {
+        let order = self.stacking_order();
+        let max = self.days.iter().map(|(_, counts)| counts.iter().sum::<usize>()).max().unwrap_or(0).max(1) as f64;
+
+        let mut svg = self.svg_frame(max);
+        let mut lower = vec![0.0; self.days.len()];
+        for (position, index) in order.iter().enumerate() {
+            let upper: Vec<f64> = self.days.iter().zip(&lower).map(|((_, counts), lower)| lower + counts[*index] as f64).collect();
+
+            // the band between the statuses below and this one on top of them
+            let count = self.days.len();
+            let top = upper.iter().enumerate().map(|(day, value)| svg_point(day, count, *value, max));
+            let bottom = lower.iter().enumerate().rev().map(|(day, value)| svg_point(day, count, *value, max));
+            let points = top.chain(bottom).collect::<Vec<_>>().join(" ");
+
+            svg += &format!(
+                "  <polygon fill=\"{}\" stroke=\"none\" points=\"{}\"><title>{}</title></polygon>\n",
+                SVG_COLORS[position % SVG_COLORS.len()],
+                points,
+                self.statuses[*index].status,
+            );
+            lower = upper;
+        }
+        svg += &format!("  <text x=\"{SVG_MARGIN}\" y=\"20\">{}</text>\n</svg>\n", self.title("Cumulative flow"));
+        svg
+    }
+
+    fn title(&self, name: &str) -> String {
+        let name = match &self.project {
+            Some(project) => format!("{name} of {project}"),
+            None => name.to_string(),
+        };
+        match (self.days.first(), self.days.last()) {
+            (Some((from, _)), Some((to, _))) => format!("{name} from {} to {}", from.format("%a %Y-%m-%d"), to.format("%a %Y-%m-%d")),
+            _ => name,
+        }
+    }
+
+    // closed statuses at the bottom, as the work flows down into them
+    fn stacking_order(&self) -> Vec<usize> {
+        let closed = (0..self.statuses.len()).filter(|index| self.statuses[*index].closed);
+        let open = (0..self.statuses.len()).rev().filter(|index| !self.statuses[*index].closed);
+        closed.chain(open).collect()
+    }
+
+    fn svg_frame(&self, max: f64) -> String {
+        let bottom = SVG_HEIGHT - SVG_MARGIN;
+        let right = SVG_WIDTH - SVG_MARGIN;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{SVG_WIDTH}\" height=\"{SVG_HEIGHT}\" font-family=\"sans-serif\" font-size=\"12\">\n"
+        );
+        svg += &format!("  <line x1=\"{SVG_MARGIN}\" y1=\"{bottom}\" x2=\"{right}\" y2=\"{bottom}\" stroke=\"black\"/>\n");
+        svg += &format!("  <line x1=\"{SVG_MARGIN}\" y1=\"{SVG_MARGIN}\" x2=\"{SVG_MARGIN}\" y2=\"{bottom}\" stroke=\"black\"/>\n");
+        svg += &format!("  <text x=\"{}\" y=\"{}\" text-anchor=\"end\">{max}</text>\n", SVG_MARGIN - 4.0, SVG_MARGIN + 4.0);
+        if let (Some((from, _)), Some((to, _))) = (self.days.first(), self.days.last()) {
+            svg += &format!("  <text x=\"{SVG_MARGIN}\" y=\"{}\">{from}</text>\n", bottom + 16.0);
+            svg += &format!("  <text x=\"{right}\" y=\"{}\" text-anchor=\"end\">{to}</text>\n", bottom + 16.0);
+        }
+        svg
+    }
+
+    fn svg_points(&self, values: &[f64], max: f64) -> String {
+        values
+            .iter()
+            .enumerate()
+            .map(|(day, value)| svg_point(day, self.days.len(), *value, max))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn svg_point(day: usize, days: usize, value: f64, max: f64) -> String {
+    let step = (SVG_WIDTH - 2.0 * SVG_MARGIN) / days.saturating_sub(1).max(1) as f64;
+    let x = SVG_MARGIN + day as f64 * step;
+    let y = SVG_HEIGHT - SVG_MARGIN - value / max * (SVG_HEIGHT - 2.0 * SVG_MARGIN);
+    format!("{x:.1},{y:.1}")
+}
+
+fn status_at(created_at: Option<DateTime<Local>>, current: &TaskStatus, changes: &[&StatusChange], at: DateTime<Local>) -> Option<TaskStatus> {
+    if created_at.is_some_and(|created_at| created_at >= at) {
+        return None;
+    }
+
+    match changes.iter().rev().find(|change| change.changed_at < at) {
+        Some(change) => Some(change.status.clone()),
+        // a task starts as undone, and the ones older than the history just have their current status
+        None if !changes.is_empty() => Some(TaskStatus::Undone),
+        None => Some(current.clone()),
+    }
+}
+
+fn scale(value: f64, max: f64, width: usize) -> usize {
+    (value / max * width as f64).round().max(0.0) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Days, Local};
+
+    use crate::manager::{task::TaskStatus, TaskManager};
+
+    use super::{ChartStyle, FlowChart};
+
+    fn set_up_manager() -> TaskManager {
+        let manager = TaskManager::open_in_memory()
+            .expect("unable to create in-memory database for testing purpuses");
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.get_task("task A").unwrap().set_status(&TaskStatus::Done);
+        manager
+    }
+
+    #[test]
+    fn test_build() {
+        let manager = set_up_manager();
+        let today = Local::now().date_naive();
+
+        // the tasks didn't exist yesterday
+        let chart = FlowChart::build(&manager, today - Days::new(1), today, None).unwrap();
+        assert_eq!(2, chart.days.len());
+        assert_eq!(vec![0, 1], chart.remaining());
+        assert_eq!(0, chart.days[0].1.iter().sum::<usize>());
+        assert_eq!(2, chart.days[1].1.iter().sum::<usize>());
+
+        assert!(FlowChart::build(&manager, today, today - Days::new(1), None).is_err());
+
+        // only the tasks of the project count
+        let _ = manager.get_task("task B").unwrap().set_project(Some("website"));
+        let chart = FlowChart::build(&manager, today, today, Some("website")).unwrap();
+        assert_eq!(vec![1], chart.remaining());
+        assert_eq!(1, chart.days[0].1.iter().sum::<usize>());
+        assert!(chart.burndown_to_text(ChartStyle::Ascii).starts_with("Burndown of website from"));
+    }
+
+    #[test]
+    fn test_render() {
+        let manager = set_up_manager();
+        let today = Local::now().date_naive();
+        let chart = FlowChart::build(&manager, today, today, None).unwrap();
+
+        assert!(chart.burndown_to_text(ChartStyle::Ascii).contains('#'));
+        assert!(chart.cfd_to_text(ChartStyle::Unicode).contains("█ done"));
+        assert!(chart.cfd_to_svg().starts_with("<svg"));
+        assert!(chart.burndown_to_svg().contains("<polyline"));
+    }
+}
@@ -1,10 +1,25 @@
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
-use crate::manager::task::TaskStatus;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::manager::task::{self, Priority, TaskStatus};
+use crate::store::StoreKind;
 
 #[derive(Parser, Debug)]
 #[command(name = "peppertodo (td)", version, about, long_about = None)]
 pub struct Cli {
+    /// The path of the database to use, overriding any profile
+    #[arg(long = "db", global = true, conflicts_with = "profile")]
+    pub database: Option<PathBuf>,
+
+    /// The profile whose task list should be used
+    #[arg(long = "profile", global = true)]
+    pub profile: Option<String>,
+
+    /// Where to keep the tasks from now on, by default where they were kept last time
+    #[arg(long = "store", global = true)]
+    pub store: Option<StoreKind>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -16,62 +31,670 @@ pub enum Commands {
     #[command(alias = "+")]
     Add {
         /// The title of the new task to add
-        #[arg(required = true)]
+        #[arg(required_unless_present = "editor")]
         #[arg(short = 't', long = "title")]
-        title: String,
+        title: Option<String>,
 
         /// The description of the new task to add
-        #[arg(required = true)]
+        #[arg(required_unless_present = "editor")]
         #[arg(short = 'd', long = "description")]
-        description: String,
+        description: Option<String>,
+
+        /// How long the task is expected to take (e.g. 2h, 1d)
+        #[arg(short = 'e', long = "estimate")]
+        estimate: Option<String>,
+
+        /// How many story points the task is worth
+        #[arg(short = 'p', long = "points")]
+        points: Option<u32>,
+
+        /// When the task is due (e.g. friday, 2026-11-02, "2026-11-02 18:00")
+        #[arg(long = "due")]
+        due: Option<String>,
+
+        /// How important the task is (high, medium or low)
+        #[arg(long = "priority", value_parser = parse_priority)]
+        priority: Option<Priority>,
+
+        /// The project the task belongs to
+        #[arg(long = "project", value_parser = parse_project)]
+        project: Option<String>,
+
+        /// A tag of the new task (can be repeated)
+        #[arg(long = "tag", value_parser = parse_tag)]
+        tags: Vec<String>,
+
+        /// The title of a task to close before this one (can be repeated)
+        #[arg(long = "depends-on")]
+        depends_on: Vec<String>,
+
+        /// Writes the task in $VISUAL or $EDITOR, starting from the other options
+        #[arg(long = "editor")]
+        editor: bool,
     },
 
     /// Lists all tasks
     #[command(alias = "ls")]
     List {
         /// If specified, only the tasks with a certain status will be displayed
-        filter: Option<TaskStatus>
+        #[arg(value_parser = parse_status)]
+        filter: Option<TaskStatus>,
+
+        /// Also lists the cancelled tasks
+        #[arg(short = 'a', long = "all")]
+        all: bool,
+
+        /// Lists the snoozed tasks, still waiting for their date
+        #[arg(short = 'w', long = "waiting")]
+        waiting: bool,
+
+        /// How the tasks should be sorted
+        #[arg(short = 's', long = "sort", value_enum, default_value_t = SortOrder::Status)]
+        sort: SortOrder,
+    },
+
+    /// Shows the most urgent tasks that are ready to be worked on (not waiting for other tasks)
+    Next {
+        /// How many tasks to show
+        #[arg(short = 'n', long = "limit", default_value_t = 5)]
+        limit: usize,
+    },
+
+    /// Explains how the urgency of the specified task is computed
+    Urgency {
+        /// The title of the task
+        #[arg(required = true)]
+        #[arg(short = 't', long = "title")]
+        target: String,
+    },
+
+    /// Shows the specified task in detail, with its description rendered as Markdown
+    Show {
+        /// The title of the task
+        #[arg(required = true)]
+        target: String,
+    },
+
+    /// Checks (or unchecks) an item of the `- [ ]` checklist in the specified task's description
+    Check {
+        /// The title of the task
+        #[arg(required = true)]
+        target: String,
+
+        /// The number of the item, as shown by `show`
+        #[arg(required = true)]
+        number: usize,
+    },
+
+    /// Writes a timestamped note on the specified task, listed by `show`
+    Note {
+        /// The title of the task
+        #[arg(required = true)]
+        target: String,
+
+        /// The text of the note
+        #[arg(required_unless_present = "delete", conflicts_with = "delete")]
+        text: Option<String>,
+
+        /// Replaces the text of a note, by its number as shown by `show`
+        #[arg(long = "edit", requires = "text")]
+        edit: Option<usize>,
+
+        /// Deletes a note, by its number as shown by `show`
+        #[arg(long = "delete", conflicts_with = "edit")]
+        delete: Option<usize>,
+    },
+
+    /// Attaches a file (copied into the application's folder, unless linked) or a URL to the specified task
+    Attach {
+        /// The title of the task
+        #[arg(required = true)]
+        target: String,
+
+        /// The file to attach
+        #[arg(required_unless_present = "url", conflicts_with = "url")]
+        file: Option<PathBuf>,
+
+        /// The URL to attach (e.g. https://example.com)
+        #[arg(long = "url")]
+        url: Option<String>,
+
+        /// Refers to the file where it is, instead of keeping a copy
+        #[arg(long = "link", conflicts_with = "url")]
+        link: bool,
+    },
+
+    /// Lists the files and URLs attached to the specified task
+    Attachments {
+        /// The title of the task
+        #[arg(required = true)]
+        target: String,
+    },
+
+    /// Opens an attachment of the specified task with the system's default application
+    Open {
+        /// The title of the task
+        #[arg(required = true)]
+        target: String,
+
+        /// The number of the attachment, as listed by `attachments` (only needed when there are several)
+        number: Option<usize>,
     },
 
     /// Marks (edits) the specified task's status
     #[command(alias = "!")]
     Mark {
-        /// The title of the task to mark
+        /// The title of the task to mark (chosen interactively if omitted)
+        #[arg(short = 't', long = "title", conflicts_with = "filter")]
+        target: Option<String>,
+
+        /// The new status of the task
+        #[arg(required = true, value_parser = parse_status)]
+        status: TaskStatus,
+
+        #[command(flatten)]
+        bulk: BulkArgs,
+    },
+
+    /// Cancels the specified task, meaning it won't be done
+    Cancel {
+        /// The title of the task to cancel
         #[arg(required = true)]
         #[arg(short = 't', long = "title")]
         target: String,
 
-        /// The new status of the task
+        /// Why the task won't be done
+        #[arg(short = 'r', long = "reason")]
+        reason: Option<String>,
+    },
+
+    /// Hides the specified task from the list until a date (e.g. `until monday`)
+    Snooze {
+        /// The title of the task to snooze
         #[arg(required = true)]
-        status: TaskStatus,
+        target: String,
+
+        /// When the task should be back (e.g. tomorrow, friday, 2026-11-02)
+        #[arg(required_unless_present = "wait", conflicts_with = "wait")]
+        until: Vec<String>,
+
+        /// How long the task should wait (e.g. 3d, 2w, 4h)
+        #[arg(long = "wait")]
+        wait: Option<String>,
+    },
+
+    /// Starts tracking the time spent on the specified task, marking it as underway
+    Start {
+        /// The title of the task to work on
+        #[arg(required = true)]
+        target: String,
+    },
+
+    /// Stops the running timer
+    Stop,
+
+    /// Shows the running timer, if any
+    Status,
+
+    /// Records time spent on the specified task
+    Track {
+        /// The title of the task worked on
+        #[arg(required = true)]
+        target: String,
+
+        /// How long the task has been worked on (e.g. 1h30m)
+        #[arg(required = true)]
+        duration: String,
+
+        /// When the work started (e.g. yesterday, "2026-11-02 09:00"), otherwise it just ended
+        #[arg(long = "at")]
+        at: Option<String>,
+    },
+
+    /// Works on the specified task for a pomodoro (25 minutes), followed by a break (5 minutes)
+    Pomodoro {
+        /// The title of the task to work on
+        #[arg(required = true)]
+        target: String,
+
+        /// How many pomodoros to run in a row
+        #[arg(short = 'n', long = "rounds", default_value_t = 1)]
+        rounds: usize,
     },
 
     /// Edits the specified task's fileds (title and description)
     #[command(alias = "ed")]
     Edit {
-        /// The title of the target task
-        #[arg(required = true)]
-        #[arg(long = "target")]
-        target: String,
+        /// The title of the target task (chosen interactively if omitted)
+        #[arg(long = "target", conflicts_with = "filter")]
+        target: Option<String>,
 
         /// The new title of the task
-        #[arg(short = 't', long = "title")]
+        #[arg(short = 't', long = "title", conflicts_with = "filter")]
         title: Option<String>,
 
         /// The new description of the task
         #[arg(short = 'd', long = "description")]
         description: Option<String>,
+
+        /// The new estimate of the task (0 to remove it)
+        #[arg(short = 'e', long = "estimate")]
+        estimate: Option<String>,
+
+        /// The new story points of the task (0 to remove them)
+        #[arg(short = 'p', long = "points")]
+        points: Option<u32>,
+
+        /// The new due date of the task ("none" to remove it)
+        #[arg(long = "due")]
+        due: Option<String>,
+
+        /// The new priority of the task (high, medium, low or "none" to remove it)
+        #[arg(long = "priority")]
+        priority: Option<String>,
+
+        /// The new project of the task ("none" to remove it)
+        #[arg(long = "project")]
+        project: Option<String>,
+
+        /// Adds a tag to the task (can be repeated)
+        #[arg(long = "add-tag", value_parser = parse_tag)]
+        add_tags: Vec<String>,
+
+        /// Removes a tag from the task (can be repeated)
+        #[arg(long = "remove-tag", value_parser = parse_tag)]
+        remove_tags: Vec<String>,
+
+        /// The title of a task to close before this one (can be repeated)
+        #[arg(long = "add-dependency")]
+        add_dependencies: Vec<String>,
+
+        /// The title of a task this one doesn't depend on anymore (can be repeated)
+        #[arg(long = "remove-dependency")]
+        remove_dependencies: Vec<String>,
+
+        /// Edits the task in $VISUAL or $EDITOR, after applying the other options
+        #[arg(long = "editor", conflicts_with = "filter")]
+        editor: bool,
+
+        #[command(flatten)]
+        bulk: BulkArgs,
     },
 
     /// Deletes the specified task
     #[command(alias = "del")]
     Delete {
-        /// The title of the task to delete
-        #[arg(required = true)]
-        target: String,
+        /// The title of the task to delete (chosen interactively if omitted)
+        #[arg(conflicts_with = "filter")]
+        target: Option<String>,
+
+        #[command(flatten)]
+        bulk: BulkArgs,
     },
 
-    /// Deletes all the tasks marked as done
+    /// Deletes all the tasks marked as done (or with any other closed status)
     #[command(alias = "cls")]
-    Clear
+    Clear {
+        /// Also deletes the cancelled tasks
+        #[arg(long = "cancelled")]
+        cancelled: bool,
+    },
+
+    /// Summarizes how many tasks there are for each status
+    Summary,
+
+    /// Opens a full-screen terminal interface to browse and edit the tasks
+    Tui,
+
+    /// Shows the tasks in side-by-side columns, one per status
+    Board {
+        /// Also shows the cancelled tasks
+        #[arg(short = 'a', long = "all")]
+        all: bool,
+
+        /// The width of the board, the terminal's by default
+        #[arg(short = 'w', long = "width")]
+        width: Option<usize>,
+    },
+
+    /// Shows a month calendar with the open tasks on their due days
+    Calendar {
+        /// The month to show (e.g. 2026-11), the current one by default
+        #[arg(short = 'm', long = "month")]
+        month: Option<String>,
+
+        /// Lists the titles of the tasks due in the month, under the calendar
+        #[arg(long = "titles")]
+        titles: bool,
+    },
+
+    /// Lists the open tasks due in the next days, and the overdue ones
+    Agenda {
+        /// How many days to list, today included
+        #[arg(short = 'n', long = "days", default_value_t = 7)]
+        days: u64,
+    },
+
+    /// Shows statistics about the task list (e.g. tasks created and completed, lead time)
+    Stats {
+        /// Counts the created and completed tasks per week, instead of per day
+        #[arg(short = 'w', long = "weekly")]
+        weekly: bool,
+
+        /// How many days (or weeks) to count the created and completed tasks for
+        #[arg(short = 'n', long = "periods", default_value_t = 7)]
+        periods: usize,
+
+        /// The format of the statistics
+        #[arg(short = 'f', long = "format", value_enum, default_value_t = StatsFormat::Text)]
+        format: StatsFormat,
+    },
+
+    /// Manages the statuses a task can have and how it can move between them
+    Workflow {
+        #[command(subcommand)]
+        command: WorkflowCommands,
+    },
+
+    /// Generates reports (e.g. a timesheet of the tracked time)
+    Report {
+        #[command(subcommand)]
+        command: ReportCommands,
+    },
+
+    /// Draws charts of how the tasks moved through their statuses
+    Chart {
+        #[command(subcommand)]
+        command: ChartCommands,
+    },
+
+    /// Manages the settings of the task list (e.g. the urgency coefficients)
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// Creates a local task list in the current directory
+    Init,
+
+    /// Prints which task list (database) is in use
+    Where,
+
+    /// Manages the profiles, each one with its own task list
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommands,
+    },
+}
+
+#[derive(Debug, Clone, Default, Args)]
+pub struct BulkArgs {
+    /// Acts on every task matching a filter instead (e.g. "status:underway due-before:friday title:docs")
+    #[arg(long = "where")]
+    pub filter: Option<String>,
+
+    /// Only lists the tasks that would change
+    #[arg(long = "dry-run", requires = "filter")]
+    pub dry_run: bool,
+
+    /// Changes the tasks without asking for confirmation
+    #[arg(short = 'y', long = "yes", requires = "filter")]
+    pub yes: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortOrder {
+    /// By status, in the order defined by the workflow
+    Status,
+    /// By urgency, the most urgent first
+    Urgency,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// A human readable report
+    Text,
+    /// Comma separated values
+    Csv,
+    /// A JSON document
+    Json,
+    /// A Markdown table
+    Markdown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatsFormat {
+    /// Human readable tables
+    Text,
+    /// A JSON document
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ChartCommands {
+
+    /// Draws how many open tasks were left each day, against an ideal line down to none
+    Burndown {
+        /// Only draws the tasks of this project
+        #[arg(long = "project", value_parser = parse_project)]
+        project: Option<String>,
+
+        /// The first day of the chart (two weeks ago by default)
+        #[arg(long = "from")]
+        from: Option<String>,
+
+        /// The last day of the chart, included (today by default)
+        #[arg(long = "to")]
+        to: Option<String>,
+
+        /// Draws the chart with plain ASCII characters
+        #[arg(long = "ascii")]
+        ascii: bool,
+
+        /// Also writes the chart to the given SVG file
+        #[arg(long = "svg")]
+        svg: Option<PathBuf>,
+    },
+
+    /// Draws how many tasks were in each status, each day (cumulative flow diagram)
+    Cfd {
+        /// Only draws the tasks of this project
+        #[arg(long = "project", value_parser = parse_project)]
+        project: Option<String>,
+
+        /// The first day of the chart (two weeks ago by default)
+        #[arg(long = "from")]
+        from: Option<String>,
+
+        /// The last day of the chart, included (today by default)
+        #[arg(long = "to")]
+        to: Option<String>,
+
+        /// Draws the chart with plain ASCII characters
+        #[arg(long = "ascii")]
+        ascii: bool,
+
+        /// Also writes the chart to the given SVG file
+        #[arg(long = "svg")]
+        svg: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReportCommands {
+
+    /// Reports the time tracked per day and per task (the current week by default)
+    Time {
+        /// The first day of the report (e.g. monday, 2026-11-02)
+        #[arg(long = "from")]
+        from: Option<String>,
+
+        /// The last day of the report, included (today by default)
+        #[arg(long = "to")]
+        to: Option<String>,
+
+        /// The format of the report
+        #[arg(short = 'f', long = "format", value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+
+    /// Reports the estimated effort still needed by the open tasks
+    Effort,
+
+    /// Compares the estimates of the closed tasks with the time actually tracked on them
+    Accuracy,
+
+    /// Reports how many pomodoros have been completed (or interrupted) on each task
+    Pomodoro,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommands {
+
+    /// Lists all the settings that have been set
+    #[command(alias = "ls")]
+    List,
+
+    /// Prints the value of a setting
+    Get {
+        /// The name of the setting (e.g. urgency.age)
+        #[arg(required = true)]
+        key: String,
+    },
+
+    /// Sets the value of a setting
+    Set {
+        /// The name of the setting (e.g. urgency.age)
+        #[arg(required = true)]
+        key: String,
+
+        /// The new value of the setting
+        #[arg(required = true, allow_hyphen_values = true)]
+        value: String,
+    },
+
+    /// Resets a setting to its default value
+    Unset {
+        /// The name of the setting (e.g. urgency.age)
+        #[arg(required = true)]
+        key: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WorkflowCommands {
+
+    /// Shows the statuses, in the order they are listed, and the allowed transitions
+    Show,
+
+    /// Adds a new status
+    Add {
+        /// The name of the new status
+        #[arg(required = true, value_parser = parse_status)]
+        status: TaskStatus,
+
+        /// Whether the tasks with this status are closed (e.g. cleared by `clear`)
+        #[arg(long = "closed")]
+        closed: bool,
+    },
+
+    /// Removes a status no task is marked with
+    #[command(alias = "rm")]
+    Remove {
+        /// The name of the status to remove
+        #[arg(required = true, value_parser = parse_status)]
+        status: TaskStatus,
+    },
+
+    /// Marks a status as closed
+    Close {
+        /// The name of the status to mark
+        #[arg(required = true, value_parser = parse_status)]
+        status: TaskStatus,
+    },
+
+    /// Marks a status as open
+    Reopen {
+        /// The name of the status to mark
+        #[arg(required = true, value_parser = parse_status)]
+        status: TaskStatus,
+    },
+
+    /// Sets how many tasks can be in a status at once, before a warning is shown
+    Limit {
+        /// The name of the status to limit
+        #[arg(required = true, value_parser = parse_status)]
+        status: TaskStatus,
+
+        /// The maximum number of tasks (0 to remove the limit)
+        #[arg(required = true)]
+        limit: u32,
+    },
+
+    /// Allows tasks to move from a status to another (from then on, only the allowed transitions are)
+    Allow {
+        /// The status tasks move from
+        #[arg(required = true, value_parser = parse_status)]
+        from: TaskStatus,
+
+        /// The status tasks move to
+        #[arg(required = true, value_parser = parse_status)]
+        to: TaskStatus,
+    },
+
+    /// Forbids tasks to move from a status to another
+    Forbid {
+        /// The status tasks move from
+        #[arg(required = true, value_parser = parse_status)]
+        from: TaskStatus,
+
+        /// The status tasks move to
+        #[arg(required = true, value_parser = parse_status)]
+        to: TaskStatus,
+    },
+
+    /// Lets tasks move freely between statuses again, forgetting the allowed transitions
+    Free,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProfileCommands {
+
+    /// Creates a new profile
+    Create {
+        /// The name of the new profile
+        #[arg(required = true)]
+        name: String,
+
+        /// The database file of the profile (defaults to one in the app's folder)
+        #[arg(long = "path")]
+        path: Option<PathBuf>,
+    },
+
+    /// Lists all the profiles, marking the one in use
+    #[command(alias = "ls")]
+    List,
+
+    /// Sets the profile to use when none is specified
+    Use {
+        /// The name of the profile to use ("default" for the default one)
+        #[arg(required = true)]
+        name: String,
+    },
+}
+
+fn parse_status(status: &str) -> Result<TaskStatus, String> {
+    status.parse().map_err(|error: Box<dyn std::error::Error>| error.to_string())
+}
+
+fn parse_priority(priority: &str) -> Result<Priority, String> {
+    priority.parse().map_err(|error: Box<dyn std::error::Error>| error.to_string())
+}
+
+fn parse_project(project: &str) -> Result<String, String> {
+    task::parse_project(project).map_err(|error| error.to_string())
+}
+
+fn parse_tag(tag: &str) -> Result<String, String> {
+    task::parse_tag(tag).map_err(|error| error.to_string())
 }
@@ -0,0 +1,182 @@
+use std::error::Error;
+
+use chrono::{DateTime, Datelike, Days, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+
+pub fn parse_date(input: &str, now: DateTime<Local>) -> Result<DateTime<Local>, Box<dyn Error>> {
+    let input = input.trim().to_lowercase();
+    let today = now.date_naive();
+
+    let date = match input.as_str() {
+        "now" => return Ok(now),
+        "today" => today,
+        "tomorrow" => today + Days::new(1),
+        "yesterday" => today - Days::new(1),
+        _ => {
+            if let Ok(weekday) = input.parse::<Weekday>() {
+                // the next occurrence of that day, never today
+                let days_ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday() - 1) % 7 + 1;
+                today + Days::new(days_ahead as u64)
+            } else if let Ok(date) = NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+                date
+            } else if let Ok(date_time) = NaiveDateTime::parse_from_str(&input, "%Y-%m-%d %H:%M") {
+                return from_naive(date_time);
+            } else if let Ok(duration) = parse_duration(input.trim_start_matches("in ")) {
+                return after(now, duration);
+            } else {
+                return Err(Box::<dyn std::error::Error>::from(format!("unable to understand the date '{input}'")))
+            }
+        }
+    };
+
+    from_naive(date.and_time(NaiveTime::MIN))
+}
+
+pub fn parse_duration(input: &str) -> Result<Duration, Box<dyn Error>> {
+    let input = input.trim();
+    let invalid = || Box::<dyn std::error::Error>::from(format!("unable to understand the duration '{input}'"));
+
+    if input.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+
+        let amount: i64 = digits.parse().map_err(|_| invalid())?;
+        digits.clear();
+
+        // amounts too large to be a duration are as invalid as unknown units
+        let duration = match c {
+            'w' => Duration::try_weeks(amount),
+            'd' => Duration::try_days(amount),
+            'h' => Duration::try_hours(amount),
+            'm' => Duration::try_minutes(amount),
+            's' => Duration::try_seconds(amount),
+            _ => return Err(invalid()),
+        };
+        total = duration.and_then(|duration| total.checked_add(&duration)).ok_or_else(invalid)?;
+    }
+
+    // every amount must be followed by its unit
+    if !digits.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(total)
+}
+
+// the date `duration` after `date`, unless that's further than dates go
+pub fn after(date: DateTime<Local>, duration: Duration) -> Result<DateTime<Local>, Box<dyn Error>> {
+    date.checked_add_signed(duration)
+        .ok_or_else(|| Box::<dyn std::error::Error>::from(format!("{} is too far away", format_duration(duration))))
+}
+
+// the date `duration` before `date`, unless that's further than dates go
+pub fn before(date: DateTime<Local>, duration: Duration) -> Result<DateTime<Local>, Box<dyn Error>> {
+    date.checked_sub_signed(duration)
+        .ok_or_else(|| Box::<dyn std::error::Error>::from(format!("{} is too far away", format_duration(duration))))
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h{minutes:02}m"),
+    }
+}
+
+pub fn from_timestamp(timestamp: i64) -> DateTime<Local> {
+    DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .with_timezone(&Local)
+}
+
+pub fn format_timestamp(timestamp: i64) -> String {
+    let date_time = from_timestamp(timestamp);
+    match date_time.time() == NaiveTime::MIN {
+        true => date_time.format("%a %Y-%m-%d").to_string(),
+        false => date_time.format("%a %Y-%m-%d %H:%M").to_string(),
+    }
+}
+
+pub fn from_naive(date_time: NaiveDateTime) -> Result<DateTime<Local>, Box<dyn Error>> {
+    Local
+        .from_local_datetime(&date_time)
+        .earliest()
+        .ok_or_else(|| Box::<dyn std::error::Error>::from("the given date doesn't exist in the local time zone"))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Weekday};
+
+    use super::{after, before, format_duration, parse_date, parse_duration};
+
+    fn now() -> DateTime<Local> {
+        // a wednesday
+        Local.with_ymd_and_hms(2026, 10, 21, 15, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_date_keywords() {
+        assert_eq!(now(), parse_date("now", now()).unwrap());
+        assert_eq!(22, parse_date("tomorrow", now()).unwrap().day());
+        assert_eq!(20, parse_date("Yesterday", now()).unwrap().day());
+    }
+
+    #[test]
+    fn test_parse_date_weekdays() {
+        let monday = parse_date("monday", now()).unwrap();
+        assert_eq!((Weekday::Mon, 26), (monday.weekday(), monday.day()));
+
+        // the same weekday means next week
+        assert_eq!(28, parse_date("wednesday", now()).unwrap().day());
+    }
+
+    #[test]
+    fn test_parse_date_absolute_and_relative() {
+        let date = parse_date("2026-11-02", now()).unwrap();
+        assert_eq!((11, 2), (date.month(), date.day()));
+        assert_eq!(now() + Duration::days(3), parse_date("3d", now()).unwrap());
+        assert_eq!(now() + Duration::hours(2), parse_date("in 2h", now()).unwrap());
+        assert!(parse_date("someday", now()).is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(Duration::minutes(90), parse_duration("1h30m").unwrap());
+        assert_eq!(Duration::days(14), parse_duration("2w").unwrap());
+        assert!(parse_duration("12").is_err());
+        assert!(parse_duration("1x").is_err());
+        assert!(parse_duration("").is_err());
+
+        // amounts too large for a duration are refused rather than overflowing
+        assert!(parse_duration("99999999999999999w").is_err());
+        assert!(parse_duration("9223372036854775807s").is_err());
+        assert!(parse_duration("106751991167d106751991167d").is_err());
+    }
+
+    #[test]
+    fn test_dates_too_far_away() {
+        let now = Local::now();
+        let far = parse_duration("100000000w").unwrap();
+        assert!(after(now, far).is_err());
+        assert!(before(now, far).is_err());
+        assert!(parse_date("in 100000000w", now).is_err());
+        assert_eq!(now + Duration::days(2), after(now, Duration::days(2)).unwrap());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!("45m", format_duration(Duration::minutes(45)));
+        assert_eq!("2h", format_duration(Duration::hours(2)));
+        assert_eq!("1h05m", format_duration(Duration::minutes(65)));
+    }
+}
@@ -0,0 +1,288 @@
+use std::env;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::process::Command;
+
+use chrono::{DateTime, Duration, Local, NaiveTime};
+
+use crate::dates;
+use crate::manager::task::{parse_tag, Task, TaskStatus};
+use crate::manager::TaskManager;
+
+pub const DEFAULT_EDITOR: &str = "vi";
+const FRONT_MATTER: &str = "---";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskFile {
+    pub title: String,
+    pub status: TaskStatus,
+    pub due: Option<DateTime<Local>>,
+    pub estimate: Option<Duration>,
+    pub points: Option<u32>,
+    // sorted, without duplicates
+    pub tags: Vec<String>,
+    pub description: String,
+}
+
+impl TaskFile {
+    pub fn new(title: &str, description: &str) -> TaskFile {
+        TaskFile {
+            title: title.to_string(),
+            status: TaskStatus::Undone,
+            due: None,
+            estimate: None,
+            points: None,
+            tags: Vec::new(),
+            description: description.to_string(),
+        }
+    }
+
+    pub fn load(task: &Task) -> Result<TaskFile, Box<dyn Error>> {
+        Ok(TaskFile {
+            title: task.get_title()?,
+            status: task.get_status()?,
+            due: task.get_due()?,
+            estimate: task.get_estimate()?,
+            points: task.get_points()?,
+            tags: task.get_tags()?,
+            description: task.get_description()?,
+        })
+    }
+
+    pub fn to_text(&self) -> String {
+        let due = match self.due {
+            Some(due) if due.time() == NaiveTime::MIN => due.format("%Y-%m-%d").to_string(),
+            Some(due) => due.format("%Y-%m-%d %H:%M").to_string(),
+            None => String::new(),
+        };
+
+        format!(
+            "{FRONT_MATTER}\ntitle: {}\nstatus: {}\ndue: {due}\nestimate: {}\npoints: {}\ntags: {}\n{FRONT_MATTER}\n{}\n",
+            self.title,
+            self.status,
+            self.estimate.map(dates::format_duration).unwrap_or_default(),
+            self.points.map(|points| points.to_string()).unwrap_or_default(),
+            self.tags.join(", "),
+            self.description,
+        )
+    }
+
+    pub fn parse(text: &str, now: DateTime<Local>) -> Result<TaskFile, Box<dyn Error>> {
+        // the comments on top (e.g. the errors of the previous attempt) aren't part of the task
+        let mut lines = text.lines().skip_while(|line| line.starts_with('#') || line.trim().is_empty());
+        if lines.next().map(str::trim) != Some(FRONT_MATTER) {
+            return Err(Box::<dyn std::error::Error>::from(format!("the file must start with a '{FRONT_MATTER}' line")))
+        }
+
+        let mut file = TaskFile::new("", "");
+        let mut closed = false;
+        for line in lines.by_ref() {
+            if line.trim() == FRONT_MATTER {
+                closed = true;
+                break;
+            }
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| Box::<dyn std::error::Error>::from(format!("'{line}' isn't a 'field: value' line")))?;
+            let value = value.trim();
+            match key.trim() {
+                "title" => file.title = value.to_string(),
+                "status" => file.status = value.parse()?,
+                "due" => file.due = none_if_empty(value).map(|due| dates::parse_date(due, now)).transpose()?,
+                "estimate" => file.estimate = none_if_empty(value).map(dates::parse_duration).transpose()?,
+                "points" => file.points = none_if_empty(value)
+                    .map(|points| points.parse().map_err(|_| format!("'{points}' isn't a number of points")))
+                    .transpose()?,
+                "tags" => {
+                    file.tags = value.split([',', ' ']).filter(|tag| !tag.trim().is_empty()).map(parse_tag).collect::<Result<_, _>>()?;
+                    file.tags.sort();
+                    file.tags.dedup();
+                },
+                key => return Err(Box::<dyn std::error::Error>::from(format!("unknown field '{key}'"))),
+            }
+        }
+
+        if !closed {
+            return Err(Box::<dyn std::error::Error>::from(format!("the fields must end with a '{FRONT_MATTER}' line")))
+        }
+        if file.title.is_empty() {
+            return Err(Box::<dyn std::error::Error>::from("a task needs a title"))
+        }
+
+        file.description = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+        Ok(file)
+    }
+
+    pub fn check(&self, manager: &TaskManager, task: Option<&Task>) -> Result<(), Box<dyn Error>> {
+        // checking for title availability
+        if let Ok(other) = manager.get_task(&self.title) {
+            if task.is_none_or(|task| task.get_id() != other.get_id()) {
+                return Err(Box::<dyn std::error::Error>::from("a task with the same title already exists"))
+            }
+        }
+
+        let current = match task {
+            Some(task) => task.get_status()?,
+            None => TaskStatus::Undone,
+        };
+        manager.workflow().check_transition(&current, &self.status)?;
+
+        if self.estimate.is_some_and(|estimate| estimate <= Duration::zero()) {
+            return Err(Box::<dyn std::error::Error>::from("the estimate must be positive"))
+        }
+        Ok(())
+    }
+
+    pub fn apply(&self, task: &mut Task) -> Result<(), Box<dyn Error>> {
+        task.set_title(&self.title)?;
+        task.set_description(&self.description)?;
+        if task.get_status()? != self.status {
+            task.set_status(&self.status)?;
+        }
+        task.set_due(self.due)?;
+        task.set_estimate(self.estimate)?;
+        task.set_points(self.points.filter(|points| *points > 0))?;
+        task.set_tags(&self.tags)?;
+        Ok(())
+    }
+}
+
+pub fn editor_command() -> String {
+    env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or(DEFAULT_EDITOR.to_string())
+}
+
+// opening the text in the editor until it's accepted, or left as it was
+pub fn edit_until_valid<T>(editor: &str, text: &str, mut accept: impl FnMut(&str) -> Result<T, Box<dyn Error>>) -> Result<T, Box<dyn Error>> {
+    // never reusing a file that's already there, which someone else may be able to read or replace
+    let path = env::temp_dir().join(format!("peppertodo-{}-{}.md", std::process::id(), Local::now().timestamp_nanos_opt().unwrap_or_default()));
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .map_err(|error| format!("unable to create {}: {error}", path.display()))?;
+
+    let result = loop {
+        let before = fs::read_to_string(&path)?;
+        if let Err(error) = open_editor(editor, &path) {
+            break Err(error);
+        }
+        let after = fs::read_to_string(&path)?;
+
+        if after.trim().is_empty() {
+            break Err(Box::<dyn std::error::Error>::from("the file was emptied, nothing has been changed"));
+        }
+
+        match accept(&after) {
+            Ok(value) => break Ok(value),
+            // giving up when the file comes back untouched, instead of reopening it forever
+            Err(error) if after == before => break Err(error),
+            Err(error) => {
+                let body: String = after.lines()
+                    .skip_while(|line| line.starts_with('#'))
+                    .map(|line| format!("{line}\n"))
+                    .collect();
+                fs::write(&path, format!("# Error: {error}\n# Fix it and save, or close the editor without saving to give up\n{body}"))?;
+            }
+        }
+    };
+
+    let _ = fs::remove_file(&path);
+    result
+}
+
+fn open_editor(editor: &str, path: &std::path::Path) -> Result<(), Box<dyn Error>> {
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or(DEFAULT_EDITOR);
+
+    let status = Command::new(program).args(words).arg(path).status()
+        .map_err(|error| format!("unable to open the editor '{program}': {error}"))?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(Box::<dyn std::error::Error>::from(format!("the editor '{program}' exited with {status}"))),
+    }
+}
+
+fn none_if_empty(value: &str) -> Option<&str> {
+    Some(value).filter(|value| !value.is_empty() && *value != "none")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use crate::manager::{task::TaskStatus, TaskManager};
+
+    use super::{edit_until_valid, TaskFile};
+
+    #[test]
+    fn test_parse() {
+        let text = "# a comment\n---\ntitle: task\nstatus: done\ndue: 2026-11-02\nestimate: 1h30m\npoints:\ntags: release, Docs docs\n---\n\nfirst line\nsecond line\n";
+        let file = TaskFile::parse(text, Local::now()).unwrap();
+        assert_eq!("task", file.title);
+        assert_eq!(TaskStatus::Done, file.status);
+        assert_eq!("2026-11-02", file.due.unwrap().format("%Y-%m-%d").to_string());
+        assert_eq!(Some(Duration::minutes(90)), file.estimate);
+        assert_eq!(None, file.points);
+        assert_eq!(vec!["docs", "release"], file.tags);
+        assert_eq!("first line\nsecond line", file.description);
+
+        assert!(TaskFile::parse("title: task\n", Local::now()).is_err());
+        assert!(TaskFile::parse("---\ntitle: task\n", Local::now()).is_err());
+        assert!(TaskFile::parse("---\ntitle: task\ncolor: red\n---\n", Local::now()).is_err());
+        assert!(TaskFile::parse("---\ntitle:\n---\n", Local::now()).is_err());
+        assert!(TaskFile::parse("---\ntitle: task\ntags: not!valid\n---\n", Local::now()).is_err());
+    }
+
+    #[test]
+    fn test_to_text_and_back() {
+        let mut file = TaskFile::new("task", "a description\nover two lines");
+        file.estimate = Some(Duration::minutes(65));
+        file.points = Some(3);
+        file.tags = vec!["docs".to_string(), "release".to_string()];
+        assert_eq!(file, TaskFile::parse(&file.to_text(), Local::now()).unwrap());
+    }
+
+    #[test]
+    fn test_check_and_apply() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let mut task = manager.get_task("task A").unwrap();
+
+        let mut file = TaskFile::load(&task).unwrap();
+        file.title = "task B".to_string();
+        assert!(file.check(&manager, Some(&task)).is_err());
+
+        file.title = "task C".to_string();
+        file.status = TaskStatus::Underway;
+        file.tags = vec!["release".to_string()];
+        file.check(&manager, Some(&task)).unwrap();
+        file.apply(&mut task).unwrap();
+        assert_eq!("task C", task.get_title().unwrap());
+        assert_eq!(vec!["release"], task.get_tags().unwrap());
+        assert_eq!(TaskStatus::Underway, task.get_status().unwrap());
+    }
+
+    #[test]
+    fn test_edit_until_valid() {
+        let file = TaskFile::new("task", "desc");
+
+        // the editor is any command taking the path of the file as its last argument
+        let edited = edit_until_valid("sed -i s/undone/done/", &file.to_text(), |text| TaskFile::parse(text, Local::now())).unwrap();
+        assert_eq!(TaskStatus::Done, edited.status);
+
+        // an invalid edit, left as it is when reopened, gives up with its error
+        let result = edit_until_valid("sed -i s/undone/not!valid/", &file.to_text(), |text| TaskFile::parse(text, Local::now()));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use std::error::Error;
+
+use chrono::{DateTime, Local};
+
+use crate::dates;
+use crate::manager::task::{parse_project, parse_tag, Task};
+use crate::manager::{TaskManager, TaskQuery};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Search {
+    Title(String),
+    Text(String),
+}
+
+// a selection of tasks, written as space separated terms that must all hold
+// (e.g. `status:underway due-before:friday tag:release title:"the docs"`)
+#[derive(Debug, Clone)]
+pub struct TaskFilter {
+    query: TaskQuery,
+    searches: Vec<Search>,
+}
+
+impl TaskFilter {
+    pub fn parse(expression: &str, now: DateTime<Local>) -> Result<TaskFilter, Box<dyn Error>> {
+        let mut filter = TaskFilter { query: TaskQuery::default(), searches: Vec::new() };
+
+        for term in split_terms(expression)? {
+            match term.split_once(':') {
+                Some(("status", status)) => filter.query.status = Some(status.parse()?),
+                Some(("title", text)) => filter.searches.push(Search::Title(text.to_lowercase())),
+                Some(("text", text)) => filter.searches.push(Search::Text(text.to_lowercase())),
+                Some(("due-before", date)) => filter.query.due_to = Some(dates::parse_date(date, now)?),
+                Some(("due-after", date)) => filter.query.due_from = Some(dates::parse_date(date, now)?),
+                Some(("tag", tag)) => filter.query.tags.push(parse_tag(tag)?),
+                Some(("project", project)) => filter.query.project = Some(parse_project(project)?),
+                Some((key, _)) => return Err(Box::<dyn std::error::Error>::from(format!("unknown filter '{key}:'"))),
+                None => match term.as_str() {
+                    "open" => filter.query.only_open = true,
+                    "waiting" => filter.query.waiting = true,
+                    "all" => filter.query.include_cancelled = true,
+                    "overdue" => {
+                        filter.query.only_open = true;
+                        filter.query.due_to = Some(now);
+                    },
+                    term => return Err(Box::<dyn std::error::Error>::from(format!("unknown filter '{term}'"))),
+                },
+            }
+        }
+
+        Ok(filter)
+    }
+
+    pub fn select<'a>(&self, manager: &'a TaskManager) -> Result<Vec<Task<'a>>, Box<dyn Error>> {
+        let mut selected = Vec::new();
+        for task in manager.query_tasks(&self.query)? {
+            if self.matches(&task)? {
+                selected.push(task);
+            }
+        }
+        Ok(selected)
+    }
+
+    fn matches(&self, task: &Task) -> Result<bool, Box<dyn Error>> {
+        let title = task.get_title()?.to_lowercase();
+        let description = task.get_description()?.to_lowercase();
+        let notes: Vec<String> = task.get_notes()?.into_iter().map(|note| note.text.to_lowercase()).collect();
+
+        Ok(self.searches.iter().all(|search| match search {
+            Search::Title(text) => title.contains(text),
+            Search::Text(text) => title.contains(text) || description.contains(text) || notes.iter().any(|note| note.contains(text)),
+        }))
+    }
+}
+
+// splitting on spaces, except within double quotes
+fn split_terms(expression: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut terms = Vec::new();
+    let mut term = String::new();
+    let mut quoted = false;
+
+    for c in expression.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !term.is_empty() {
+                    terms.push(std::mem::take(&mut term));
+                }
+            },
+            c => term.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(Box::<dyn std::error::Error>::from("a quote isn't closed"))
+    }
+    if !term.is_empty() {
+        terms.push(term);
+    }
+    if terms.is_empty() {
+        return Err(Box::<dyn std::error::Error>::from("the filter is empty"))
+    }
+    Ok(terms)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use crate::manager::{task::TaskStatus, TaskManager};
+
+    use super::TaskFilter;
+
+    #[test]
+    fn test_select() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("write the docs", "for the release");
+        let _ = manager.add_new_task("review the docs", "before the release");
+        let _ = manager.add_new_task("fix the bug", "in the parser");
+        manager.get_task("review the docs").unwrap().set_status(&TaskStatus::Underway).unwrap();
+        manager.get_task("fix the bug").unwrap().set_due(Some(Local::now() - Duration::days(1))).unwrap();
+        manager.get_task("write the docs").unwrap().add_tag("release").unwrap();
+        manager.get_task("fix the bug").unwrap().add_tag("release").unwrap();
+        manager.get_task("fix the bug").unwrap().add_tag("parser").unwrap();
+        manager.get_task("fix the bug").unwrap().set_project(Some("core")).unwrap();
+        manager.get_task("fix the bug").unwrap().add_note("seen again in the 2.0 beta", Local::now()).unwrap();
+
+        let titles = |expression: &str| -> Vec<String> {
+            TaskFilter::parse(expression, Local::now()).unwrap()
+                .select(&manager).unwrap()
+                .iter()
+                .map(|task| task.get_title().unwrap())
+                .collect()
+        };
+        // the tasks come in the order of their statuses
+        assert_eq!(vec!["review the docs", "write the docs"], titles("title:docs"));
+        assert_eq!(vec!["review the docs"], titles("status:underway text:release"));
+        assert_eq!(vec!["write the docs"], titles(r#"title:"write the""#));
+        assert_eq!(vec!["fix the bug"], titles("text:beta"));
+        assert_eq!(vec!["fix the bug"], titles("overdue"));
+        assert_eq!(vec!["write the docs", "fix the bug"], titles("tag:release"));
+        assert_eq!(vec!["fix the bug"], titles("tag:release tag:parser"));
+        assert_eq!(vec!["fix the bug"], titles("project:core"));
+
+        assert!(TaskFilter::parse("", Local::now()).is_err());
+        assert!(TaskFilter::parse("tag:", Local::now()).is_err());
+        assert!(TaskFilter::parse("label:release", Local::now()).is_err());
+        assert!(TaskFilter::parse(r#"title:"docs"#, Local::now()).is_err());
+    }
+}
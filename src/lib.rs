@@ -1,45 +1,821 @@
-use std::{error::Error, fs, path::PathBuf};
+use std::{error::Error, fs, io::{self, IsTerminal, Write}, path::{Path, PathBuf}, process::Command};
 
-use cli::{Cli, Commands};
-use manager::TaskManager;
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+use board::Board;
+use chart::{ChartStyle, FlowChart};
+use cli::{BulkArgs, ChartCommands, Cli, Commands, ConfigCommands, ProfileCommands, ReportCommands, ReportFormat, SortOrder, StatsFormat, WorkflowCommands};
+use editor::TaskFile;
+use filter::TaskFilter;
+use location::default_database_path;
+use manager::{stats::Period, task::{self, Task, TaskStatus}, tracking::TimeEntry, urgency::UrgencyModel, TaskManager, TaskQuery};
+use profile::{ProfileManager, DEFAULT_PROFILE};
+use report::{EstimateRow, Rounding, TimeReport};
 
+pub mod board;
+pub mod calendar;
+pub mod chart;
 pub mod cli;
+pub mod dates;
+pub mod editor;
+pub mod filter;
+pub mod location;
 pub mod manager;
+pub mod markdown;
+pub mod picker;
+pub mod pomodoro;
+pub mod profile;
+pub mod report;
+pub mod store;
+pub mod tui;
 
-pub fn run(cli: &Cli, manager: &mut TaskManager) -> Result<(), Box<dyn Error>> {
+pub fn run(cli: &Cli, manager: &TaskManager) -> Result<(), Box<dyn Error>> {
     match &cli.command {
-        Commands::Add { title, description } => {
-            manager.add_new_task(title, description)
+        Commands::Add { title, description, estimate, points, due, priority, project, tags, depends_on, editor: true } => {
+            let mut file = TaskFile::new(title.as_deref().unwrap_or_default(), description.as_deref().unwrap_or_default());
+            file.estimate = estimate.as_deref().map(dates::parse_duration).transpose()?;
+            file.points = *points;
+            file.due = due.as_deref().map(|due| dates::parse_date(due, Local::now())).transpose()?;
+            file.tags = tags.iter().map(|tag| task::parse_tag(tag)).collect::<Result<_, _>>()?;
+            file.tags.sort();
+            file.tags.dedup();
+
+            let file = editor::edit_until_valid(&editor::editor_command(), &file.to_text(), |text| {
+                let file = TaskFile::parse(text, Local::now())?;
+                file.check(manager, None)?;
+                Ok(file)
+            })?;
+
+            manager.transaction(|manager| {
+                manager.add_new_task(&file.title, &file.description)?;
+                let mut task = manager.get_task(&file.title)?;
+                file.apply(&mut task)?;
+                if priority.is_some() {
+                    task.set_priority(*priority)?;
+                }
+                if project.is_some() {
+                    task.set_project(project.as_deref())?;
+                }
+                depends_on.iter().try_for_each(|title| task.add_dependency(&manager.get_task(title)?))
+            })
+        },
+        Commands::Add { title, description, estimate, points, due, priority, project, tags, depends_on, editor: false } => {
+            let (Some(title), Some(description)) = (title, description) else {
+                return Err(Box::<dyn std::error::Error>::from("a new task needs a title and a description"));
+            };
+            manager.transaction(|manager| {
+                manager.add_new_task(title, description)?;
+
+                // Setting the estimates, if provided
+                let mut task = manager.get_task(title)?;
+                if let Some(estimate) = estimate {
+                    task.set_estimate(Some(dates::parse_duration(estimate)?))?;
+                }
+                if let Some(points) = points {
+                    task.set_points(Some(*points))?;
+                }
+                if let Some(due) = due {
+                    task.set_due(Some(dates::parse_date(due, Local::now())?))?;
+                }
+                if priority.is_some() {
+                    task.set_priority(*priority)?;
+                }
+                if project.is_some() {
+                    task.set_project(project.as_deref())?;
+                }
+                for tag in tags {
+                    task.add_tag(tag)?;
+                }
+                for title in depends_on {
+                    task.add_dependency(&manager.get_task(title)?)?;
+                }
+
+                Ok(())
+            })
+        },
+        Commands::List { filter, all, waiting, sort } => {
+            let query = TaskQuery { status: filter.clone(), include_cancelled: *all, waiting: *waiting, ..Default::default() };
+            let tasks = manager.query_tasks(&query)?;
+            let tasks = match sort {
+                SortOrder::Status => tasks,
+                SortOrder::Urgency => manager.rank_by_urgency(tasks)?.into_iter().map(|(task, _)| task).collect(),
+            };
+            let now = Local::now();
+            for task in tasks {
+                println!("{}", task.summary(now)?);
+            }
+            Ok(())
+        },
+        Commands::Next { limit } => {
+            let query = TaskQuery { only_open: true, ..Default::default() };
+            let mut ready = Vec::new();
+            for task in manager.query_tasks(&query)? {
+                if !task.is_blocked()? {
+                    ready.push(task);
+                }
+            }
+            let now = Local::now();
+            for (task, urgency) in manager.rank_by_urgency(ready)?.iter().take(*limit) {
+                println!("{:>6.2} {}", urgency.score(), task.summary(now)?);
+            }
+            Ok(())
         },
-        Commands::List { filter } => {
-            manager.get_all_tasks(filter.as_ref())?.iter()
-                .for_each(|task| println!("{task}"));
+        Commands::Urgency { target } => {
+            let task = manager.get_task(target)?;
+            let model = UrgencyModel::load(manager.settings())?;
+            println!("{}", model.evaluate(&task, Local::now())?);
             Ok(())
         },
-        Commands::Mark { target, status } => {
+        Commands::Show { target } => {
+            let task = manager.get_task(target)?;
+            println!("{}", task_details(manager, &task, io::stdout().is_terminal())?);
+            Ok(())
+        },
+        Commands::Check { target, number } => {
             let mut task = manager.get_task(target)?;
-            task.set_status(status)
+            let (description, item) = markdown::toggle_item(&task.get_description()?, *number)?;
+            task.set_description(&description)?;
+
+            let mark = match item.checked {
+                true => "x",
+                false => " ",
+            };
+            println!("[{mark}] {}", item.text);
+            Ok(())
         },
-        Commands::Edit { target, title, description } => {
+        Commands::Note { target, text, edit, delete } => {
             let mut task = manager.get_task(target)?;
+            match (text, edit, delete) {
+                (_, _, Some(number)) => task.delete_note(*number),
+                (Some(text), Some(number), _) => task.edit_note(*number, text, Local::now()),
+                (Some(text), None, _) => task.add_note(text, Local::now()),
+                (None, _, None) => Err(Box::<dyn std::error::Error>::from("a note needs a text")),
+            }
+        },
+        Commands::Attachments { target } => {
+            let task = manager.get_task(target)?;
+            manager.attachments().get_attachments(&task)?.iter()
+                .enumerate()
+                .for_each(|(index, attachment)| println!("{}. {attachment}", index + 1));
+            Ok(())
+        },
+        Commands::Open { target, number } => {
+            let task = manager.get_task(target)?;
+            let attachments = manager.attachments().get_attachments(&task)?;
+            let attachment = match (number, attachments.len()) {
+                (_, 0) => return Err(Box::<dyn std::error::Error>::from("the task has no attachments")),
+                (None, 1) => &attachments[0],
+                (None, count) => return Err(Box::<dyn std::error::Error>::from(format!("the task has {count} attachments, which one should be opened?"))),
+                (Some(number), count) => number
+                    .checked_sub(1)
+                    .and_then(|index| attachments.get(index))
+                    .ok_or_else(|| Box::<dyn std::error::Error>::from(format!("there's no attachment {number}, the task has {count}")))?,
+            };
+            open_with_system(&attachment.location)
+        },
+        Commands::Mark { target, status, bulk } => {
+            let mut targets = resolve_targets(manager, target, bulk, &format!("Mark as {status}"), true)?;
+            manager.transaction(|_| {
+                for task in &mut targets {
+                    task.set_status(status)?;
+                }
+                Ok(())
+            })?;
+            warn_over_wip_limit(manager, status)
+        },
+        Commands::Cancel { target, reason } => {
+            let mut task = manager.get_task(target)?;
+            task.cancel(reason.as_deref())
+        },
+        Commands::Snooze { target, until, wait } => {
+            let mut task = manager.get_task(target)?;
+            let now = Local::now();
 
-            // Editing the title if provided
-            if let Some(new_title) = title {
-                task.set_title(new_title)?;
+            let wait_until = match wait {
+                Some(wait) => dates::after(now, dates::parse_duration(wait)?)?,
+                None => {
+                    // `until` reads well on the command line, but it's not part of the date
+                    let words: Vec<&str> = until.iter()
+                        .map(String::as_str)
+                        .skip_while(|word| *word == "until")
+                        .collect();
+                    dates::parse_date(&words.join(" "), now)?
+                }
             };
 
-            // Editing the description if provided
-            if let Some(new_description) = description {
-                task.set_description(new_description)?;
+            if wait_until <= now {
+                return Err(Box::<dyn std::error::Error>::from("a task can only be snoozed until a future date"));
+            }
+
+            task.set_wait_until(Some(wait_until))?;
+            println!("Snoozed until {}", dates::format_timestamp(wait_until.timestamp()));
+            Ok(())
+        },
+        Commands::Start { target } => {
+            let mut task = manager.get_task(target)?;
+            if let Some(stopped) = manager.time_tracker().start(&mut task, Local::now())? {
+                print_stopped_timer(manager, &stopped)?;
+            }
+            println!("Started working on '{}'", task.get_title()?);
+            warn_over_wip_limit(manager, &TaskStatus::Underway)
+        },
+        Commands::Stop => {
+            match manager.time_tracker().stop(Local::now())? {
+                Some(stopped) => print_stopped_timer(manager, &stopped),
+                None => Err(Box::<dyn std::error::Error>::from("no timer is running")),
+            }
+        },
+        Commands::Status => {
+            let now = Local::now();
+            match manager.time_tracker().get_running()? {
+                Some(running) => println!(
+                    "Working on '{}' since {} ({})",
+                    manager.get_task_by_id(running.task_id)?.get_title()?,
+                    running.started_at.format("%H:%M"),
+                    dates::format_duration(running.duration(now)),
+                ),
+                None => println!("No timer is running"),
+            }
+            Ok(())
+        },
+        Commands::Track { target, duration, at } => {
+            let task = manager.get_task(target)?;
+            let duration = dates::parse_duration(duration)?;
+            let now = Local::now();
+            let started_at = match at {
+                Some(at) => dates::parse_date(at, now)?,
+                None => dates::before(now, duration)?,
             };
+            manager.time_tracker().track(&task, started_at, duration)
+        },
+        Commands::Pomodoro { target, rounds } => {
+            pomodoro::install_interrupt_handler()?;
+            pomodoro::run_pomodoro(manager, target, *rounds)
+        },
+        Commands::Edit {
+            target, title, description, estimate, points, due, priority, project, add_tags, remove_tags, add_dependencies, remove_dependencies, editor, bulk,
+        } => {
+            // reading the new values first, so that a typo doesn't leave some tasks changed
+            let estimate = estimate.as_deref().map(dates::parse_duration).transpose()?;
+            let due = match due.as_deref() {
+                Some("none") => Some(None),
+                Some(due) => Some(Some(dates::parse_date(due, Local::now())?)),
+                None => None,
+            };
+            let priority = match priority.as_deref() {
+                Some("none") => Some(None),
+                Some(priority) => Some(Some(priority.parse()?)),
+                None => None,
+            };
+            let project = match project.as_deref() {
+                Some("none") => Some(None),
+                Some(project) => Some(Some(task::parse_project(project)?)),
+                None => None,
+            };
+            let add_dependencies = add_dependencies.iter().map(|title| manager.get_task(title)).collect::<Result<Vec<_>, _>>()?;
+            let remove_dependencies = remove_dependencies.iter().map(|title| manager.get_task(title)).collect::<Result<Vec<_>, _>>()?;
+
+            let edit_task = |task: &mut Task| -> Result<(), Box<dyn Error>> {
+                // Editing the title if provided
+                if let Some(new_title) = title {
+                    task.set_title(new_title)?;
+                };
+
+                // Editing the description if provided
+                if let Some(new_description) = description {
+                    task.set_description(new_description)?;
+                };
+
+                // Editing the estimates if provided, zero meaning none
+                if let Some(new_estimate) = estimate {
+                    task.set_estimate(Some(new_estimate).filter(|estimate| *estimate > Duration::zero()))?;
+                };
+                if let Some(new_points) = points {
+                    task.set_points(Some(*new_points).filter(|points| *points > 0))?;
+                };
+
+                // Editing the due date if provided, "none" meaning none
+                if let Some(new_due) = due {
+                    task.set_due(new_due)?;
+                };
+
+                // Editing the tags if provided
+                for tag in add_tags {
+                    task.add_tag(tag)?;
+                }
+                for tag in remove_tags {
+                    task.remove_tag(tag)?;
+                }
 
+                // Editing the priority, project and dependencies if provided
+                if let Some(new_priority) = priority {
+                    task.set_priority(new_priority)?;
+                };
+                if let Some(new_project) = &project {
+                    task.set_project(new_project.as_deref())?;
+                };
+                for other in &add_dependencies {
+                    task.add_dependency(other)?;
+                }
+                for other in &remove_dependencies {
+                    task.remove_dependency(other)?;
+                }
+                Ok(())
+            };
+
+            let mut targets = resolve_targets(manager, target, bulk, "Edit", false)?;
+
+            // Editing everything else in the editor, if requested (never along with --where): the file starts
+            // with the new values, and nothing is saved until the editor returns
+            if *editor {
+                let mut task = targets.remove(0);
+                let mut file = TaskFile::load(&task)?;
+                if let Some(new_title) = title {
+                    file.title = new_title.clone();
+                }
+                if let Some(new_description) = description {
+                    file.description = new_description.clone();
+                }
+                if let Some(new_estimate) = estimate {
+                    file.estimate = Some(new_estimate).filter(|estimate| *estimate > Duration::zero());
+                }
+                if let Some(new_points) = points {
+                    file.points = Some(*new_points).filter(|points| *points > 0);
+                }
+                if let Some(new_due) = due {
+                    file.due = new_due;
+                }
+                for tag in add_tags {
+                    file.tags.push(task::parse_tag(tag)?);
+                }
+                for tag in remove_tags {
+                    let tag = task::parse_tag(tag)?;
+                    file.tags.retain(|other| *other != tag);
+                }
+                file.tags.sort();
+                file.tags.dedup();
+
+                let file = editor::edit_until_valid(&editor::editor_command(), &file.to_text(), |text| {
+                    let file = TaskFile::parse(text, Local::now())?;
+                    file.check(manager, Some(&task))?;
+                    Ok(file)
+                })?;
+                return manager.transaction(|_| {
+                    edit_task(&mut task)?;
+                    file.apply(&mut task)
+                });
+            }
+
+            manager.transaction(|_| targets.iter_mut().try_for_each(edit_task))
+        },
+        Commands::Delete { target, bulk } => {
+            let targets = resolve_targets(manager, target, bulk, "Delete", true)?;
+            manager.transaction(|manager| {
+                for task in &targets {
+                    manager.delete(task)?;
+                }
+                Ok(())
+            })
+        },
+        Commands::Clear { cancelled } => {
+            manager.clear_closed_tasks(*cancelled)
+        },
+        Commands::Summary => {
+            let counts = manager.count_tasks_by_status()?;
+            counts.iter()
+                .for_each(|(definition, count)| println!("{definition}: {count}"));
+
+            // cancelled tasks count neither as completed nor as still to do
+            let (completed, total) = counts.iter()
+                .filter(|(definition, _)| definition.status != TaskStatus::Cancelled)
+                .fold((0, 0), |(completed, total), (definition, count)| match definition.closed {
+                    true => (completed + count, total + count),
+                    false => (completed, total + count),
+                });
+            if let Some(percentage) = (completed * 100).checked_div(total) {
+                println!("completion: {completed}/{total} ({percentage}%)");
+            }
+            Ok(())
+        },
+        Commands::Board { all, width } => {
+            let board = Board::build(manager, *all)?;
+            println!("{}", board.to_text(width.unwrap_or_else(board::terminal_width)));
             Ok(())
         },
-        Commands::Delete { target } => {
-            manager.delete_task(target)
+        Commands::Tui => {
+            tui::run_tui(manager)
+        },
+        Commands::Calendar { month, titles } => {
+            let now = Local::now();
+            let month = match month {
+                Some(month) => NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+                    .map_err(|_| Box::<dyn std::error::Error>::from(format!("unable to understand the month '{month}'")))?,
+                None => now.date_naive().with_day(1).unwrap_or(now.date_naive()),
+            };
+            println!("{}", calendar::month_to_text(manager, month, *titles, now)?);
+            Ok(())
+        },
+        Commands::Agenda { days } => {
+            println!("{}", calendar::agenda_to_text(manager, *days, Local::now())?);
+            Ok(())
+        },
+        Commands::Stats { weekly, periods, format } => {
+            let period = match weekly {
+                true => Period::Week,
+                false => Period::Day,
+            };
+            let statistics = manager.statistics(period, *periods, Local::now())?;
+            match format {
+                StatsFormat::Text => println!("{}", statistics.to_text(period)),
+                StatsFormat::Json => println!("{}", statistics.to_json()?),
+            }
+            Ok(())
+        },
+        Commands::Workflow { command } => {
+            run_workflow(command, manager)
+        },
+        Commands::Report { command } => {
+            run_report(command, manager)
+        },
+        Commands::Chart { command } => {
+            run_chart(command, manager)
+        },
+        Commands::Config { command } => {
+            run_config(command, manager)
+        },
+        Commands::Init | Commands::Where | Commands::Profile { .. } => {
+            Err(Box::<dyn std::error::Error>::from("this command doesn't operate on a task list"))
+        },
+        Commands::Attach { target, file, url, link } => {
+            let task = manager.get_task(target)?;
+            let mut attachments = manager.attachments();
+            match (file, url, manager.attachments_dir()) {
+                (_, Some(url), _) => attachments.attach_url(&task, url, Local::now()),
+                (Some(file), None, _) if *link => attachments.attach_link(&task, file, Local::now()),
+                (Some(file), None, Some(folder)) => attachments.attach_copy(&task, file, folder, Local::now()),
+                (Some(_), None, None) => Err(Box::<dyn std::error::Error>::from("files can only be copied next to a task list kept in a file, use --link")),
+                (None, None, _) => Err(Box::<dyn std::error::Error>::from("either a file or a URL is needed")),
+            }
+        }
+    }
+}
+
+// the tasks to change: the target, the tasks matching --where or the ones picked interactively,
+// as they were read before asking, so that the ones changed since are left alone
+fn resolve_targets<'a>(manager: &'a TaskManager, target: &Option<String>, bulk: &BulkArgs, action: &str, multiple: bool) -> Result<Vec<Task<'a>>, Box<dyn Error>> {
+    let Some(filter) = &bulk.filter else {
+        return picker::resolve_targets(manager, target, action, multiple)?
+            .iter()
+            .map(|title| manager.get_task(title))
+            .collect();
+    };
+
+    let now = Local::now();
+    let selected = TaskFilter::parse(filter, now)?.select(manager)?;
+    for task in &selected {
+        println!("{}", task.summary(now)?);
+    }
+
+    let tasks = match selected.len() {
+        0 => {
+            println!("No task matches '{filter}'");
+            return Ok(Vec::new());
+        },
+        1 => "1 task".to_string(),
+        count => format!("{count} tasks"),
+    };
+
+    if bulk.dry_run {
+        println!("{action}: {tasks} would change");
+        return Ok(Vec::new());
+    }
+
+    if !bulk.yes {
+        if !io::stdin().is_terminal() {
+            return Err(Box::<dyn std::error::Error>::from("changes to several tasks need --yes when not running in a terminal"));
+        }
+
+        print!("{action}: {tasks} will change, continue? [y/N] ");
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !["y", "yes"].contains(&answer.trim().to_lowercase().as_str()) {
+            println!("Nothing has been changed");
+            return Ok(Vec::new());
+        }
+    }
+
+    Ok(selected)
+}
+
+fn print_stopped_timer(manager: &TaskManager, stopped: &TimeEntry) -> Result<(), Box<dyn Error>> {
+    println!(
+        "Stopped working on '{}' after {}",
+        manager.get_task_by_id(stopped.task_id)?.get_title()?,
+        dates::format_duration(stopped.duration(Local::now())),
+    );
+    Ok(())
+}
+
+fn task_details(manager: &TaskManager, task: &Task, styled: bool) -> Result<String, Box<dyn Error>> {
+    let now = Local::now();
+    let mut lines = vec![format!("{} [{}]", task.get_title()?, task.get_status()?)];
+
+    if let Some(reason) = task.get_cancellation_reason()? {
+        lines.push(format!("Cancelled: {reason}"));
+    }
+    if let Some(due) = task.get_due()? {
+        let overdue = match task.is_overdue(now)? {
+            true => " (overdue)",
+            false => "",
+        };
+        lines.push(format!("Due: {}{overdue}", dates::format_timestamp(due.timestamp())));
+    }
+    if let Some(wait_until) = task.get_wait_until()?.filter(|wait_until| *wait_until > now) {
+        lines.push(format!("Waiting until: {}", dates::format_timestamp(wait_until.timestamp())));
+    }
+    if let Some(estimate) = task.get_estimate()? {
+        lines.push(format!("Estimate: {}", dates::format_duration(estimate)));
+    }
+    if let Some(points) = task.get_points()? {
+        lines.push(format!("Points: {points}"));
+    }
+    if let Some(priority) = task.get_priority()? {
+        lines.push(format!("Priority: {priority}"));
+    }
+    if let Some(project) = task.get_project()? {
+        lines.push(format!("Project: {project}"));
+    }
+    let tags = task.get_tags()?;
+    if !tags.is_empty() {
+        lines.push(format!("Tags: {}", tags.join(", ")));
+    }
+    let dependencies = task.get_dependencies()?.iter().map(Task::get_title).collect::<Result<Vec<_>, _>>()?;
+    if !dependencies.is_empty() {
+        let blocked = match task.is_blocked()? {
+            true => " (blocked)",
+            false => "",
+        };
+        lines.push(format!("Depends on: {}{blocked}", dependencies.join(", ")));
+    }
+    let tracked = manager.time_tracker().get_total(task, now)?;
+    if tracked >= Duration::minutes(1) {
+        lines.push(format!("Tracked: {}", dates::format_duration(tracked)));
+    }
+    let pomodoros = manager.pomodoro_log().count_completed(task)?;
+    if pomodoros > 0 {
+        lines.push(format!("Pomodoros: {pomodoros}"));
+    }
+
+    let description = task.get_description()?;
+    let checklist = markdown::checklist(&description);
+    if !checklist.is_empty() {
+        let checked = checklist.iter().filter(|item| item.checked).count();
+        lines.push(format!("Checklist: {checked}/{} done", checklist.len()));
+    }
+
+    if !description.trim().is_empty() {
+        lines.push(String::new());
+        lines.push(markdown::render(&description, styled));
+    }
+
+    let attachments = manager.attachments().get_attachments(task)?;
+    if !attachments.is_empty() {
+        lines.push(String::new());
+        lines.push("Attachments:".to_string());
+    }
+    for (number, attachment) in attachments.iter().enumerate() {
+        lines.push(format!("  {}. {attachment}", number + 1));
+    }
+
+    let notes = task.get_notes()?;
+    if !notes.is_empty() {
+        lines.push(String::new());
+        lines.push("Notes:".to_string());
+    }
+    for (number, note) in notes.iter().enumerate() {
+        let edited = match note.edited_at {
+            Some(edited_at) => format!(" (edited {})", dates::format_timestamp(edited_at.timestamp())),
+            None => String::new(),
+        };
+        lines.push(format!("  {}. {}{edited}", number + 1, dates::format_timestamp(note.created_at.timestamp())));
+        lines.extend(note.text.lines().map(|line| format!("     {line}")));
+    }
+    Ok(lines.join("\n"))
+}
+
+// handing the file or URL over to the desktop, as double-clicking it would
+fn open_with_system(location: &str) -> Result<(), Box<dyn Error>> {
+    let mut command = match std::env::consts::OS {
+        "macos" => Command::new("open"),
+        "windows" => {
+            let mut command = Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        },
+        _ => Command::new("xdg-open"),
+    };
+
+    let opener = command.get_program().to_string_lossy().to_string();
+    let status = command.arg(location).status()
+        .map_err(|error| format!("unable to run '{opener}' to open '{location}': {error}"))?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(Box::<dyn std::error::Error>::from(format!("unable to open '{location}', the opener exited with {status}"))),
+    }
+}
+
+fn warn_over_wip_limit(manager: &TaskManager, status: &TaskStatus) -> Result<(), Box<dyn Error>> {
+    if let Some((count, limit)) = manager.workflow().check_wip_limit(status)? {
+        eprintln!("Warning: {count} tasks are '{status}', over its WIP limit of {limit}");
+    }
+    Ok(())
+}
+
+pub fn run_workflow(command: &WorkflowCommands, manager: &TaskManager) -> Result<(), Box<dyn Error>> {
+    let mut workflow = manager.workflow();
+
+    match command {
+        WorkflowCommands::Show => {
+            println!("Statuses:");
+            for status in workflow.get_statuses()? {
+                match workflow.get_wip_limit(&status.status)? {
+                    Some(limit) => println!("  {status} (WIP limit {limit})"),
+                    None => println!("  {status}"),
+                }
+            }
+
+            if !workflow.is_restricted()? {
+                println!("Transitions: any");
+                return Ok(());
+            }
+
+            let transitions = workflow.get_transitions()?;
+            if transitions.is_empty() {
+                println!("Transitions: none");
+            } else {
+                println!("Transitions:");
+                transitions.iter()
+                    .for_each(|(from, to)| println!("  {from} -> {to}"));
+            }
+            Ok(())
+        },
+        WorkflowCommands::Add { status, closed } => {
+            workflow.add_status(status, *closed)
+        },
+        WorkflowCommands::Remove { status } => {
+            workflow.remove_status(status)
+        },
+        WorkflowCommands::Close { status } => {
+            workflow.set_closed(status, true)
+        },
+        WorkflowCommands::Reopen { status } => {
+            workflow.set_closed(status, false)
+        },
+        WorkflowCommands::Limit { status, limit } => {
+            workflow.set_wip_limit(status, Some(*limit).filter(|limit| *limit > 0))
+        },
+        WorkflowCommands::Allow { from, to } => {
+            workflow.allow_transition(from, to)
+        },
+        WorkflowCommands::Forbid { from, to } => {
+            workflow.forbid_transition(from, to)
+        },
+        WorkflowCommands::Free => {
+            workflow.lift_restrictions()
+        }
+    }
+}
+
+pub fn run_chart(command: &ChartCommands, manager: &TaskManager) -> Result<(), Box<dyn Error>> {
+    let (ChartCommands::Burndown { project, from, to, ascii, svg } | ChartCommands::Cfd { project, from, to, ascii, svg }) = command;
+    let now = Local::now();
+
+    // the last two weeks, unless a range is given
+    let to = match to {
+        Some(to) => dates::parse_date(to, now)?.date_naive(),
+        None => now.date_naive(),
+    };
+    let from = match from {
+        Some(from) => dates::parse_date(from, now)?.date_naive(),
+        None => to - chrono::Days::new(13),
+    };
+
+    let chart = FlowChart::build(manager, from, to, project.as_deref())?;
+    let style = match ascii {
+        true => ChartStyle::Ascii,
+        false => ChartStyle::Unicode,
+    };
+    let (text, image) = match command {
+        ChartCommands::Burndown { .. } => (chart.burndown_to_text(style), chart.burndown_to_svg()),
+        ChartCommands::Cfd { .. } => (chart.cfd_to_text(style), chart.cfd_to_svg()),
+    };
+
+    println!("{text}");
+    if let Some(path) = svg {
+        fs::write(path, image)?;
+        println!("Chart written to {}", path.display());
+    }
+    Ok(())
+}
+
+pub fn run_report(command: &ReportCommands, manager: &TaskManager) -> Result<(), Box<dyn Error>> {
+    match command {
+        ReportCommands::Time { from, to, format } => {
+            let now = Local::now();
+            let today = now.date_naive();
+
+            // the current week, unless a range is given
+            let week = today.week(Weekday::Mon);
+            let (from, to) = match (from, to) {
+                (None, None) => (week.first_day(), week.last_day()),
+                (Some(from), to) => (
+                    dates::parse_date(from, now)?.date_naive(),
+                    match to {
+                        Some(to) => dates::parse_date(to, now)?.date_naive(),
+                        None => today,
+                    },
+                ),
+                (None, Some(_)) => return Err(Box::<dyn std::error::Error>::from("a report with an end needs a start too")),
+            };
+
+            let rounding = Rounding::load(manager.settings())?;
+            let report = TimeReport::build(manager, from, to, &rounding, now)?;
+            match format {
+                ReportFormat::Text => println!("{}", report.to_text()),
+                ReportFormat::Csv => println!("{}", report.to_csv()),
+                ReportFormat::Json => println!("{}", report.to_json()?),
+                ReportFormat::Markdown => println!("{}", report.to_markdown()),
+            }
+            Ok(())
+        },
+        ReportCommands::Effort => {
+            let rows: Vec<EstimateRow> = EstimateRow::collect(manager, Local::now())?
+                .into_iter()
+                .filter(|row| !row.closed)
+                .collect();
+            println!("{}", report::effort_to_text(&rows));
+            Ok(())
+        },
+        ReportCommands::Accuracy => {
+            let rows: Vec<EstimateRow> = EstimateRow::collect(manager, Local::now())?
+                .into_iter()
+                .filter(|row| row.closed && row.status != TaskStatus::Cancelled)
+                .collect();
+            println!("{}", report::accuracy_to_text(&rows));
+            Ok(())
+        },
+        ReportCommands::Pomodoro => {
+            manager.pomodoro_log().count_per_task()?.iter()
+                .for_each(|count| println!("{}: {} completed, {} interrupted", count.task, count.completed, count.interrupted));
+            Ok(())
+        }
+    }
+}
+
+pub fn run_config(command: &ConfigCommands, manager: &TaskManager) -> Result<(), Box<dyn Error>> {
+    let mut settings = manager.settings();
+
+    match command {
+        ConfigCommands::List => {
+            settings.get_all()?.iter()
+                .for_each(|(key, value)| println!("{key} = {value}"));
+            Ok(())
+        },
+        ConfigCommands::Get { key } => {
+            match settings.get(key)? {
+                Some(value) => println!("{value}"),
+                None => println!("{key} is not set (the default value is used)"),
+            }
+            Ok(())
+        },
+        ConfigCommands::Set { key, value } => {
+            settings.set(key, value)
+        },
+        ConfigCommands::Unset { key } => {
+            settings.unset(key)
+        }
+    }
+}
+
+pub fn run_profile(command: &ProfileCommands, profiles: &mut ProfileManager, app_home: &Path) -> Result<(), Box<dyn Error>> {
+    match command {
+        ProfileCommands::Create { name, path } => {
+            let path = match path {
+                Some(path) => path.clone(),
+                None => app_home.join("profiles").join(format!("{name}.db")),
+            };
+            profiles.create_profile(name, &path)
+        },
+        ProfileCommands::List => {
+            let active = profiles.get_active_profile()?;
+            let marker = if active.is_none() { "*" } else { " " };
+            println!("{} {}: {}", marker, DEFAULT_PROFILE, default_database_path(app_home).display());
+            profiles.get_all_profiles()?.iter()
+                .for_each(|profile| println!("{profile}"));
+            Ok(())
         },
-        Commands::Clear => {
-            manager.clear_done_tasks()
+        ProfileCommands::Use { name } => {
+            profiles.use_profile(name)
         }
     }
 }
@@ -0,0 +1,171 @@
+use std::{env, error::Error, fmt::Display, fs, path::{Path, PathBuf}};
+
+use crate::{cli::Cli, manager::{builder::locate_store, TaskManager}, profile::{ProfileManager, DEFAULT_PROFILE}, store::StoreKind};
+
+pub const DATABASE_ENV_VAR: &str = "PEPPERTODO_DB";
+pub const LOCAL_DIRECTORY: &str = ".peppertodo";
+pub const LOCAL_DATABASE: &str = "tasks.db";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatabaseSource {
+    Option,
+    Profile(String),
+    Environment,
+    Local,
+    Default,
+}
+
+impl Display for DatabaseSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            DatabaseSource::Option => write!(f, "--db option"),
+            DatabaseSource::Profile(name) => write!(f, "profile '{name}'"),
+            DatabaseSource::Environment => write!(f, "{DATABASE_ENV_VAR} environment variable"),
+            DatabaseSource::Local => write!(f, "local task list"),
+            DatabaseSource::Default => write!(f, "default task list"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseLocation {
+    pub path: PathBuf,
+    pub source: DatabaseSource,
+}
+
+impl DatabaseLocation {
+    fn new(path: PathBuf, source: DatabaseSource) -> DatabaseLocation {
+        DatabaseLocation { path, source }
+    }
+}
+
+impl Display for DatabaseLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.path.display(), self.source)
+    }
+}
+
+pub fn locate_database(cli: &Cli, profiles: &ProfileManager, app_home: &Path, working_dir: &Path) -> Result<DatabaseLocation, Box<dyn Error>> {
+    // an explicit path always wins
+    if let Some(path) = &cli.database {
+        return Ok(DatabaseLocation::new(path.clone(), DatabaseSource::Option));
+    }
+
+    // then an explicitly requested profile
+    if let Some(name) = &cli.profile {
+        return match name.as_str() {
+            DEFAULT_PROFILE => Ok(DatabaseLocation::new(default_database_path(app_home), DatabaseSource::Default)),
+            _ => Ok(DatabaseLocation::new(profiles.get_profile(name)?.path, DatabaseSource::Profile(name.clone()))),
+        };
+    }
+
+    // then the environment
+    if let Some(path) = env::var_os(DATABASE_ENV_VAR).filter(|path| !path.is_empty()) {
+        return Ok(DatabaseLocation::new(PathBuf::from(path), DatabaseSource::Environment));
+    }
+
+    // then the nearest local task list
+    if let Some(path) = find_local_database(working_dir) {
+        return Ok(DatabaseLocation::new(path, DatabaseSource::Local));
+    }
+
+    // and finally the profile in use, if any
+    match profiles.get_active_profile()? {
+        Some(profile) => Ok(DatabaseLocation::new(profile.path, DatabaseSource::Profile(profile.name))),
+        None => Ok(DatabaseLocation::new(default_database_path(app_home), DatabaseSource::Default)),
+    }
+}
+
+pub fn find_local_database(working_dir: &Path) -> Option<PathBuf> {
+    working_dir
+        .ancestors()
+        .map(|dir| dir.join(LOCAL_DIRECTORY).join(LOCAL_DATABASE))
+        .find(|path| path.is_file())
+}
+
+pub fn init_local_database(working_dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let local_home = working_dir.join(LOCAL_DIRECTORY);
+    let path = local_home.join(LOCAL_DATABASE);
+
+    if path.exists() {
+        return Err(Box::<dyn std::error::Error>::from(format!("a local task list already exists at {}", path.display())))
+    }
+
+    fs::create_dir_all(&local_home)?;
+
+    // opening the task list once, so that the tables get created
+    TaskManager::open(&path)?;
+
+    Ok(path)
+}
+
+// the JSON file keeping the tasks of the database, when they aren't kept in the database itself
+pub fn locate_tasks_file(database: &Path, store: Option<StoreKind>) -> Result<Option<PathBuf>, Box<dyn Error>> {
+    let store = match store {
+        Some(store) => store,
+        None => locate_store(database)?,
+    };
+    Ok((store == StoreKind::Json).then(|| database.with_extension("json")))
+}
+
+pub fn default_database_path(app_home: &Path) -> PathBuf {
+    app_home.join("appdata.db")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use crate::manager::TaskManager;
+    use crate::store::StoreKind;
+
+    use super::{find_local_database, init_local_database, locate_tasks_file, LOCAL_DATABASE, LOCAL_DIRECTORY};
+
+    fn set_up_working_dir(name: &str) -> PathBuf {
+        // every test gets its own folder, so that they can run in parallel
+        let dir = env::temp_dir().join(format!("peppertodo-location-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("nested").join("deeper")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_init_local_database() {
+        let dir = set_up_working_dir("init");
+        let path = init_local_database(&dir).unwrap();
+        assert_eq!(dir.join(LOCAL_DIRECTORY).join(LOCAL_DATABASE), path);
+        assert!(path.is_file());
+
+        // a second initialization is refused
+        assert!(init_local_database(&dir).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_find_local_database() {
+        let dir = set_up_working_dir("find");
+        let nested = dir.join("nested").join("deeper");
+        assert!(find_local_database(&nested).is_none_or(|path| !path.starts_with(&dir)));
+
+        let path = init_local_database(&dir).unwrap();
+        assert_eq!(Some(path), find_local_database(&nested));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_locate_tasks_file() {
+        let dir = set_up_working_dir("store");
+        let database = dir.join("tasks.db");
+        let tasks_file = dir.join("tasks.json");
+        assert_eq!(None, locate_tasks_file(&database, None).unwrap());
+        assert_eq!(Some(tasks_file.clone()), locate_tasks_file(&database, Some(StoreKind::Json)).unwrap());
+
+        // a file alone changes nothing, the database tells where the tasks are
+        fs::write(&tasks_file, "[]").unwrap();
+        assert_eq!(None, locate_tasks_file(&database, None).unwrap());
+        TaskManager::builder().path(&database).store(StoreKind::Json).build().unwrap();
+        assert_eq!(Some(tasks_file), locate_tasks_file(&database, None).unwrap());
+        assert_eq!(None, locate_tasks_file(&database, Some(StoreKind::Sqlite)).unwrap());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
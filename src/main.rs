@@ -1,5 +1,13 @@
+use std::{env, error::Error, fs, path::Path};
+
 use clap::Parser;
-use peppertodo::{cli::Cli, manager::TaskManager, setup_application_directory};
+use peppertodo::{
+    cli::{Cli, Commands},
+    manager::TaskManager,
+    profile::ProfileManager,
+    location::{init_local_database, locate_database, locate_tasks_file},
+    setup_application_directory,
+};
 use rusqlite::Connection;
 
 fn main() {
@@ -9,14 +17,54 @@ fn main() {
     // Setting up the app's folder
     let app_home = setup_application_directory(env!("CARGO_PKG_NAME"));
 
-    // Setting up the connection to the db
-    let connection = Connection::open(app_home.join("appdata.db"))
-        .expect("Unable to open or create the application's database");
+    // Setting up the connection to the profiles' db
+    let profiles_connection = Connection::open(app_home.join("profiles.db"))
+        .expect("Unable to open or create the application's profiles database");
+
+    // Setting up the ProfileManager
+    let mut profiles = ProfileManager::new(&profiles_connection);
 
-    // Setting up the TaskManager
-    let mut manager = TaskManager::new(&connection);
+    let result = match &cli.command {
+        Commands::Init => init(),
+        Commands::Where => print_location(&cli, &profiles, &app_home),
+        Commands::Profile { command } => peppertodo::run_profile(command, &mut profiles, &app_home),
+        _ => open_and_run(&cli, &profiles, &app_home),
+    };
 
-    if let Err(error) = peppertodo::run(&cli, &mut manager) {
+    if let Err(error) = result {
         eprint!("Error: {error}");
     }
 }
+
+fn init() -> Result<(), Box<dyn Error>> {
+    let path = init_local_database(&env::current_dir()?)?;
+    println!("Initialized an empty local task list in {}", path.display());
+    Ok(())
+}
+
+fn print_location(cli: &Cli, profiles: &ProfileManager, app_home: &Path) -> Result<(), Box<dyn Error>> {
+    let location = locate_database(cli, profiles, app_home, &env::current_dir()?)?;
+    println!("{location}");
+    if let Some(tasks_file) = locate_tasks_file(&location.path, cli.store)? {
+        println!("tasks kept in {}", tasks_file.display());
+    }
+    Ok(())
+}
+
+fn open_and_run(cli: &Cli, profiles: &ProfileManager, app_home: &Path) -> Result<(), Box<dyn Error>> {
+    let database = locate_database(cli, profiles, app_home, &env::current_dir()?)?.path;
+
+    // Making sure the database's folder exists
+    if let Some(parent) = database.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Setting up the TaskManager, with its connection to the db and where the tasks are kept
+    let mut builder = TaskManager::builder().path(&database);
+    if let Some(store) = cli.store {
+        builder = builder.store(store);
+    }
+    let manager = builder.build()?;
+
+    peppertodo::run(cli, &manager)
+}
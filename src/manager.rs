@@ -1,129 +1,333 @@
+use std::cell::{Cell, RefCell};
 use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
 
+pub mod attachments;
+pub mod builder;
+pub mod history;
+pub mod notes;
+pub mod pomodoro;
+pub mod settings;
+pub mod stats;
 pub mod task;
+pub mod tracking;
+pub mod urgency;
+pub mod workflow;
 
-use rusqlite::{params, Connection, Statement};
-use task::{Task, TaskStatus};
+use chrono::{DateTime, Local, NaiveDate};
+use attachments::TaskAttachments;
+use builder::TaskManagerBuilder;
+use history::StatusHistory;
+use pomodoro::PomodoroLog;
+use settings::Settings;
+use stats::{OpenTask, Period, PeriodCount, Statistics, StatusCount, Streaks};
+use task::{Task, TaskRecord, TaskStatus};
+use tracking::TimeTracker;
+use urgency::{Urgency, UrgencyModel};
+use workflow::{StatusDefinition, Workflow};
 
-pub struct TaskManager<'a> {
-    connection: &'a Connection,
+use crate::store::{within, TaskStore};
+
+// a completed task, and when it got there
+// how long to wait for another process to release the database
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default)]
+pub struct TaskQuery {
+    pub status: Option<TaskStatus>,
+    pub include_cancelled: bool,
+    pub waiting: bool,
+    pub only_open: bool,
+    pub due_from: Option<DateTime<Local>>,
+    pub due_to: Option<DateTime<Local>>,
+    // listing the snoozed tasks along with the others
+    pub include_waiting: bool,
+    // the tags the tasks must all have
+    pub tags: Vec<String>,
+    pub project: Option<String>,
+}
+
+impl TaskQuery {
+    pub fn everything() -> TaskQuery {
+        TaskQuery { include_cancelled: true, include_waiting: true, ..Default::default() }
+    }
 }
 
-impl<'a> TaskManager<'a> {
-    pub fn new(connection: &Connection) -> TaskManager {
-        // initializing, if not present, the working table for TaskManager
-        let _ = connection.execute(
-            r#"CREATE TABLE IF NOT EXISTS tasks (
-                "id" INTEGER PRIMARY KEY AUTOINCREMENT, 
-                "title" text, 
-                "description" text, 
-                "status" text
-            )"#, ()
-        );
+pub struct TaskManager {
+    store: Box<dyn TaskStore>,
+    // where the copies of attached files are kept, none for task lists only kept in memory
+    attachments_dir: Option<PathBuf>,
+    // the copies nothing refers to anymore, removed once the changes are kept
+    unused_copies: RefCell<Vec<PathBuf>>,
+    // how many transactions are under way
+    depth: Cell<usize>,
+}
 
-        TaskManager { connection }
+impl TaskManager {
+    pub fn builder() -> TaskManagerBuilder {
+        TaskManagerBuilder::default()
     }
 
-    pub fn add_new_task(&mut self, title: &str, description: &str) -> Result<(), Box<dyn Error>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<TaskManager, Box<dyn Error>> {
+        TaskManager::builder().path(path).build()
+    }
 
-        // checking for title availability
-        if let Ok(_) = self.get_task(title) {
-            return Err(Box::<dyn std::error::Error>::from("a task with the same title already exists"))
-        }
+    pub fn open_in_memory() -> Result<TaskManager, Box<dyn Error>> {
+        TaskManager::builder().build()
+    }
+
+    pub fn new(store: impl TaskStore + 'static) -> TaskManager {
+        TaskManager { store: Box::new(store), attachments_dir: None, unused_copies: RefCell::new(Vec::new()), depth: Cell::new(0) }
+    }
 
-        let result = self.connection.execute(
-            r#"INSERT INTO "tasks" ("title", "description", "status") VALUES (?1, ?2, ?3)"#,
-            params![title, description, TaskStatus::Undone],
-        );
+    // keeping the copies of attached files in the folder, which only holds the ones of this task list
+    pub fn with_attachments_dir<P: AsRef<Path>>(mut self, attachments_dir: P) -> TaskManager {
+        self.attachments_dir = Some(attachments_dir.as_ref().to_path_buf());
+        self
+    }
 
-        Ok(result.map(|_| ())?)
+    pub fn attachments_dir(&self) -> Option<&Path> {
+        self.attachments_dir.as_deref()
     }
 
-    pub fn get_all_tasks(&self, filter: Option<&TaskStatus>) -> Result<Vec<Task>, Box<dyn Error>> {
-        let mut stmt: Statement<'_>;
+    pub fn store(&self) -> &dyn TaskStore {
+        self.store.as_ref()
+    }
 
-        let mut rows = match filter {
-            Some(filter) => {
-                stmt = self
-                    .connection
-                    .prepare(
-                        r#"SELECT "id" FROM "tasks" WHERE "status"=?1"#)?;
-                stmt.query(params![filter])?
-            }
-            None => {
-                stmt = self.connection.prepare(
-                    r#"SELECT "id"
-                        FROM "tasks"
-                        ORDER BY "status",
-	                        CASE
-		                        WHEN "status" = "undone" THEN 2
-		                        WHEN "status" = "underway" THEN 1
-		                        WHEN "status" = "done" THEN 3
-	                        END;"#)?;
-                stmt.query([])?
-            }
-        };
+    pub fn workflow(&self) -> Workflow<'_> {
+        Workflow::from_store(self.store())
+    }
 
-        let mut tasks = Vec::new();
+    pub fn settings(&self) -> Settings<'_> {
+        Settings::new(self.store.data())
+    }
 
-        loop {
-            let row = rows.next()?;
-            match row {
-                Some(task) => tasks.push(Task::from(self.connection, task.get("id")?)),
-                None => break,
-            }
-        }
+    pub fn time_tracker(&self) -> TimeTracker<'_> {
+        TimeTracker::new(self.store.data())
+    }
+
+    pub fn pomodoro_log(&self) -> PomodoroLog<'_> {
+        PomodoroLog::from_store(self.store())
+    }
+
+    pub fn status_history(&self) -> StatusHistory<'_> {
+        StatusHistory::new(self.store.data())
+    }
+
+    pub fn attachments(&self) -> TaskAttachments<'_> {
+        TaskAttachments::new(self.store.data())
+    }
+
+    pub fn add_new_task(&self, title: &str, description: &str) -> Result<(), Box<dyn Error>> {
+        self.store.create(title, description).map(|_| ())
+    }
+
+    pub fn get_all_tasks(&self, filter: Option<&TaskStatus>) -> Result<Vec<Task<'_>>, Box<dyn Error>> {
+        self.query_tasks(&TaskQuery { status: filter.cloned(), ..Default::default() })
+    }
+
+    // the same tasks as `query_tasks`, as owned records that don't borrow the manager
+    pub fn get_records(&self, query: &TaskQuery) -> Result<Vec<TaskRecord>, Box<dyn Error>> {
+        self.store.list(query)
+    }
+
+    pub fn query_tasks(&self, query: &TaskQuery) -> Result<Vec<Task<'_>>, Box<dyn Error>> {
+        Ok(self.store
+            .list(query)?
+            .iter()
+            .map(|record| Task::from_record(self.store(), record))
+            .collect())
+    }
+
+    pub fn rank_by_urgency<'t>(&self, tasks: Vec<Task<'t>>) -> Result<Vec<(Task<'t>, Urgency)>, Box<dyn Error>> {
+        let model = UrgencyModel::load(self.settings())?;
+        let now = Local::now();
+
+        let mut ranked = tasks
+            .into_iter()
+            .map(|task| model.evaluate(&task, now).map(|urgency| (task, urgency)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // the most urgent first, keeping the listing order between equally urgent tasks
+        ranked.sort_by(|(_, a), (_, b)| b.score().total_cmp(&a.score()));
+
+        Ok(ranked)
+    }
+
+    pub fn count_tasks_by_status(&self) -> Result<Vec<(StatusDefinition, usize)>, Box<dyn Error>> {
+        let counts = self.store.count_by_status()?;
+        let counts = self
+            .workflow()
+            .get_statuses()?
+            .into_iter()
+            .map(|definition| {
+                let count = counts.iter().find(|(status, _)| *status == definition.status).map(|(_, count)| *count).unwrap_or(0);
+                (definition, count)
+            })
+            .collect();
+
+        Ok(counts)
+    }
+
+    pub fn statistics(&self, period: Period, periods: usize, now: DateTime<Local>) -> Result<Statistics, Box<dyn Error>> {
+        let statuses = self
+            .count_tasks_by_status()?
+            .into_iter()
+            .map(|(definition, count)| StatusCount { status: definition.status.to_string(), closed: definition.closed, count })
+            .collect();
+
+        // the tasks completed are the closed ones, but not the cancelled ones
+        let completed: Vec<TaskStatus> = self.workflow()
+            .get_statuses()?
+            .into_iter()
+            .filter(|definition| definition.closed && definition.status != TaskStatus::Cancelled)
+            .map(|definition| definition.status)
+            .collect();
+
+        let created_counts = self.store.count_created(period)?;
+        let completed_counts = self.store.count_completed(period, &completed)?;
+        let count_in = |counts: &[(NaiveDate, usize)], start: NaiveDate| counts.iter()
+            .find(|(date, _)| *date == start)
+            .map(|(_, count)| *count)
+            .unwrap_or(0);
+        let periods = period
+            .last(periods, now)
+            .into_iter()
+            .map(|start| PeriodCount { start, created: count_in(&created_counts, start), completed: count_in(&completed_counts, start) })
+            .collect();
+
+        let averages = self.store.average_times(&completed)?;
+
+        let query = TaskQuery { only_open: true, include_waiting: true, ..Default::default() };
+        let mut open: Vec<TaskRecord> = self.store
+            .list(&query)?
+            .into_iter()
+            .filter(|task| task.created_at.is_some())
+            .collect();
+        open.sort_by_key(|task| (task.created_at, task.id));
+        let oldest_open_tasks = open
+            .into_iter()
+            .take(5)
+            .map(|task| OpenTask {
+                title: task.title,
+                status: task.status.to_string(),
+                created_at: task.created_at.unwrap_or(now),
+            })
+            .collect();
+
+        let days: Vec<NaiveDate> = self.store
+            .count_completed(Period::Day, &completed)?
+            .into_iter()
+            .map(|(date, _)| date)
+            .collect();
+        let streaks = Streaks::from_days(&days, now.date_naive());
 
-        Ok(tasks)
+        Ok(Statistics {
+            statuses,
+            periods,
+            average_lead_time_minutes: averages.lead_time_minutes,
+            average_cycle_time_minutes: averages.cycle_time_minutes,
+            oldest_open_tasks,
+            streaks,
+        })
     }
 
-    pub fn get_task(&self, title: &str) -> Result<Task, Box<dyn Error>> {
-        let mut stmt = self
-            .connection
-            .prepare(r#"SELECT * FROM "tasks" WHERE "title"=?1"#)?;
-        let mut result = stmt.query(params![title])?;
-        let rows = result.next()?;
-        match rows {
-            Some(task) => Ok(Task::from(self.connection, task.get("id")?)),
+    pub fn get_task(&self, title: &str) -> Result<Task<'_>, Box<dyn Error>> {
+        match self.store.find(title)? {
+            Some(record) => Ok(Task::from_record(self.store(), &record)),
             None => Err(Box::<dyn std::error::Error>::from("No task found")),
         }
     }
 
-    pub fn delete_task(&mut self, title: &str) -> Result<(), Box<dyn Error>> {
-        let mut stmt = self
-            .connection
-            .prepare(r#"DELETE FROM "tasks" WHERE "title"=?1"#)?;
-        let result = stmt.execute(params![title]);
-        Ok(result.map(|_| ()).map_err(Box::new)?)
+    pub fn get_task_by_id(&self, id: i32) -> Result<Task<'_>, Box<dyn Error>> {
+        let record = self.store.get(id)?;
+        Ok(Task::from_record(self.store(), &record))
     }
 
-    pub fn clear_done_tasks(&mut self) -> Result<(), Box<dyn Error>> {
-        let mut stmt = self
-            .connection
-            .prepare(r#"DELETE FROM "tasks" WHERE "status" = ?1"#)?;
-        let result = stmt.execute(params![TaskStatus::Done]);
-        Ok(result.map(|_| ()).map_err(Box::new)?)
+    // the task as it was when listed, so that changing it fails if it has been changed since
+    pub fn get_task_as_read(&self, id: i32, version: i64) -> Result<Task<'_>, Box<dyn Error>> {
+        let record = self.store.get(id)?;
+        Ok(Task::from_record(self.store(), &TaskRecord { version, ..record }))
+    }
+
+    pub fn delete_task(&self, title: &str) -> Result<(), Box<dyn Error>> {
+        self.delete(&self.get_task(title)?)
+    }
+
+    // deleting the task along with whatever belonged to it, unless it has changed since it was read
+    pub fn delete(&self, task: &Task) -> Result<(), Box<dyn Error>> {
+        self.transaction(|manager| {
+            manager.store.delete(&task.as_read()?)?;
+            manager.delete_belongings(&[task.get_id()])
+        })
+    }
+
+    pub fn clear_done_tasks(&self) -> Result<(), Box<dyn Error>> {
+        self.clear_closed_tasks(false)
+    }
+
+    pub fn clear_closed_tasks(&self, include_cancelled: bool) -> Result<(), Box<dyn Error>> {
+        self.transaction(|manager| {
+            let ids = manager.store.clear(include_cancelled)?;
+            manager.delete_belongings(&ids)
+        })
+    }
+
+    // applying every change made by `apply`, or none of them when it fails
+    pub fn transaction<T>(&self, apply: impl FnOnce(&TaskManager) -> Result<T, Box<dyn Error>>) -> Result<T, Box<dyn Error>> {
+        let depth = self.depth.get();
+        let unused = self.unused_copies.borrow().len();
+
+        self.depth.set(depth + 1);
+        let result = within(self.store(), || apply(self));
+        self.depth.set(depth);
+
+        // the files are only removed once nothing can bring their attachments back
+        if result.is_err() {
+            self.unused_copies.borrow_mut().truncate(unused);
+        } else if depth == 0 {
+            for copy in self.unused_copies.take() {
+                let _ = fs::remove_file(copy);
+            }
+        }
+        result
+    }
+
+    // removing whatever belonged to deleted tasks
+    fn delete_belongings(&self, ids: &[i32]) -> Result<(), Box<dyn Error>> {
+        for id in ids {
+            self.store.data().forget_task(*id)?;
+        }
+
+        // the copies made for another task list (e.g. in an older shared folder) are left alone
+        let copies = self.attachments().delete_for_tasks(ids)?;
+        if let Some(attachments_dir) = &self.attachments_dir {
+            self.unused_copies
+                .borrow_mut()
+                .extend(copies.into_iter().filter(|copy| copy.starts_with(attachments_dir)));
+        }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::{Duration, Local, Utc};
     use rusqlite::Connection;
 
-    use super::{task::TaskStatus, TaskManager};
+    use super::{stats::Period, task::TaskStatus, TaskManager, TaskQuery};
 
     #[test]
     fn test_add_new_task() {
-        let conn = Connection::open_in_memory().unwrap();
-        let mut manager = TaskManager::new(&conn);
+        let manager = TaskManager::open_in_memory().unwrap();
         let _ = manager.add_new_task("task title", "task description");
         assert!(!manager.get_all_tasks(None).unwrap().is_empty());
     }
 
     #[test]
     fn test_add_new_task_with_already_taken_title() {
-        let conn = Connection::open_in_memory().unwrap();
-        let mut manager = TaskManager::new(&conn);
+        let manager = TaskManager::open_in_memory().unwrap();
         let _ = manager.add_new_task("task title", "task description");
         let result = manager.add_new_task("task title", "another t. description");
         assert!(result.is_err());
@@ -131,8 +335,7 @@ mod tests {
 
     #[test]
     fn test_get_all_tasks() {
-        let conn = Connection::open_in_memory().unwrap();
-        let mut manager = TaskManager::new(&conn);
+        let manager = TaskManager::open_in_memory().unwrap();
         let _ = manager.add_new_task("task title", "task description");
         let result = manager.get_all_tasks(None);
         assert!(!result.unwrap().is_empty());
@@ -140,8 +343,7 @@ mod tests {
 
     #[test]
     fn test_get_all_tasks_filtered() {
-        let conn = Connection::open_in_memory().unwrap();
-        let mut manager = TaskManager::new(&conn);
+        let manager = TaskManager::open_in_memory().unwrap();
         let _ = manager.add_new_task("first task", "task description");
         let _ = manager.add_new_task("second task", "another task");
         let _ = manager.get_task("first task").unwrap().set_status(&TaskStatus::Underway);
@@ -150,25 +352,37 @@ mod tests {
 
     #[test]
     fn test_get_task() {
-        let conn = Connection::open_in_memory().unwrap();
-        let mut manager = TaskManager::new(&conn);
+        let manager = TaskManager::open_in_memory().unwrap();
         let _ = manager.add_new_task("task title", "task description");
         assert!(manager.get_task("task title").is_ok())
     }
 
     #[test]
     fn test_delete_task() {
-        let conn = Connection::open_in_memory().unwrap();
-        let mut manager = TaskManager::new(&conn);
+        let manager = TaskManager::open_in_memory().unwrap();
         let _ = manager.add_new_task("task title", "task description");
         let _ = manager.delete_task("task title");
         assert!(manager.get_all_tasks(None).unwrap().is_empty())
     }
 
+    #[test]
+    fn test_delete_stale_task() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task title", "task description");
+        let task = manager.get_task("task title").unwrap();
+
+        // the task changed after it was read, so it isn't deleted
+        manager.get_task("task title").unwrap().set_description("changed elsewhere").unwrap();
+        assert!(manager.delete(&task).is_err());
+        assert!(manager.get_task("task title").is_ok());
+
+        manager.delete(&manager.get_task("task title").unwrap()).unwrap();
+        assert!(manager.get_task("task title").is_err());
+    }
+
     #[test]
     fn test_clear_done_tasks() {
-        let conn = Connection::open_in_memory().unwrap();
-        let mut manager = TaskManager::new(&conn);
+        let manager = TaskManager::open_in_memory().unwrap();
 
         // adding some tasks
         let _ = manager.add_new_task("task A", "desc A");
@@ -179,4 +393,210 @@ mod tests {
 
         assert_eq!(1, manager.get_all_tasks(None).unwrap().len());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_get_all_tasks_ordered_by_workflow() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.get_task("task B").unwrap().set_status(&TaskStatus::Underway);
+        let titles: Vec<String> = manager.get_all_tasks(None).unwrap().iter()
+            .map(|task| task.get_title().unwrap())
+            .collect();
+        assert_eq!(vec!["task B", "task A"], titles);
+    }
+
+    #[test]
+    fn test_clear_closed_custom_tasks() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let wontfix: TaskStatus = "wontfix".parse().unwrap();
+        let _ = manager.workflow().add_status(&wontfix, true);
+
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.get_task("task A").unwrap().set_status(&wontfix);
+
+        let _ = manager.clear_done_tasks();
+
+        assert_eq!(1, manager.get_all_tasks(None).unwrap().len());
+    }
+
+    #[test]
+    fn test_get_all_tasks_without_cancelled() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.get_task("task A").unwrap().cancel(None);
+        assert_eq!(1, manager.get_all_tasks(None).unwrap().len());
+        assert_eq!(1, manager.get_all_tasks(Some(&TaskStatus::Cancelled)).unwrap().len());
+        let query = TaskQuery { include_cancelled: true, ..Default::default() };
+        assert_eq!(2, manager.query_tasks(&query).unwrap().len());
+    }
+
+    #[test]
+    fn test_clear_closed_tasks() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.get_task("task A").unwrap().set_status(&TaskStatus::Done);
+        let _ = manager.get_task("task B").unwrap().cancel(Some("not needed"));
+
+        // cancelled tasks are kept unless requested
+        let _ = manager.clear_done_tasks();
+        assert!(manager.get_task("task B").is_ok());
+
+        let _ = manager.clear_closed_tasks(true);
+        assert!(manager.get_task("task B").is_err());
+    }
+
+    #[test]
+    fn test_count_tasks_by_status() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.get_task("task A").unwrap().cancel(None);
+        let counts = manager.count_tasks_by_status().unwrap();
+        let count_of = |status: TaskStatus| counts.iter()
+            .find(|(definition, _)| definition.status == status)
+            .map(|(_, count)| *count);
+        assert_eq!(Some(1), count_of(TaskStatus::Undone));
+        assert_eq!(Some(1), count_of(TaskStatus::Cancelled));
+        assert_eq!(Some(0), count_of(TaskStatus::Done));
+    }
+
+    #[test]
+    fn test_get_all_tasks_without_waiting() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.add_new_task("task C", "desc C");
+        let now = Utc::now();
+        let _ = manager.get_task("task A").unwrap().set_wait_until(Some(now + Duration::days(1)));
+        let _ = manager.get_task("task B").unwrap().set_wait_until(Some(now - Duration::days(1)));
+
+        // the task whose waiting date has passed is back
+        assert_eq!(2, manager.get_all_tasks(None).unwrap().len());
+
+        let query = TaskQuery { waiting: true, ..Default::default() };
+        let waiting = manager.query_tasks(&query).unwrap();
+        assert_eq!(1, waiting.len());
+        assert_eq!("task A", waiting[0].get_title().unwrap());
+    }
+
+    #[test]
+    fn test_get_open_tasks() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.get_task("task A").unwrap().set_status(&TaskStatus::Done);
+        let query = TaskQuery { only_open: true, ..Default::default() };
+        assert_eq!(1, manager.query_tasks(&query).unwrap().len());
+    }
+
+    #[test]
+    fn test_rank_by_urgency() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.get_task("task B").unwrap().set_status(&TaskStatus::Underway);
+        let _ = manager.settings().set("urgency.status.underway", "-1");
+        let ranked = manager.rank_by_urgency(manager.get_all_tasks(None).unwrap()).unwrap();
+        assert_eq!("task A", ranked[0].0.get_title().unwrap());
+    }
+
+    #[test]
+    fn test_delete_task_with_time_entries() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task", "desc");
+        let task = manager.get_task("task").unwrap();
+        let _ = manager.time_tracker().track(&task, Utc::now(), Duration::hours(1));
+        let _ = manager.delete_task("task");
+        assert!(manager.store().data().get_time_entries(task.get_id()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_transaction() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+
+        // a failure undoes the changes made before it
+        let result = manager.transaction(|manager| {
+            manager.delete_task("task A")?;
+            manager.add_new_task("task B", "desc B")
+        });
+        assert!(result.is_err());
+        assert_eq!(2, manager.get_all_tasks(None).unwrap().len());
+
+        // a failed inner transaction only undoes its own changes
+        manager.transaction(|manager| {
+            manager.delete_task("task A")?;
+            let inner = manager.transaction(|manager| {
+                manager.delete_task("task B")?;
+                manager.get_task("task C").map(|_| ())
+            });
+            assert!(inner.is_err());
+            Ok(())
+        }).unwrap();
+        let titles: Vec<String> = manager.get_all_tasks(None).unwrap().iter().map(|task| task.get_title().unwrap()).collect();
+        assert_eq!(vec!["task B"], titles);
+    }
+
+    #[test]
+    fn test_shared_database() {
+        let path = std::env::temp_dir().join(format!("peppertodo-shared-{}.db", std::process::id()));
+        let manager = TaskManager::open(&path).unwrap();
+        let _ = manager.add_new_task("task", "desc");
+        let mode: String = Connection::open(&path).unwrap().query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+        assert_eq!("wal", mode);
+
+        // another process holding the database for a while is waited for
+        let writer = {
+            let path = path.clone();
+            let (locked, wait_for_lock) = std::sync::mpsc::channel();
+            let writer = std::thread::spawn(move || {
+                let other = Connection::open(&path).unwrap();
+                other.execute_batch(r#"BEGIN IMMEDIATE; UPDATE "tasks" SET "description" = 'elsewhere'"#).unwrap();
+                locked.send(()).unwrap();
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                other.execute_batch(r#"UPDATE "tasks" SET "version" = "version" + 1; COMMIT"#).unwrap();
+            });
+            wait_for_lock.recv().unwrap();
+            writer
+        };
+        let mut task = manager.get_task("task").unwrap();
+        manager.add_new_task("another task", "desc").unwrap();
+        writer.join().unwrap();
+
+        // but a task read before its change isn't saved over it
+        assert!(task.set_description("here").is_err());
+        assert_eq!("elsewhere", task.get_description().unwrap());
+
+        drop(manager);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+        }
+    }
+
+    #[test]
+    fn test_statistics() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.add_new_task("task C", "desc C");
+        let _ = manager.get_task("task A").unwrap().set_status(&TaskStatus::Underway);
+        let _ = manager.get_task("task A").unwrap().set_status(&TaskStatus::Done);
+        let _ = manager.get_task("task B").unwrap().cancel(None);
+
+        let statistics = manager.statistics(Period::Day, 3, Local::now()).unwrap();
+
+        // cancelled tasks don't count as completed
+        let today = statistics.periods.last().unwrap();
+        assert_eq!(3, statistics.periods.len());
+        assert_eq!((3, 1), (today.created, today.completed));
+        assert_eq!(Some(0), statistics.average_lead_time_minutes);
+        assert_eq!(Some(0), statistics.average_cycle_time_minutes);
+        assert_eq!(vec!["task C"], statistics.oldest_open_tasks.iter().map(|task| task.title.as_str()).collect::<Vec<_>>());
+        assert_eq!(1, statistics.streaks.current);
+    }
+}
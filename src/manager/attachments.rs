@@ -0,0 +1,229 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, TimeZone};
+use rusqlite::types::{FromSql, FromSqlError, ValueRef};
+use rusqlite::ToSql;
+use sha2::{Digest, Sha256};
+
+use super::task::Task;
+use crate::store::DataStore;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentKind {
+    // a copy of the file, kept in the attachments' folder under its content hash
+    Copy,
+    // the path of the file, wherever it is
+    Link,
+    Url,
+}
+
+impl Display for AttachmentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttachmentKind::Copy => write!(f, "copy"),
+            AttachmentKind::Link => write!(f, "link"),
+            AttachmentKind::Url => write!(f, "url"),
+        }
+    }
+}
+
+impl FromStr for AttachmentKind {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "copy" => Ok(AttachmentKind::Copy),
+            "link" => Ok(AttachmentKind::Link),
+            "url" => Ok(AttachmentKind::Url),
+            kind => Err(Box::<dyn std::error::Error>::from(format!("unknown attachment kind '{kind}'"))),
+        }
+    }
+}
+
+impl ToSql for AttachmentKind {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromSql for AttachmentKind {
+    fn column_result(value: ValueRef<'_>) -> Result<Self, FromSqlError> {
+        let s: String = FromSql::column_result(value)?;
+        s.parse().map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub id: i32,
+    pub task_id: i32,
+    pub kind: AttachmentKind,
+    pub name: String,
+    pub location: String,
+    pub added_at: DateTime<Local>,
+}
+
+impl Display for Attachment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            AttachmentKind::Url => write!(f, "{}", self.location),
+            AttachmentKind::Copy => write!(f, "{} (copied)", self.name),
+            AttachmentKind::Link => write!(f, "{} (linked)", self.location),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TaskAttachments<'a> {
+    data: &'a dyn DataStore,
+}
+
+impl<'a> TaskAttachments<'a> {
+    pub fn new(data: &'a dyn DataStore) -> TaskAttachments<'a> {
+        TaskAttachments { data }
+    }
+
+    // copying the file into the folder, where identical files are only stored once
+    pub fn attach_copy<Tz: TimeZone>(&mut self, task: &Task, file: &Path, folder: &Path, added_at: DateTime<Tz>) -> Result<(), Box<dyn Error>> {
+        let content = fs::read(file).map_err(|error| format!("unable to read {}: {error}", file.display()))?;
+        let hash = format!("{:x}", Sha256::digest(&content));
+        let copy = match file.extension() {
+            Some(extension) => folder.join(format!("{hash}.{}", extension.to_string_lossy())),
+            None => folder.join(hash),
+        };
+
+        if !copy.exists() {
+            fs::create_dir_all(folder)?;
+            fs::write(&copy, content)?;
+        }
+        self.insert(task, AttachmentKind::Copy, &file_name(file), &copy.to_string_lossy(), added_at)
+    }
+
+    pub fn attach_link<Tz: TimeZone>(&mut self, task: &Task, file: &Path, added_at: DateTime<Tz>) -> Result<(), Box<dyn Error>> {
+        let file = file.canonicalize().map_err(|error| format!("unable to find {}: {error}", file.display()))?;
+        self.insert(task, AttachmentKind::Link, &file_name(&file), &file.to_string_lossy(), added_at)
+    }
+
+    pub fn attach_url<Tz: TimeZone>(&mut self, task: &Task, url: &str, added_at: DateTime<Tz>) -> Result<(), Box<dyn Error>> {
+        if !url.contains("://") {
+            return Err(Box::<dyn std::error::Error>::from(format!("'{url}' isn't a URL (e.g. https://example.com)")))
+        }
+        self.insert(task, AttachmentKind::Url, url, url, added_at)
+    }
+
+    pub fn get_attachments(&self, task: &Task) -> Result<Vec<Attachment>, Box<dyn Error>> {
+        self.data.get_attachments(task.get_id())
+    }
+
+    // removing the attachments of deleted tasks, and giving back the copies nothing refers to anymore
+    pub fn delete_for_tasks(&mut self, ids: &[i32]) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        let mut copies: Vec<String> = Vec::new();
+        for id in ids {
+            copies.extend(self.data
+                .get_attachments(*id)?
+                .into_iter()
+                .filter(|attachment| attachment.kind == AttachmentKind::Copy)
+                .map(|attachment| attachment.location));
+            self.data.delete_attachments(*id)?;
+        }
+        copies.sort();
+        copies.dedup();
+
+        let mut unused = Vec::new();
+        for copy in copies {
+            if !self.data.is_copy_attached(&copy)? {
+                unused.push(PathBuf::from(copy));
+            }
+        }
+        Ok(unused)
+    }
+
+    fn insert<Tz: TimeZone>(&mut self, task: &Task, kind: AttachmentKind, name: &str, location: &str, added_at: DateTime<Tz>) -> Result<(), Box<dyn Error>> {
+        self.data.add_attachment(task.get_id(), kind, name, location, added_at.with_timezone(&Local))
+    }
+}
+
+fn file_name(file: &Path) -> String {
+    file.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| file.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use chrono::Local;
+
+    use crate::manager::TaskManager;
+
+    use super::AttachmentKind;
+
+    #[test]
+    fn test_attachments() {
+        let folder = env::temp_dir().join(format!("peppertodo-attachments-{}", std::process::id()));
+        let file = env::temp_dir().join(format!("peppertodo-design-{}.txt", std::process::id()));
+        fs::write(&file, "the design").unwrap();
+
+        let manager = TaskManager::builder().attachments_dir(&folder).build().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let task_a = manager.get_task("task A").unwrap();
+        let task_b = manager.get_task("task B").unwrap();
+
+        let mut attachments = manager.attachments();
+        attachments.attach_copy(&task_a, &file, &folder, Local::now()).unwrap();
+        attachments.attach_copy(&task_b, &file, &folder, Local::now()).unwrap();
+        attachments.attach_link(&task_a, &file, Local::now()).unwrap();
+        attachments.attach_url(&task_a, "https://example.com", Local::now()).unwrap();
+        assert!(attachments.attach_url(&task_a, "example", Local::now()).is_err());
+
+        let kinds: Vec<AttachmentKind> = attachments.get_attachments(&task_a).unwrap().into_iter().map(|attachment| attachment.kind).collect();
+        assert_eq!(vec![AttachmentKind::Copy, AttachmentKind::Link, AttachmentKind::Url], kinds);
+
+        // both tasks share the same copy, kept until neither needs it
+        let copy = attachments.get_attachments(&task_a).unwrap()[0].location.clone();
+        assert_eq!(copy, attachments.get_attachments(&task_b).unwrap()[0].location);
+        manager.delete_task("task A").unwrap();
+        assert!(fs::exists(&copy).unwrap());
+
+        // nor removed by a deletion that doesn't go through
+        let result = manager.transaction(|manager| {
+            manager.delete_task("task B")?;
+            manager.get_task("missing").map(|_| ())
+        });
+        assert!(result.is_err());
+        assert!(fs::exists(&copy).unwrap());
+        assert_eq!(1, attachments.get_attachments(&task_b).unwrap().len());
+
+        manager.delete_task("task B").unwrap();
+        assert!(!fs::exists(&copy).unwrap());
+        assert!(fs::exists(&file).unwrap());
+
+        let _ = fs::remove_file(&file);
+        let _ = fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn test_copies_of_another_task_list() {
+        let folder = env::temp_dir().join(format!("peppertodo-shared-attachments-{}", std::process::id()));
+        let file = env::temp_dir().join(format!("peppertodo-spec-{}.txt", std::process::id()));
+        fs::write(&file, "the spec").unwrap();
+
+        // a copy made in a folder the task list doesn't own is never removed
+        let manager = TaskManager::builder().attachments_dir(folder.join("mine")).build().unwrap();
+        let _ = manager.add_new_task("task", "desc");
+        let task = manager.get_task("task").unwrap();
+        manager.attachments().attach_copy(&task, &file, &folder, Local::now()).unwrap();
+        let copy = manager.attachments().get_attachments(&task).unwrap()[0].location.clone();
+        manager.delete_task("task").unwrap();
+        assert!(fs::exists(&copy).unwrap());
+
+        let _ = fs::remove_file(&file);
+        let _ = fs::remove_dir_all(&folder);
+    }
+}
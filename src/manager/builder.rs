@@ -0,0 +1,200 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rusqlite::{Connection, OpenFlags};
+
+use crate::store::file::{self, FileStore};
+use crate::store::sqlite::{SqliteData, SqliteStore};
+use crate::store::{DataStore, StoreKind, STORE_SETTING};
+
+use super::{TaskManager, BUSY_TIMEOUT};
+
+#[derive(Debug, Clone)]
+pub struct TaskManagerBuilder {
+    path: Option<PathBuf>,
+    store: Option<StoreKind>,
+    tasks_file: Option<PathBuf>,
+    attachments_dir: Option<PathBuf>,
+    busy_timeout: Duration,
+    wal: bool,
+}
+
+impl Default for TaskManagerBuilder {
+    fn default() -> Self {
+        TaskManagerBuilder { path: None, store: None, tasks_file: None, attachments_dir: None, busy_timeout: BUSY_TIMEOUT, wal: true }
+    }
+}
+
+impl TaskManagerBuilder {
+    // the database file, created if needed; without one the tasks only live in memory
+    pub fn path<P: AsRef<Path>>(mut self, path: P) -> TaskManagerBuilder {
+        self.path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    // where to keep the tasks from now on, by default where they were kept last time (at first in the database);
+    // moving away from tasks that are still there is refused, as they would be hidden
+    pub fn store(mut self, store: StoreKind) -> TaskManagerBuilder {
+        self.store = Some(store);
+        self
+    }
+
+    // keeping the tasks in a JSON file instead of the database, which still holds everything else;
+    // by default the file is next to the database (e.g. tasks.json for tasks.db)
+    pub fn tasks_file<P: AsRef<Path>>(mut self, tasks_file: P) -> TaskManagerBuilder {
+        self.store = Some(StoreKind::Json);
+        self.tasks_file = Some(tasks_file.as_ref().to_path_buf());
+        self
+    }
+
+    // where to keep the copies of attached files, by default next to the database (e.g. tasks.attachments for tasks.db)
+    pub fn attachments_dir<P: AsRef<Path>>(mut self, attachments_dir: P) -> TaskManagerBuilder {
+        self.attachments_dir = Some(attachments_dir.as_ref().to_path_buf());
+        self
+    }
+
+    // how long to wait for another process to release the database before giving up
+    pub fn busy_timeout(mut self, busy_timeout: Duration) -> TaskManagerBuilder {
+        self.busy_timeout = busy_timeout;
+        self
+    }
+
+    // whether to use SQLite's write-ahead log, letting readers and a writer work at the same time
+    pub fn wal(mut self, wal: bool) -> TaskManagerBuilder {
+        self.wal = wal;
+        self
+    }
+
+    pub fn build(self) -> Result<TaskManager, Box<dyn Error>> {
+        let connection = match &self.path {
+            Some(path) => Connection::open(path)
+                .map_err(|error| format!("Unable to open or create the database at {}: {error}", path.display()))?,
+            None => Connection::open_in_memory()?,
+        };
+
+        connection.busy_timeout(self.busy_timeout)?;
+        // databases in memory have no log to write ahead to
+        if self.wal && self.path.is_some() {
+            connection.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        }
+
+        let data = SqliteData::new(connection);
+        let recorded = recorded_store(&data)?;
+        let store = self.store.unwrap_or(recorded);
+        let tasks_file = self.tasks_file.clone().or_else(|| self.path.as_ref().map(|path| path.with_extension("json")));
+
+        if store != recorded {
+            // the tasks left behind would be hidden, and the new ones would take their ids
+            let left = match (store, &tasks_file) {
+                (StoreKind::Json, _) => data.count_database_tasks() as usize,
+                (StoreKind::Sqlite, Some(tasks_file)) => file::read(tasks_file, 0)?.len(),
+                (StoreKind::Sqlite, None) => 0,
+            };
+            if left > 0 {
+                return Err(Box::<dyn std::error::Error>::from(format!(
+                    "the tasks are kept in the {recorded} store, which still holds {left} task(s): delete them before keeping the tasks in the {store} store")));
+            }
+            data.set_setting(STORE_SETTING, &store.to_string())?;
+        }
+
+        let manager = match (store, tasks_file) {
+            (StoreKind::Json, Some(tasks_file)) => TaskManager::new(FileStore::open(tasks_file, data)?),
+            (StoreKind::Json, None) => return Err(Box::<dyn std::error::Error>::from("a JSON file for the tasks needs a database path or a tasks_file")),
+            (StoreKind::Sqlite, _) => TaskManager::new(SqliteStore::from_data(data)),
+        };
+
+        let attachments_dir = self.attachments_dir.or_else(|| self.path.map(|path| path.with_extension("attachments")));
+        match attachments_dir {
+            Some(attachments_dir) => Ok(manager.with_attachments_dir(attachments_dir)),
+            None => Ok(manager),
+        }
+    }
+}
+
+fn recorded_store(data: &dyn DataStore) -> Result<StoreKind, Box<dyn Error>> {
+    Ok(data.get_setting(STORE_SETTING)?.map(|store| store.parse()).transpose()?.unwrap_or(StoreKind::Sqlite))
+}
+
+// where the tasks of the database are kept, without creating it when it doesn't exist yet
+pub fn locate_store<P: AsRef<Path>>(path: P) -> Result<StoreKind, Box<dyn Error>> {
+    if !path.as_ref().is_file() {
+        return Ok(StoreKind::Sqlite);
+    }
+    let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    // a database from before the settings keeps its tasks
+    Ok(recorded_store(&SqliteData::new(connection)).unwrap_or(StoreKind::Sqlite))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, thread};
+
+    use rusqlite::Connection;
+
+    use crate::manager::TaskManager;
+    use crate::store::StoreKind;
+
+    #[test]
+    fn test_build() {
+        let path = env::temp_dir().join(format!("peppertodo-builder-{}.db", std::process::id()));
+        let manager = TaskManager::builder().path(&path).wal(false).build().unwrap();
+        manager.add_new_task("task", "desc").unwrap();
+        let mode: String = Connection::open(&path).unwrap().query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+        assert_eq!("delete", mode);
+
+        // the manager owns its connection, and can move to another thread
+        let title = thread::spawn(move || manager.get_task("task").unwrap().get_title().unwrap()).join().unwrap();
+        assert_eq!("task", title);
+
+        let manager = TaskManager::open(&path).unwrap();
+        assert_eq!(1, manager.get_all_tasks(None).unwrap().len());
+
+        drop(manager);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = fs::remove_file(format!("{}{suffix}", path.display()));
+        }
+    }
+
+    #[test]
+    fn test_switch_store() {
+        let path = env::temp_dir().join(format!("peppertodo-switch-{}.db", std::process::id()));
+        let tasks_file = path.with_extension("json");
+        let manager = TaskManager::builder().path(&path).build().unwrap();
+        manager.add_new_task("in the database", "desc").unwrap();
+        drop(manager);
+
+        // the tasks left in the database would be hidden
+        assert!(TaskManager::builder().path(&path).store(StoreKind::Json).build().is_err());
+        assert!(!tasks_file.exists());
+
+        let manager = TaskManager::open(&path).unwrap();
+        manager.delete_task("in the database").unwrap();
+        drop(manager);
+        let manager = TaskManager::builder().path(&path).store(StoreKind::Json).build().unwrap();
+        manager.add_new_task("in the file", "desc").unwrap();
+        // a new id, rather than the one of the deleted task
+        assert_eq!(2, manager.get_task("in the file").unwrap().get_id());
+        drop(manager);
+
+        // the choice is remembered
+        let manager = TaskManager::open(&path).unwrap();
+        assert_eq!(1, manager.get_all_tasks(None).unwrap().len());
+        assert_eq!(Some("json".to_string()), manager.settings().get("store").unwrap());
+        drop(manager);
+
+        assert!(TaskManager::builder().path(&path).store(StoreKind::Sqlite).build().is_err());
+        let manager = TaskManager::open(&path).unwrap();
+        manager.delete_task("in the file").unwrap();
+        drop(manager);
+        let manager = TaskManager::builder().path(&path).store(StoreKind::Sqlite).build().unwrap();
+        manager.add_new_task("back in the database", "desc").unwrap();
+        assert_eq!(1, manager.get_all_tasks(None).unwrap().len());
+
+        drop(manager);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = fs::remove_file(format!("{}{suffix}", path.display()));
+        }
+        let _ = fs::remove_file(tasks_file);
+    }
+}
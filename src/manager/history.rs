@@ -0,0 +1,59 @@
+use std::error::Error;
+
+use chrono::{DateTime, Local, TimeZone};
+
+use super::task::{Task, TaskStatus};
+use crate::store::DataStore;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusChange {
+    pub task_id: i32,
+    pub status: TaskStatus,
+    pub changed_at: DateTime<Local>,
+}
+
+#[derive(Clone)]
+pub struct StatusHistory<'a> {
+    data: &'a dyn DataStore,
+}
+
+impl<'a> StatusHistory<'a> {
+    pub fn new(data: &'a dyn DataStore) -> StatusHistory<'a> {
+        StatusHistory { data }
+    }
+
+    pub fn record<Tz: TimeZone>(&mut self, task_id: i32, status: &TaskStatus, changed_at: DateTime<Tz>) -> Result<(), Box<dyn Error>> {
+        self.data.add_status_change(task_id, status, changed_at.with_timezone(&Local))
+    }
+
+    pub fn get_changes(&self, task: &Task) -> Result<Vec<StatusChange>, Box<dyn Error>> {
+        self.data.get_status_changes(Some(task.get_id()))
+    }
+
+    pub fn get_all_changes(&self) -> Result<Vec<StatusChange>, Box<dyn Error>> {
+        self.data.get_status_changes(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::manager::{task::TaskStatus, TaskManager};
+
+    #[test]
+    fn test_status_changes_are_recorded() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task", "desc");
+        let mut task = manager.get_task("task").unwrap();
+
+        task.set_status(&TaskStatus::Underway).unwrap();
+        task.set_status(&TaskStatus::Underway).unwrap();
+        task.set_status(&TaskStatus::Done).unwrap();
+
+        // setting the same status again isn't a change
+        let statuses: Vec<TaskStatus> = manager.status_history().get_changes(&task).unwrap()
+            .into_iter()
+            .map(|change| change.status)
+            .collect();
+        assert_eq!(vec![TaskStatus::Underway, TaskStatus::Done], statuses);
+    }
+}
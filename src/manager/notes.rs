@@ -0,0 +1,74 @@
+use std::error::Error;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+// a note kept along with its task, the notes of a task being referred to by their position (from 1), oldest first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Note {
+    pub text: String,
+    pub created_at: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<DateTime<Local>>,
+}
+
+pub(crate) fn check_text(text: &str) -> Result<(), Box<dyn Error>> {
+    match text.trim().is_empty() {
+        true => Err(Box::<dyn std::error::Error>::from("a note can't be empty")),
+        false => Ok(()),
+    }
+}
+
+// the index of the note with this number
+pub(crate) fn position(notes: &[Note], number: usize) -> Result<usize, Box<dyn Error>> {
+    let count = notes.len();
+    match (1..=count).contains(&number) {
+        true => Ok(number - 1),
+        false => Err(Box::<dyn std::error::Error>::from(format!("there's no note {number}, the task has {count}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+    use rusqlite::Connection;
+
+    use crate::manager::TaskManager;
+    use crate::manager::TaskQuery;
+
+    #[test]
+    fn test_notes() {
+        let path = std::env::temp_dir().join(format!("peppertodo-notes-{}.db", std::process::id()));
+        let manager = TaskManager::builder().path(&path).wal(false).build().unwrap();
+        let conn = Connection::open(&path).unwrap();
+        let _ = manager.add_new_task("task", "desc");
+        let mut task = manager.get_task("task").unwrap();
+        let now = Local::now();
+
+        task.add_note("second", now).unwrap();
+        task.add_note("first", now - Duration::hours(1)).unwrap();
+        assert!(task.add_note(" ", now).is_err());
+
+        // the notes are numbered from the oldest
+        task.edit_note(2, "second, edited", now).unwrap();
+        let texts: Vec<String> = task.get_notes().unwrap().into_iter().map(|note| note.text).collect();
+        assert_eq!(vec!["first", "second, edited"], texts);
+        assert!(task.get_notes().unwrap()[1].edited_at.is_some());
+        assert!(task.edit_note(1, "", now).is_err());
+
+        // the notes come along with the task
+        let records = manager.get_records(&TaskQuery::everything()).unwrap();
+        assert_eq!(2, records[0].notes.len());
+
+        task.delete_note(1).unwrap();
+        assert!(task.delete_note(2).is_err());
+        assert_eq!(1, task.get_notes().unwrap().len());
+
+        // the notes go away with their task
+        manager.delete_task("task").unwrap();
+        assert_eq!(0, conn.query_row(r#"SELECT COUNT(*) FROM "task_notes""#, [], |row| row.get::<_, i64>(0)).unwrap());
+
+        drop(manager);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,89 @@
+use std::error::Error;
+
+use chrono::{DateTime, Local, TimeZone};
+
+use super::task::Task;
+use crate::store::TaskStore;
+
+pub const DEFAULT_WORK_DURATION: &str = "25m";
+pub const DEFAULT_BREAK_DURATION: &str = "5m";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PomodoroCount {
+    pub task: String,
+    pub completed: usize,
+    pub interrupted: usize,
+}
+
+// the id of a task, and how many of its pomodoros were completed and interrupted
+pub type TaskPomodoros = (i32, usize, usize);
+
+#[derive(Clone)]
+pub struct PomodoroLog<'a> {
+    // the tasks, for their titles, and the pomodoros kept around them
+    store: &'a dyn TaskStore,
+}
+
+impl<'a> PomodoroLog<'a> {
+    pub fn from_store(store: &'a dyn TaskStore) -> PomodoroLog<'a> {
+        PomodoroLog { store }
+    }
+
+    pub fn record<Tz: TimeZone>(&mut self, task: &Task, started_at: DateTime<Tz>, ended_at: DateTime<Tz>, completed: bool) -> Result<(), Box<dyn Error>> {
+        self.store.data().add_pomodoro(task.get_id(), started_at.with_timezone(&Local), ended_at.with_timezone(&Local), completed)
+    }
+
+    pub fn count_completed(&self, task: &Task) -> Result<usize, Box<dyn Error>> {
+        self.store.data().count_completed_pomodoros(task.get_id())
+    }
+
+    pub fn count_per_task(&self) -> Result<Vec<PomodoroCount>, Box<dyn Error>> {
+        let counts = self.store.data().count_pomodoros()?;
+
+        // the pomodoros of deleted tasks aren't counted
+        let mut counts: Vec<PomodoroCount> = counts
+            .into_iter()
+            .filter_map(|(id, completed, interrupted)| {
+                let task = self.store.get(id).ok()?;
+                Some(PomodoroCount { task: task.title, completed, interrupted })
+            })
+            .collect();
+        counts.sort_by(|a, b| b.completed.cmp(&a.completed).then_with(|| a.task.cmp(&b.task)));
+
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use crate::manager::TaskManager;
+
+    use super::PomodoroCount;
+
+    #[test]
+    fn test_record_and_count() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let task_a = manager.get_task("task A").unwrap();
+        let task_b = manager.get_task("task B").unwrap();
+        let mut log = manager.pomodoro_log();
+        let now = Local::now();
+
+        log.record(&task_a, now, now + Duration::minutes(25), true).unwrap();
+        log.record(&task_a, now, now + Duration::minutes(10), false).unwrap();
+        log.record(&task_b, now, now + Duration::minutes(25), true).unwrap();
+        log.record(&task_b, now, now + Duration::minutes(25), true).unwrap();
+
+        assert_eq!(1, log.count_completed(&task_a).unwrap());
+        assert_eq!(
+            vec![
+                PomodoroCount { task: "task B".to_string(), completed: 2, interrupted: 0 },
+                PomodoroCount { task: "task A".to_string(), completed: 1, interrupted: 1 },
+            ],
+            log.count_per_task().unwrap()
+        );
+    }
+}
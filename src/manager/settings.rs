@@ -0,0 +1,102 @@
+use std::error::Error;
+use std::str::FromStr;
+
+use crate::store::{DataStore, STORE_SETTING};
+
+#[derive(Clone)]
+pub struct Settings<'a> {
+    data: &'a dyn DataStore,
+}
+
+impl<'a> Settings<'a> {
+    pub fn new(data: &'a dyn DataStore) -> Settings<'a> {
+        Settings { data }
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<String>, Box<dyn Error>> {
+        self.data.get_setting(key)
+    }
+
+    pub fn get_or<T: FromStr>(&self, key: &str, default: T) -> Result<T, Box<dyn Error>> {
+        match self.get(key)? {
+            Some(value) => value
+                .parse()
+                .map_err(|_| Box::<dyn std::error::Error>::from(format!("invalid value '{value}' for setting '{key}'"))),
+            None => Ok(default),
+        }
+    }
+
+    pub fn get_all(&self) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        self.data.get_settings()
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        if key.is_empty() {
+            return Err(Box::<dyn std::error::Error>::from("invalid setting name"))
+        }
+        check_changeable(key)?;
+
+        // the urgency coefficients are numbers, checked now rather than when ranking tasks
+        if key.starts_with("urgency.") && !value.parse::<f64>().is_ok_and(f64::is_finite) {
+            return Err(Box::<dyn std::error::Error>::from(format!("invalid value '{value}' for setting '{key}', a number is expected")))
+        }
+        if key == "urgency.age.max" && value.parse::<f64>().is_ok_and(|days| days <= 0.0) {
+            return Err(Box::<dyn std::error::Error>::from("'urgency.age.max' must be a positive number of days"))
+        }
+
+        self.data.set_setting(key, value)
+    }
+
+    pub fn unset(&mut self, key: &str) -> Result<(), Box<dyn Error>> {
+        check_changeable(key)?;
+        self.data.unset_setting(key)
+    }
+}
+
+// the store follows where the tasks are, and only changes along with them
+fn check_changeable(key: &str) -> Result<(), Box<dyn Error>> {
+    match key == STORE_SETTING {
+        true => Err(Box::<dyn std::error::Error>::from(format!("'{STORE_SETTING}' changes when the tasks are kept elsewhere (--store)"))),
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::store::memory::MemoryData;
+
+    use super::Settings;
+
+    #[test]
+    fn test_set_and_get() {
+        let data = MemoryData::default();
+        let mut settings = Settings::new(&data);
+        settings.set("urgency.age", "3.5").unwrap();
+        assert_eq!(Some("3.5".to_string()), settings.get("urgency.age").unwrap());
+        assert_eq!(3.5, settings.get_or("urgency.age", 2.0).unwrap());
+
+        settings.unset("urgency.age").unwrap();
+        assert_eq!(2.0, settings.get_or("urgency.age", 2.0).unwrap());
+    }
+
+    #[test]
+    fn test_get_or_with_invalid_value() {
+        let data = MemoryData::default();
+        let mut settings = Settings::new(&data);
+        settings.set("report.width", "wide").unwrap();
+        assert!(settings.get_or("report.width", 80).is_err());
+    }
+
+    #[test]
+    fn test_set_invalid_number() {
+        let data = MemoryData::default();
+        let mut settings = Settings::new(&data);
+        assert!(settings.set("urgency.age", "very").is_err());
+        assert!(settings.set("urgency.tag.release", "inf").is_err());
+        assert!(settings.set("urgency.age.max", "0").is_err());
+        settings.set("urgency.blocked", "-2.5").unwrap();
+        assert!(settings.set("store", "json").is_err());
+        assert!(settings.unset("store").is_err());
+        assert_eq!(None, settings.get("urgency.age").unwrap());
+    }
+}
@@ -0,0 +1,203 @@
+use std::error::Error;
+
+use chrono::{DateTime, Datelike, Days, Duration, Local, NaiveDate};
+use serde::Serialize;
+
+use crate::dates;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    Week,
+}
+
+impl Period {
+    pub fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+        }
+    }
+
+    pub fn last(&self, count: usize, now: DateTime<Local>) -> Vec<NaiveDate> {
+        let days = match self {
+            Period::Day => 1,
+            Period::Week => 7,
+        };
+        let current = self.start_of(now.date_naive());
+        (0..count as u64).rev().map(|index| current - Days::new(index * days)).collect()
+    }
+}
+
+// how many events (e.g. tasks created) each period holds, the earliest period first
+pub(crate) fn count_per_period(events: impl Iterator<Item = DateTime<Local>>, period: Period) -> Vec<(NaiveDate, usize)> {
+    let mut counts: Vec<(NaiveDate, usize)> = Vec::new();
+    for event in events {
+        let start = period.start_of(event.date_naive());
+        match counts.iter_mut().find(|(date, _)| *date == start) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((start, 1)),
+        }
+    }
+    counts.sort();
+    counts
+}
+
+// lead time goes from the creation of a task, cycle time from when the work on it started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AverageTimes {
+    pub lead_time_minutes: Option<i64>,
+    pub cycle_time_minutes: Option<i64>,
+}
+
+impl AverageTimes {
+    pub(crate) fn from_seconds(lead_time: Option<f64>, cycle_time: Option<f64>) -> AverageTimes {
+        let minutes = |seconds: Option<f64>| seconds.map(|seconds| (seconds / 60.0).round() as i64);
+        AverageTimes { lead_time_minutes: minutes(lead_time), cycle_time_minutes: minutes(cycle_time) }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatusCount {
+    pub status: String,
+    pub closed: bool,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PeriodCount {
+    pub start: NaiveDate,
+    pub created: usize,
+    pub completed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OpenTask {
+    pub title: String,
+    pub status: String,
+    pub created_at: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Streaks {
+    pub current: usize,
+    pub longest: usize,
+}
+
+impl Streaks {
+    pub fn from_days(days: &[NaiveDate], today: NaiveDate) -> Streaks {
+        let mut longest = 0;
+        let mut run = 0;
+        let mut previous: Option<NaiveDate> = None;
+
+        for day in days {
+            run = match previous {
+                Some(previous) if previous + Days::new(1) == *day => run + 1,
+                _ => 1,
+            };
+            longest = longest.max(run);
+            previous = Some(*day);
+        }
+
+        // the current streak is still alive until a whole day goes by without completions
+        let current = match previous {
+            Some(last) if last >= today - Days::new(1) => run,
+            _ => 0,
+        };
+
+        Streaks { current, longest }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Statistics {
+    pub statuses: Vec<StatusCount>,
+    pub periods: Vec<PeriodCount>,
+    pub average_lead_time_minutes: Option<i64>,
+    pub average_cycle_time_minutes: Option<i64>,
+    pub oldest_open_tasks: Vec<OpenTask>,
+    pub streaks: Streaks,
+}
+
+impl Statistics {
+    pub fn to_text(&self, period: Period) -> String {
+        let mut text = String::from("Tasks per status\n");
+        for status in &self.statuses {
+            let name = match status.closed {
+                true => format!("{} (closed)", status.status),
+                false => status.status.clone(),
+            };
+            text += &format!("  {:<28} {:>9}\n", name, status.count);
+        }
+
+        let label = match period {
+            Period::Day => "day",
+            Period::Week => "week of",
+        };
+        text += &format!("\n{:<30} {:>9} {:>9}\n", format!("Per {label}"), "Created", "Completed");
+        for count in &self.periods {
+            text += &format!("  {:<28} {:>9} {:>9}\n", count.start.format("%a %Y-%m-%d").to_string(), count.created, count.completed);
+        }
+
+        text += &format!("\n{:<30} {:>9}\n", "Average lead time", format_average(self.average_lead_time_minutes));
+        text += &format!("{:<30} {:>9}\n", "Average cycle time", format_average(self.average_cycle_time_minutes));
+
+        if !self.oldest_open_tasks.is_empty() {
+            text += "\nOldest open tasks\n";
+            for task in &self.oldest_open_tasks {
+                text += &format!("  {} [{}] {}\n", task.created_at.format("%Y-%m-%d"), task.status, task.title);
+            }
+        }
+
+        text += &format!("\n{:<30} {:>9}\n", "Current streak", format_days(self.streaks.current));
+        text += &format!("{:<30} {:>9}", "Longest streak", format_days(self.streaks.longest));
+        text
+    }
+
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+fn format_average(minutes: Option<i64>) -> String {
+    match minutes {
+        // long times are easier to read in days
+        Some(minutes) if minutes >= 24 * 60 => format!("{:.1}d", minutes as f64 / (24.0 * 60.0)),
+        Some(minutes) => dates::format_duration(Duration::minutes(minutes)),
+        None => "-".to_string(),
+    }
+}
+
+fn format_days(days: usize) -> String {
+    match days {
+        1 => "1 day".to_string(),
+        days => format!("{days} days"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{Period, Streaks};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    #[test]
+    fn test_streaks() {
+        let days = [date(1), date(2), date(3), date(10), date(11)];
+        assert_eq!(Streaks { current: 2, longest: 3 }, Streaks::from_days(&days, date(12)));
+        assert_eq!(Streaks { current: 0, longest: 3 }, Streaks::from_days(&days, date(13)));
+        assert_eq!(Streaks { current: 0, longest: 0 }, Streaks::from_days(&[], date(13)));
+    }
+
+    #[test]
+    fn test_start_of_week() {
+        // 2026-10-21 is a wednesday
+        assert_eq!(date(19), Period::Week.start_of(date(21)));
+        assert_eq!(date(19), Period::Week.start_of(date(19)));
+        assert_eq!(date(21), Period::Day.start_of(date(21)));
+    }
+}
@@ -1,15 +1,31 @@
 use std::str::FromStr;
 use std::{error::Error, fmt::Display};
 
-use clap::{builder::PossibleValue, ValueEnum};
+use chrono::{DateTime, Duration, Local, TimeZone};
 use rusqlite::types::{FromSql, FromSqlError, ValueRef};
-use rusqlite::{params, Connection, ToSql};
+use rusqlite::ToSql;
+
+use super::notes::{self, Note};
+use super::pomodoro::PomodoroLog;
+use super::tracking::TimeTracker;
+use super::workflow::Workflow;
+use super::TaskQuery;
+use crate::store::TaskStore;
+use crate::{dates, markdown};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TaskStatus {
     Undone,
     Underway,
     Done,
+    Cancelled,
+    Custom(String),
+}
+
+impl TaskStatus {
+    pub fn is_built_in(&self) -> bool {
+        !matches!(self, TaskStatus::Custom(_))
+    }
 }
 
 impl Display for TaskStatus {
@@ -18,6 +34,8 @@ impl Display for TaskStatus {
             TaskStatus::Undone => write!(f, "undone"),
             TaskStatus::Underway => write!(f, "underway"),
             TaskStatus::Done => write!(f, "done"),
+            TaskStatus::Cancelled => write!(f, "cancelled"),
+            TaskStatus::Custom(name) => write!(f, "{name}"),
         }
     }
 }
@@ -30,6 +48,10 @@ impl FromStr for TaskStatus {
             "undone" => Ok(TaskStatus::Undone),
             "underway" => Ok(TaskStatus::Underway),
             "done" => Ok(TaskStatus::Done),
+            "cancelled" => Ok(TaskStatus::Cancelled),
+            _ if !s.is_empty() && s.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_') => {
+                Ok(TaskStatus::Custom(s.to_string()))
+            },
             _ => Err(Box::new(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
                 "Invalid task status",
@@ -38,156 +60,487 @@ impl FromStr for TaskStatus {
     }
 }
 
-impl ValueEnum for TaskStatus {
-    fn value_variants<'a>() -> &'a [Self] {
-        &[TaskStatus::Undone, TaskStatus::Underway, TaskStatus::Done]
+impl FromSql for TaskStatus {
+    fn column_result(value: ValueRef<'_>) -> Result<Self, FromSqlError> {
+        let s: String = FromSql::column_result(value)?;
+        s.parse().map_err(|_| FromSqlError::InvalidType)
     }
+}
 
-    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
-        Some(PossibleValue::new(self.to_string()))
+impl ToSql for TaskStatus {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
     }
 }
 
-impl FromSql for TaskStatus {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Priority::Low => write!(f, "low"),
+            Priority::Medium => write!(f, "medium"),
+            Priority::High => write!(f, "high"),
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" | "l" => Ok(Priority::Low),
+            "medium" | "m" => Ok(Priority::Medium),
+            "high" | "h" => Ok(Priority::High),
+            _ => Err(Box::<dyn std::error::Error>::from(format!("'{s}' isn't a valid priority (high, medium or low)"))),
+        }
+    }
+}
+
+impl FromSql for Priority {
     fn column_result(value: ValueRef<'_>) -> Result<Self, FromSqlError> {
         let s: String = FromSql::column_result(value)?;
-        match s.as_str() {
-            "undone" => Ok(TaskStatus::Undone),
-            "underway" => Ok(TaskStatus::Underway),
-            "done" => Ok(TaskStatus::Done),
-            _ => Err(FromSqlError::InvalidType),
-        }
+        s.parse().map_err(|_| FromSqlError::InvalidType)
     }
 }
 
-impl ToSql for TaskStatus {
+impl ToSql for Priority {
     fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
         Ok(self.to_string().into())
     }
 }
 
-#[derive(Debug, Clone)]
+// a tag as it is kept: lowercase letters, digits and a few separators (e.g. "release", "v1.2", "ui/forms")
+pub fn parse_tag(tag: &str) -> Result<String, Box<dyn Error>> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    match !tag.is_empty() && tag.chars().all(|c| c.is_alphanumeric() || "-_./".contains(c)) {
+        true => Ok(tag),
+        false => Err(Box::<dyn std::error::Error>::from(format!("'{tag}' isn't a valid tag (letters, digits, '-', '_', '.' and '/' only)"))),
+    }
+}
+
+// a project as it is named: letters, digits and a few separators, in any case (e.g. "website", "Website/v2")
+pub fn parse_project(project: &str) -> Result<String, Box<dyn Error>> {
+    let project = project.trim();
+    match !project.is_empty() && project.chars().all(|c| c.is_alphanumeric() || "-_./".contains(c)) {
+        true => Ok(project.to_string()),
+        false => Err(Box::<dyn std::error::Error>::from(format!("'{project}' isn't a valid project (letters, digits, '-', '_', '.' and '/' only)"))),
+    }
+}
+
+// a snapshot of a task that owns its values, for keeping past the manager it was read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskRecord {
+    pub id: i32,
+    pub title: String,
+    pub description: String,
+    pub status: TaskStatus,
+    pub cancellation_reason: Option<String>,
+    pub created_at: Option<DateTime<Local>>,
+    pub due: Option<DateTime<Local>>,
+    pub wait_until: Option<DateTime<Local>>,
+    pub estimate: Option<Duration>,
+    pub points: Option<u32>,
+    pub priority: Option<Priority>,
+    pub project: Option<String>,
+    // sorted, without duplicates
+    pub tags: Vec<String>,
+    // the ids of the tasks to close first, sorted
+    pub depends_on: Vec<i32>,
+    // oldest first
+    pub notes: Vec<Note>,
+    pub version: i64,
+}
+
+#[derive(Clone)]
 pub struct Task<'a> {
-    connection: &'a Connection,
+    store: &'a dyn TaskStore,
     id: i32,
+    // the version of the task when it was read, to notice the changes made since
+    version: Option<i64>,
+}
+
+impl<'a> std::fmt::Debug for Task<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Task").field("id", &self.id).field("version", &self.version).finish()
+    }
 }
 
 impl<'a> Task<'a> {
-    pub fn from(connection: &'a Connection, id: i32) -> Task<'a> {
-        Task { connection, id }
+    pub fn from(store: &'a dyn TaskStore, id: i32) -> Task<'a> {
+        let version = store.get(id).ok().map(|task| task.version);
+        Task { store, id, version }
     }
 
-    pub fn get_title(&self) -> Result<String, Box<dyn Error>> {
-        let mut stmt = self
-            .connection
-            .prepare(r#"SELECT "title" FROM "tasks" WHERE "id"=?1"#)?;
-        let mut result = stmt.query(params![&self.id])?;
-        if let Some(row) = result.next()? {
-            let title: String = row.get("title")?;
-            Ok(title)
-        } else {
-            Err(Box::<dyn std::error::Error>::from("Title not found"))
+    pub(crate) fn from_record(store: &'a dyn TaskStore, record: &TaskRecord) -> Task<'a> {
+        Task { store, id: record.id, version: Some(record.version) }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn to_record(&self) -> Result<TaskRecord, Box<dyn Error>> {
+        self.store.get(self.id)
+    }
+
+    // the task as it was read, for the changes that must not override the ones made since
+    // the store the task is read from, to change it along with other things at once
+    pub(crate) fn store(&self) -> &'a dyn TaskStore {
+        self.store
+    }
+
+    pub(crate) fn as_read(&self) -> Result<TaskRecord, Box<dyn Error>> {
+        let mut task = self.store.get(self.id)?;
+        if let Some(version) = self.version {
+            task.version = version;
         }
+        Ok(task)
+    }
+
+    pub fn get_title(&self) -> Result<String, Box<dyn Error>> {
+        Ok(self.to_record()?.title)
     }
 
     pub fn set_title(&mut self, title: &str) -> Result<(), Box<dyn Error>> {
-        let mut stmt = self
-            .connection
-            .prepare(r#"UPDATE "tasks" SET "title" = ?1 WHERE "id" = ?2"#)?;
-        let _ = stmt.execute(params![title, self.id]);
-        Ok(())
+        self.change(|task| task.title = title.to_string())
     }
 
     pub fn get_description(&self) -> Result<String, Box<dyn Error>> {
-        let mut stmt = self
-            .connection
-            .prepare(r#"SELECT "description" FROM "tasks" WHERE "id"=?1"#)?;
-        let mut result = stmt.query(params![&self.id])?;
-        if let Some(row) = result.next()? {
-            let title: String = row.get("description")?;
-            Ok(title)
-        } else {
-            Err(Box::<dyn std::error::Error>::from("Description not found"))
-        }
+        Ok(self.to_record()?.description)
     }
 
     pub fn set_description(&mut self, description: &str) -> Result<(), Box<dyn Error>> {
-        let mut stmt = self
-            .connection
-            .prepare(r#"UPDATE "tasks" SET "description" = ?1 WHERE "id" = ?2"#)?;
-        let _ = stmt.execute(params![description, self.id]);
-        Ok(())
+        self.change(|task| task.description = description.to_string())
     }
 
     pub fn get_status(&self) -> Result<TaskStatus, Box<dyn Error>> {
-        let mut stmt = self
-            .connection
-            .prepare(r#"SELECT "status" FROM "tasks" WHERE "id"=?1"#)?;
-        let mut result = stmt.query(params![self.id])?;
-        if let Some(row) = result.next()? {
-            let status: TaskStatus = row.get("status")?;
-            Ok(status)
-        } else {
-            Err(Box::<dyn std::error::Error>::from("Status not found"))
-        }
+        Ok(self.to_record()?.status)
     }
 
+    // the store checks the workflow allows the change
     pub fn set_status(&mut self, status: &TaskStatus) -> Result<(), Box<dyn Error>> {
-        let mut stmt = self
-            .connection
-            .prepare(r#"UPDATE "tasks" SET "status" = ?1 WHERE "id" = ?2"#)?;
-        let _ = stmt.execute(params![status, self.id]);
+        self.change(|task| {
+            task.status = status.clone();
+            task.cancellation_reason = None;
+            task.wait_until = None;
+        })
+    }
+
+    pub fn get_cancellation_reason(&self) -> Result<Option<String>, Box<dyn Error>> {
+        Ok(self.to_record()?.cancellation_reason)
+    }
+
+    pub fn get_estimate(&self) -> Result<Option<Duration>, Box<dyn Error>> {
+        Ok(self.to_record()?.estimate)
+    }
+
+    pub fn set_estimate(&mut self, estimate: Option<Duration>) -> Result<(), Box<dyn Error>> {
+        if estimate.is_some_and(|estimate| estimate <= Duration::zero()) {
+            return Err(Box::<dyn std::error::Error>::from("the estimate must be positive"))
+        }
+
+        self.change(|task| task.estimate = estimate)
+    }
+
+    pub fn get_points(&self) -> Result<Option<u32>, Box<dyn Error>> {
+        Ok(self.to_record()?.points)
+    }
+
+    pub fn set_points(&mut self, points: Option<u32>) -> Result<(), Box<dyn Error>> {
+        self.change(|task| task.points = points)
+    }
+
+    pub fn get_priority(&self) -> Result<Option<Priority>, Box<dyn Error>> {
+        Ok(self.to_record()?.priority)
+    }
+
+    pub fn set_priority(&mut self, priority: Option<Priority>) -> Result<(), Box<dyn Error>> {
+        self.change(|task| task.priority = priority)
+    }
+
+    pub fn get_project(&self) -> Result<Option<String>, Box<dyn Error>> {
+        Ok(self.to_record()?.project)
+    }
+
+    pub fn set_project(&mut self, project: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let project = project.map(parse_project).transpose()?;
+        self.change(|task| task.project = project)
+    }
+
+    pub fn get_tags(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(self.to_record()?.tags)
+    }
+
+    pub fn has_tag(&self, tag: &str) -> Result<bool, Box<dyn Error>> {
+        Ok(self.get_tags()?.iter().any(|other| other == tag))
+    }
+
+    pub fn add_tag(&mut self, tag: &str) -> Result<(), Box<dyn Error>> {
+        let tag = parse_tag(tag)?;
+        self.change(|task| task.tags.push(tag))
+    }
+
+    pub fn set_tags(&mut self, tags: &[String]) -> Result<(), Box<dyn Error>> {
+        let tags = tags.iter().map(|tag| parse_tag(tag)).collect::<Result<Vec<_>, _>>()?;
+        self.change(|task| task.tags = tags)
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) -> Result<(), Box<dyn Error>> {
+        let tag = parse_tag(tag)?;
+        self.change(|task| task.tags.retain(|other| *other != tag))
+    }
+
+    // the tasks to close before this one
+    pub fn get_dependencies(&self) -> Result<Vec<Task<'a>>, Box<dyn Error>> {
+        Ok(self.to_record()?.depends_on.into_iter().map(|id| Task::from(self.store, id)).collect())
+    }
+
+    // the store checks the other task exists, and doesn't depend on this one already
+    pub fn add_dependency(&mut self, other: &Task) -> Result<(), Box<dyn Error>> {
+        self.change(|task| task.depends_on.push(other.id))
+    }
+
+    pub fn remove_dependency(&mut self, other: &Task) -> Result<(), Box<dyn Error>> {
+        self.change(|task| task.depends_on.retain(|id| *id != other.id))
+    }
+
+    // waiting for tasks that are still open
+    pub fn is_blocked(&self) -> Result<bool, Box<dyn Error>> {
+        let workflow = Workflow::from_store(self.store);
+        for id in self.to_record()?.depends_on {
+            let status = self.store.get(id)?.status;
+            if !workflow.get_status(&status).map(|definition| definition.closed).unwrap_or(false) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // holding back open tasks, while still open itself
+    pub fn is_blocking(&self) -> Result<bool, Box<dyn Error>> {
+        let query = TaskQuery { only_open: true, include_waiting: true, ..Default::default() };
+        let open = self.store.list(&query)?;
+        Ok(open.iter().any(|task| task.id == self.id) && open.iter().any(|task| task.depends_on.contains(&self.id)))
+    }
+
+    pub fn get_notes(&self) -> Result<Vec<Note>, Box<dyn Error>> {
+        Ok(self.to_record()?.notes)
+    }
+
+    pub fn add_note<Tz: TimeZone>(&mut self, text: &str, created_at: DateTime<Tz>) -> Result<(), Box<dyn Error>> {
+        notes::check_text(text)?;
+        let note = Note { text: text.to_string(), created_at: created_at.with_timezone(&Local), edited_at: None };
+        self.change(|task| task.notes.push(note))
+    }
+
+    pub fn edit_note<Tz: TimeZone>(&mut self, number: usize, text: &str, edited_at: DateTime<Tz>) -> Result<(), Box<dyn Error>> {
+        notes::check_text(text)?;
+        let position = notes::position(&self.get_notes()?, number)?;
+        self.change(|task| {
+            task.notes[position].text = text.to_string();
+            task.notes[position].edited_at = Some(edited_at.with_timezone(&Local));
+        })
+    }
+
+    pub fn delete_note(&mut self, number: usize) -> Result<(), Box<dyn Error>> {
+        let position = notes::position(&self.get_notes()?, number)?;
+        self.change(|task| {
+            task.notes.remove(position);
+        })
+    }
+
+    pub fn get_created_at(&self) -> Result<Option<DateTime<Local>>, Box<dyn Error>> {
+        Ok(self.to_record()?.created_at)
+    }
+
+    pub fn get_wait_until(&self) -> Result<Option<DateTime<Local>>, Box<dyn Error>> {
+        Ok(self.to_record()?.wait_until)
+    }
+
+    pub fn set_wait_until<Tz: TimeZone>(&mut self, wait_until: Option<DateTime<Tz>>) -> Result<(), Box<dyn Error>> {
+        self.change(|task| task.wait_until = wait_until.map(|date| date.with_timezone(&Local)))
+    }
+
+    pub fn get_due(&self) -> Result<Option<DateTime<Local>>, Box<dyn Error>> {
+        Ok(self.to_record()?.due)
+    }
+
+    pub fn set_due<Tz: TimeZone>(&mut self, due: Option<DateTime<Tz>>) -> Result<(), Box<dyn Error>> {
+        self.change(|task| task.due = due.map(|date| date.with_timezone(&Local)))
+    }
+
+    pub fn is_overdue(&self, now: DateTime<Local>) -> Result<bool, Box<dyn Error>> {
+        let Some(due) = self.get_due()? else {
+            return Ok(false);
+        };
+
+        // closed tasks are never late
+        let closed = Workflow::from_store(self.store)
+            .get_status(&self.get_status()?)
+            .map(|definition| definition.closed)
+            .unwrap_or(false);
+        Ok(!closed && due < now)
+    }
+
+    pub fn cancel(&mut self, reason: Option<&str>) -> Result<(), Box<dyn Error>> {
+        self.change(|task| {
+            task.status = TaskStatus::Cancelled;
+            task.cancellation_reason = reason.map(str::to_string);
+            task.wait_until = None;
+        })
+    }
+
+    // saving a change of the task, unless it has been changed since it was read (e.g. by another process)
+    fn change(&mut self, apply: impl FnOnce(&mut TaskRecord)) -> Result<(), Box<dyn Error>> {
+        let mut task = self.as_read()?;
+        apply(&mut task);
+
+        let updated = self.store.update(&task)?;
+        self.version = Some(updated.version);
         Ok(())
     }
 }
 
-impl<'a> Display for Task<'a> {
+// what a task shows on its line, read at once so that the formatting itself can't fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskSummary {
+    pub record: TaskRecord,
+    pub closed: bool,
+    pub tracked: Duration,
+    pub pomodoros: usize,
+    pub now: DateTime<Local>,
+}
+
+impl<'a> Task<'a> {
+    pub fn summary(&self, now: DateTime<Local>) -> Result<TaskSummary, Box<dyn Error>> {
+        let record = self.to_record()?;
+        let closed = Workflow::from_store(self.store)
+            .get_status(&record.status)
+            .map(|definition| definition.closed)
+            .unwrap_or(false);
+
+        Ok(TaskSummary {
+            closed,
+            tracked: TimeTracker::new(self.store.data()).get_total(self, now)?,
+            pomodoros: PomodoroLog::from_store(self.store).count_completed(self)?,
+            record,
+            now,
+        })
+    }
+}
+
+impl Display for TaskSummary {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let task = &self.record;
         write!(
             f,
             "[{}] {}: {}",
-            self.get_status().unwrap(),
-            self.get_title().unwrap(),
-            self.get_description().unwrap(),
-        )
+            task.status,
+            task.title,
+            // the rest of a multi-line description is for `show`
+            task.description.lines().next().unwrap_or_default(),
+        )?;
+
+        let checklist = markdown::checklist(&task.description);
+        if !checklist.is_empty() {
+            let checked = checklist.iter().filter(|item| item.checked).count();
+            write!(f, " (checklist {checked}/{})", checklist.len())?;
+        }
+
+        if let Some(reason) = &task.cancellation_reason {
+            write!(f, " (cancelled: {reason})")?;
+        }
+
+        let points = task.points.map(|points| match points {
+            1 => "1 point".to_string(),
+            points => format!("{points} points"),
+        });
+        match (task.estimate, points) {
+            (Some(estimate), Some(points)) => write!(f, " (estimated {}, {points})", dates::format_duration(estimate))?,
+            (Some(estimate), None) => write!(f, " (estimated {})", dates::format_duration(estimate))?,
+            (None, Some(points)) => write!(f, " ({points})")?,
+            (None, None) => {},
+        }
+
+        if let Some(project) = &task.project {
+            write!(f, " (project {project})")?;
+        }
+
+        if !task.tags.is_empty() {
+            write!(f, " (tagged {})", task.tags.join(", "))?;
+        }
+
+        if self.tracked >= Duration::minutes(1) {
+            write!(f, " (tracked {})", dates::format_duration(self.tracked))?;
+        }
+
+        match self.pomodoros {
+            0 => {},
+            1 => write!(f, " (1 pomodoro)")?,
+            pomodoros => write!(f, " ({pomodoros} pomodoros)")?,
+        }
+
+        if let Some(due) = task.due {
+            // closed tasks are never late
+            match !self.closed && due < self.now {
+                true => write!(f, " (overdue since {})", dates::format_timestamp(due.timestamp()))?,
+                false => write!(f, " (due {})", dates::format_timestamp(due.timestamp()))?,
+            }
+        }
+
+        if let Some(wait_until) = task.wait_until {
+            match wait_until > self.now {
+                true => write!(f, " (waiting until {})", dates::format_timestamp(wait_until.timestamp()))?,
+                false => write!(f, " (back from waiting)")?,
+            }
+        }
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::{Duration, Local};
     use rusqlite::Connection;
 
-    use crate::manager::{task::TaskStatus, TaskManager};
+    use crate::manager::{task::TaskStatus, workflow::Workflow, TaskManager};
+
+    use crate::store::TaskStore;
 
     use super::Task;
 
-    fn set_up_connection() -> Connection {
-        // initializing a connection to a database that lives on the memory
-        let connection = Connection::open_in_memory()
+    fn set_up_manager() -> TaskManager {
+        // initializing a manager on a database that lives on the memory, with its tasks table
+        let manager = TaskManager::open_in_memory()
             .expect("unable to create in-memory database for testing purpuses");
-
-        // setting up a temporary TaskManager to initialized tasks table
-        let mut manager = TaskManager::new(&connection); // the DB has been initialized with "tasks" table
         let _ = manager.add_new_task("task title", "task description"); // added a test task
 
-        connection
+        manager
     }
 
-    fn set_up_task(connection: &Connection) -> Task {
-        Task::from(&connection, 1) // as this is the only task who lives on the db, its id will be `1`
+    fn set_up_task(store: &dyn TaskStore) -> Task<'_> {
+        Task::from(store, 1) // as this is the only task who lives on the db, its id will be `1`
     }
 
     #[test]
     fn test_get_title() {
-        let conn = set_up_connection();
-        let task = set_up_task(&conn);
+        let manager = set_up_manager();
+        let task = set_up_task(manager.store());
         assert_eq!("task title", task.get_title().unwrap());
     }
 
     #[test]
     fn test_set_title() {
-        let conn = set_up_connection();
-        let mut task = set_up_task(&conn);
+        let manager = set_up_manager();
+        let mut task = set_up_task(manager.store());
         task.set_title("new task title")
             .expect("Task should exists and database should be accessible");
         assert_eq!("new task title", task.get_title().unwrap())
@@ -195,33 +548,169 @@ mod tests {
 
     #[test]
     fn test_get_description() {
-        let conn = set_up_connection();
-        let task = set_up_task(&conn);
+        let manager = set_up_manager();
+        let task = set_up_task(manager.store());
         assert_eq!("task description", task.get_description().unwrap());
     }
 
     #[test]
     fn test_set_description() {
-        let conn = set_up_connection();
-        let mut task = set_up_task(&conn);
+        let manager = set_up_manager();
+        let mut task = set_up_task(manager.store());
         task.set_title("new task description")
             .expect("Task should exists and database should be accessible");
         assert_eq!("new task description", task.get_title().unwrap())
     }
 
+    #[test]
+    fn test_setters_report_failures() {
+        let path = std::env::temp_dir().join(format!("peppertodo-read-only-{}.db", std::process::id()));
+        let manager = TaskManager::builder().path(&path).wal(false).build().unwrap();
+        let conn = Connection::open(&path).unwrap();
+        let mut task = set_up_task(manager.store());
+
+        // a database refusing every change to the tasks
+        conn.execute_batch(r#"CREATE TRIGGER "read_only" BEFORE UPDATE ON "tasks" BEGIN SELECT RAISE(ABORT, 'read only'); END"#).unwrap();
+        assert!(task.set_title("new task title").is_err());
+        assert!(task.set_description("new task description").is_err());
+        assert!(task.set_status(&TaskStatus::Underway).is_err());
+        assert!(task.cancel(Some("no time")).is_err());
+
+        // and nothing recorded a change that didn't happen
+        let changes: i64 = conn.query_row(r#"SELECT COUNT(*) FROM "status_changes""#, [], |row| row.get(0)).unwrap();
+        assert_eq!(0, changes);
+
+        drop(manager);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_stale_update() {
+        let manager = set_up_manager();
+        let mut task = set_up_task(manager.store());
+        let mut other = set_up_task(manager.store());
+
+        // the second change was made without knowing about the first
+        other.set_title("changed elsewhere").unwrap();
+        assert!(task.set_description("new task description").is_err());
+        assert_eq!("task description", task.get_description().unwrap());
+
+        // once read again, the task can change
+        let mut task = set_up_task(manager.store());
+        task.set_description("new task description").unwrap();
+        task.set_status(&TaskStatus::Underway).unwrap();
+        assert!(other.set_status(&TaskStatus::Done).is_err());
+    }
+
+    #[test]
+    fn test_to_record() {
+        let manager = set_up_manager();
+        let mut task = set_up_task(manager.store());
+        task.set_points(Some(3)).unwrap();
+
+        // the record keeps its values after the manager is gone
+        let record = std::thread::spawn(move || manager.get_records(&Default::default()).unwrap().remove(0))
+            .join()
+            .unwrap();
+        assert_eq!("task title", record.title);
+        assert_eq!(TaskStatus::Undone, record.status);
+        assert_eq!(Some(3), record.points);
+        assert_eq!(1, record.version);
+    }
+
     #[test]
     fn test_get_status() {
-        let conn = set_up_connection();
-        let task = set_up_task(&conn);
+        let manager = set_up_manager();
+        let task = set_up_task(manager.store());
         assert_eq!(TaskStatus::Undone, task.get_status().unwrap())
     }
 
     #[test]
     fn test_set_status() {
-        let conn = set_up_connection();
-        let mut task = set_up_task(&conn);
+        let manager = set_up_manager();
+        let mut task = set_up_task(manager.store());
         task.set_status(&TaskStatus::Underway)
             .expect("Task should exists and database should be accessible");
         assert_eq!(TaskStatus::Underway, task.get_status().unwrap())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_set_status_to_unknown_status() {
+        let manager = set_up_manager();
+        let mut task = set_up_task(manager.store());
+        assert!(task.set_status(&"review".parse().unwrap()).is_err());
+        assert_eq!(TaskStatus::Undone, task.get_status().unwrap())
+    }
+
+    #[test]
+    fn test_set_status_with_forbidden_transition() {
+        let manager = set_up_manager();
+        let mut task = set_up_task(manager.store());
+        let mut workflow = Workflow::from_store(manager.store());
+        workflow.allow_transition(&TaskStatus::Undone, &TaskStatus::Underway).unwrap();
+        assert!(task.set_status(&TaskStatus::Done).is_err());
+        assert!(task.set_status(&TaskStatus::Underway).is_ok());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let manager = set_up_manager();
+        let mut task = set_up_task(manager.store());
+        task.cancel(Some("out of scope"))
+            .expect("Task should exists and database should be accessible");
+        assert_eq!(TaskStatus::Cancelled, task.get_status().unwrap());
+        assert_eq!(Some("out of scope".to_string()), task.get_cancellation_reason().unwrap());
+
+        // cancelled is a terminal status
+        assert!(task.set_status(&TaskStatus::Undone).is_err());
+    }
+
+    #[test]
+    fn test_set_wait_until() {
+        let manager = set_up_manager();
+        let mut task = set_up_task(manager.store());
+        let tomorrow = Local::now() + Duration::days(1);
+        task.set_wait_until(Some(tomorrow))
+            .expect("Task should exists and database should be accessible");
+        assert_eq!(Some(tomorrow.timestamp()), task.get_wait_until().unwrap().map(|date| date.timestamp()));
+
+        // changing the status wakes the task up
+        task.set_status(&TaskStatus::Underway).unwrap();
+        assert!(task.get_wait_until().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_set_estimate_and_points() {
+        let manager = set_up_manager();
+        let mut task = set_up_task(manager.store());
+        task.set_estimate(Some(Duration::hours(2)))
+            .expect("Task should exists and database should be accessible");
+        task.set_points(Some(3)).unwrap();
+        assert_eq!(Some(Duration::hours(2)), task.get_estimate().unwrap());
+        assert_eq!(Some(3), task.get_points().unwrap());
+        assert!(task.set_estimate(Some(Duration::zero())).is_err());
+
+        task.set_estimate(None).unwrap();
+        assert_eq!(None, task.get_estimate().unwrap());
+    }
+
+    #[test]
+    fn test_summary() {
+        let manager = set_up_manager();
+        let mut task = set_up_task(manager.store());
+        let now = Local::now();
+        task.set_points(Some(1)).unwrap();
+        task.set_due(Some(now - Duration::days(1))).unwrap();
+
+        let summary = task.summary(now).unwrap();
+        assert!(summary.to_string().starts_with("[undone] task title: "));
+        assert!(summary.to_string().contains(" (1 point) (overdue since "));
+
+        // closed tasks are never late
+        task.set_status(&TaskStatus::Done).unwrap();
+        assert!(task.summary(now).unwrap().to_string().contains(" (due "));
+
+        manager.delete(&task).unwrap();
+        assert!(task.summary(now).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use std::error::Error;
+
+use chrono::{DateTime, Duration, Local, TimeZone};
+
+use super::task::{Task, TaskStatus};
+use crate::dates;
+use crate::store::{within, DataStore};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeEntry {
+    pub id: i64,
+    pub task_id: i32,
+    pub started_at: DateTime<Local>,
+    pub ended_at: Option<DateTime<Local>>,
+}
+
+impl TimeEntry {
+    pub fn duration(&self, now: DateTime<Local>) -> Duration {
+        self.ended_at.unwrap_or(now) - self.started_at
+    }
+}
+
+#[derive(Clone)]
+pub struct TimeTracker<'a> {
+    data: &'a dyn DataStore,
+}
+
+impl<'a> TimeTracker<'a> {
+    pub fn new(data: &'a dyn DataStore) -> TimeTracker<'a> {
+        TimeTracker { data }
+    }
+
+    pub fn start(&mut self, task: &mut Task, now: DateTime<Local>) -> Result<Option<TimeEntry>, Box<dyn Error>> {
+        // all of it happens, or none of it
+        within(task.store(), || {
+            // a task being worked on is underway
+            if task.get_status()? != TaskStatus::Underway {
+                task.set_status(&TaskStatus::Underway)?;
+            }
+
+            // only one timer runs at a time
+            let stopped = self.stop(now)?;
+
+            self.data.add_time_entry(task.get_id(), now, None)?;
+
+            Ok(stopped)
+        })
+    }
+
+    pub fn stop(&mut self, now: DateTime<Local>) -> Result<Option<TimeEntry>, Box<dyn Error>> {
+        let running = self.get_running()?;
+
+        if let Some(entry) = &running {
+            self.data.end_time_entry(entry.id, now)?;
+        }
+
+        // returning the entry as it has been stored, to the second
+        let ended_at = dates::from_timestamp(now.timestamp());
+        Ok(running.map(|entry| TimeEntry { ended_at: Some(ended_at), ..entry }))
+    }
+
+    pub fn get_running(&self) -> Result<Option<TimeEntry>, Box<dyn Error>> {
+        self.data.get_running_entry()
+    }
+
+    pub fn track<Tz: TimeZone>(&mut self, task: &Task, started_at: DateTime<Tz>, duration: Duration) -> Result<(), Box<dyn Error>> {
+        if duration <= Duration::zero() {
+            return Err(Box::<dyn std::error::Error>::from("the tracked time must be positive"))
+        }
+
+        let started_at = started_at.with_timezone(&Local);
+        let ended_at = started_at
+            .checked_add_signed(duration)
+            .ok_or_else(|| Box::<dyn std::error::Error>::from("the tracked time is too long"))?;
+        self.data.add_time_entry(task.get_id(), started_at, Some(ended_at))
+    }
+
+    pub fn get_entries(&self, task: &Task) -> Result<Vec<TimeEntry>, Box<dyn Error>> {
+        self.data.get_time_entries(task.get_id())
+    }
+
+    pub fn get_entries_between<Tz: TimeZone>(&self, from: DateTime<Tz>, to: DateTime<Tz>) -> Result<Vec<TimeEntry>, Box<dyn Error>> {
+        self.data.get_time_entries_between(from.with_timezone(&Local), to.with_timezone(&Local))
+    }
+
+    pub fn get_total(&self, task: &Task, now: DateTime<Local>) -> Result<Duration, Box<dyn Error>> {
+        Ok(self
+            .get_entries(task)?
+            .iter()
+            .map(|entry| entry.duration(now))
+            .sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+    use rusqlite::Connection;
+
+    use crate::manager::{task::TaskStatus, TaskManager};
+
+    fn set_up_manager() -> TaskManager {
+        let manager = TaskManager::open_in_memory()
+            .expect("unable to create in-memory database for testing purpuses");
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        manager
+    }
+
+    #[test]
+    fn test_start_and_stop() {
+        let manager = set_up_manager();
+        let mut tracker = manager.time_tracker();
+        let mut task = manager.get_task("task A").unwrap();
+        let now = Local::now();
+
+        tracker.start(&mut task, now).unwrap();
+        assert_eq!(TaskStatus::Underway, task.get_status().unwrap());
+        assert!(tracker.get_running().unwrap().is_some());
+
+        let stopped = tracker.stop(now + Duration::minutes(25)).unwrap().unwrap();
+        assert_eq!(Duration::minutes(25), stopped.duration(now));
+        assert!(tracker.get_running().unwrap().is_none());
+        assert_eq!(Duration::minutes(25), tracker.get_total(&task, now).unwrap());
+    }
+
+    #[test]
+    fn test_start_stops_running_timer() {
+        let manager = set_up_manager();
+        let mut tracker = manager.time_tracker();
+        let mut task_a = manager.get_task("task A").unwrap();
+        let mut task_b = manager.get_task("task B").unwrap();
+        let now = Local::now();
+
+        tracker.start(&mut task_a, now).unwrap();
+        let stopped = tracker.start(&mut task_b, now + Duration::minutes(10)).unwrap();
+        assert_eq!(task_a.get_id(), stopped.unwrap().task_id);
+        assert_eq!(task_b.get_id(), tracker.get_running().unwrap().unwrap().task_id);
+    }
+
+    #[test]
+    fn test_start_changes_nothing_when_failing() {
+        let path = std::env::temp_dir().join(format!("peppertodo-tracking-{}.db", std::process::id()));
+        let manager = TaskManager::builder().path(&path).wal(false).build().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let mut tracker = manager.time_tracker();
+        let mut task_a = manager.get_task("task A").unwrap();
+        let now = Local::now();
+        tracker.start(&mut task_a, now).unwrap();
+
+        // a database refusing new time entries
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(r#"CREATE TRIGGER "no_entries" BEFORE INSERT ON "time_entries" BEGIN SELECT RAISE(ABORT, 'no entries'); END"#).unwrap();
+        let mut task_b = manager.get_task("task B").unwrap();
+        assert!(tracker.start(&mut task_b, now + Duration::minutes(10)).is_err());
+
+        // the other timer still runs, and the task hasn't moved
+        assert_eq!(task_a.get_id(), tracker.get_running().unwrap().unwrap().task_id);
+        assert_eq!(TaskStatus::Undone, manager.get_task("task B").unwrap().get_status().unwrap());
+
+        drop(manager);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_track() {
+        let manager = set_up_manager();
+        let mut tracker = manager.time_tracker();
+        let task = manager.get_task("task A").unwrap();
+        let now = Local::now();
+
+        tracker.track(&task, now - Duration::days(1), Duration::minutes(90)).unwrap();
+        tracker.track(&task, now, Duration::minutes(30)).unwrap();
+        assert!(tracker.track(&task, now, Duration::zero()).is_err());
+        assert_eq!(Duration::hours(2), tracker.get_total(&task, now).unwrap());
+    }
+
+    #[test]
+    fn test_get_entries_between() {
+        let manager = set_up_manager();
+        let mut tracker = manager.time_tracker();
+        let task = manager.get_task("task A").unwrap();
+        let now = Local::now();
+
+        tracker.track(&task, now - Duration::days(3), Duration::hours(1)).unwrap();
+        tracker.track(&task, now - Duration::hours(2), Duration::hours(1)).unwrap();
+        let entries = tracker.get_entries_between(now - Duration::days(1), now).unwrap();
+        assert_eq!(1, entries.len());
+    }
+}
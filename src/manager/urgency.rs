@@ -0,0 +1,267 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use chrono::{DateTime, Local};
+
+use super::settings::Settings;
+use super::task::{Priority, Task, TaskStatus};
+
+pub const DEFAULT_AGE_COEFFICIENT: f64 = 2.0;
+pub const DEFAULT_AGE_MAX_DAYS: f64 = 365.0;
+pub const DEFAULT_UNDERWAY_COEFFICIENT: f64 = 4.0;
+pub const DEFAULT_DUE_COEFFICIENT: f64 = 12.0;
+pub const DEFAULT_HIGH_PRIORITY_COEFFICIENT: f64 = 6.0;
+pub const DEFAULT_MEDIUM_PRIORITY_COEFFICIENT: f64 = 3.9;
+pub const DEFAULT_LOW_PRIORITY_COEFFICIENT: f64 = 1.8;
+pub const DEFAULT_BLOCKING_COEFFICIENT: f64 = 8.0;
+pub const DEFAULT_BLOCKED_COEFFICIENT: f64 = -5.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrgencyTerm {
+    pub name: String,
+    pub value: f64,
+    pub coefficient: f64,
+}
+
+impl UrgencyTerm {
+    pub fn contribution(&self) -> f64 {
+        self.value * self.coefficient
+    }
+}
+
+impl Display for UrgencyTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<20} {:>6.3} * {:>6.2} = {:>7.3}",
+            self.name,
+            self.value,
+            self.coefficient,
+            self.contribution()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Urgency {
+    pub terms: Vec<UrgencyTerm>,
+}
+
+impl Urgency {
+    pub fn score(&self) -> f64 {
+        self.terms.iter().map(UrgencyTerm::contribution).sum()
+    }
+}
+
+impl Display for Urgency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for term in &self.terms {
+            writeln!(f, "{term}")?;
+        }
+        write!(f, "{:<20} {:>26.3}", "urgency", self.score())
+    }
+}
+
+pub struct UrgencyModel<'a> {
+    settings: Settings<'a>,
+    age_coefficient: f64,
+    age_max_days: f64,
+    due_coefficient: f64,
+    blocking_coefficient: f64,
+    blocked_coefficient: f64,
+}
+
+impl<'a> UrgencyModel<'a> {
+    pub fn load(settings: Settings<'a>) -> Result<UrgencyModel<'a>, Box<dyn Error>> {
+        let age_coefficient = settings.get_or("urgency.age", DEFAULT_AGE_COEFFICIENT)?;
+        let age_max_days = settings.get_or("urgency.age.max", DEFAULT_AGE_MAX_DAYS)?;
+        let due_coefficient = settings.get_or("urgency.due", DEFAULT_DUE_COEFFICIENT)?;
+        let blocking_coefficient = settings.get_or("urgency.blocking", DEFAULT_BLOCKING_COEFFICIENT)?;
+        let blocked_coefficient = settings.get_or("urgency.blocked", DEFAULT_BLOCKED_COEFFICIENT)?;
+
+        if age_max_days <= 0.0 {
+            return Err(Box::<dyn std::error::Error>::from("'urgency.age.max' must be a positive number of days"))
+        }
+
+        Ok(UrgencyModel { settings, age_coefficient, age_max_days, due_coefficient, blocking_coefficient, blocked_coefficient })
+    }
+
+    pub fn status_coefficient(&self, status: &TaskStatus) -> Result<f64, Box<dyn Error>> {
+        let default = match status {
+            TaskStatus::Underway => DEFAULT_UNDERWAY_COEFFICIENT,
+            _ => 0.0,
+        };
+
+        self.settings.get_or(&format!("urgency.status.{status}"), default)
+    }
+
+    pub fn priority_coefficient(&self, priority: Priority) -> Result<f64, Box<dyn Error>> {
+        let default = match priority {
+            Priority::High => DEFAULT_HIGH_PRIORITY_COEFFICIENT,
+            Priority::Medium => DEFAULT_MEDIUM_PRIORITY_COEFFICIENT,
+            Priority::Low => DEFAULT_LOW_PRIORITY_COEFFICIENT,
+        };
+
+        self.settings.get_or(&format!("urgency.priority.{priority}"), default)
+    }
+
+    // tags make no difference unless given a coefficient (e.g. urgency.tag.release)
+    pub fn tag_coefficient(&self, tag: &str) -> Result<f64, Box<dyn Error>> {
+        self.settings.get_or(&format!("urgency.tag.{tag}"), 0.0)
+    }
+
+    pub fn evaluate(&self, task: &Task, now: DateTime<Local>) -> Result<Urgency, Box<dyn Error>> {
+        let mut terms = Vec::new();
+
+        // older tasks get more urgent, up to a maximum age
+        if let Some(created_at) = task.get_created_at()? {
+            let age_days = (now - created_at).num_seconds().max(0) as f64 / 86_400.0;
+            terms.push(UrgencyTerm {
+                name: "age".to_string(),
+                value: (age_days / self.age_max_days).min(1.0),
+                coefficient: self.age_coefficient,
+            });
+        }
+
+        // the closer the due date, the more urgent, up to a week past it
+        if let Some(due) = task.get_due()? {
+            let days_left = (due - now).num_seconds() as f64 / 86_400.0;
+            terms.push(UrgencyTerm {
+                name: "due".to_string(),
+                value: ((14.0 - days_left) * 0.8 / 21.0 + 0.2).clamp(0.2, 1.0),
+                coefficient: self.due_coefficient,
+            });
+        }
+
+        if let Some(priority) = task.get_priority()? {
+            terms.push(UrgencyTerm {
+                name: format!("priority ({priority})"),
+                value: 1.0,
+                coefficient: self.priority_coefficient(priority)?,
+            });
+        }
+
+        // a task holding back others should be done first, and one waiting for others can't be
+        if task.is_blocking()? {
+            terms.push(UrgencyTerm { name: "blocking".to_string(), value: 1.0, coefficient: self.blocking_coefficient });
+        }
+        if task.is_blocked()? {
+            terms.push(UrgencyTerm { name: "blocked".to_string(), value: 1.0, coefficient: self.blocked_coefficient });
+        }
+
+        for tag in task.get_tags()? {
+            let coefficient = self.tag_coefficient(&tag)?;
+            if coefficient != 0.0 {
+                terms.push(UrgencyTerm { name: format!("tag ({tag})"), value: 1.0, coefficient });
+            }
+        }
+
+        // each status can make a task more (e.g. underway) or less (e.g. blocked) urgent
+        let status = task.get_status()?;
+        let coefficient = self.status_coefficient(&status)?;
+        if coefficient != 0.0 {
+            terms.push(UrgencyTerm {
+                name: format!("status ({status})"),
+                value: 1.0,
+                coefficient,
+            });
+        }
+
+        Ok(Urgency { terms })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use crate::manager::{task::{Priority, TaskStatus}, TaskManager};
+
+    use super::{
+        UrgencyModel, DEFAULT_BLOCKED_COEFFICIENT, DEFAULT_BLOCKING_COEFFICIENT, DEFAULT_HIGH_PRIORITY_COEFFICIENT,
+        DEFAULT_UNDERWAY_COEFFICIENT,
+    };
+
+    #[test]
+    fn test_evaluate_status() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task", "desc");
+        let mut task = manager.get_task("task").unwrap();
+        let model = UrgencyModel::load(manager.settings()).unwrap();
+
+        let before = model.evaluate(&task, Local::now()).unwrap().score();
+        task.set_status(&TaskStatus::Underway).unwrap();
+        let after = model.evaluate(&task, Local::now()).unwrap().score();
+        assert!((after - before - DEFAULT_UNDERWAY_COEFFICIENT).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_evaluate_age() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task", "desc");
+        let task = manager.get_task("task").unwrap();
+        let _ = manager.settings().set("urgency.age", "10");
+        let _ = manager.settings().set("urgency.age.max", "10");
+        let model = UrgencyModel::load(manager.settings()).unwrap();
+
+        // half of the maximum age, then well past it
+        let score = model.evaluate(&task, Local::now() + Duration::days(5)).unwrap().score();
+        assert!((score - 5.0).abs() < 0.01);
+        let score = model.evaluate(&task, Local::now() + Duration::days(50)).unwrap().score();
+        assert!((score - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_evaluate_due() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task", "desc");
+        let mut task = manager.get_task("task").unwrap();
+        let _ = manager.settings().set("urgency.age", "0");
+        let _ = manager.settings().set("urgency.due", "10");
+        let model = UrgencyModel::load(manager.settings()).unwrap();
+        let now = Local::now();
+
+        // far away, then a week late
+        let _ = task.set_due(Some(now + Duration::days(30)));
+        assert!((model.evaluate(&task, now).unwrap().score() - 2.0).abs() < 0.01);
+        let _ = task.set_due(Some(now - Duration::days(7)));
+        assert!((model.evaluate(&task, now).unwrap().score() - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_evaluate_priority_dependencies_and_tags() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("release", "desc");
+        let _ = manager.add_new_task("docs", "desc");
+        let _ = manager.settings().set("urgency.age", "0");
+        let _ = manager.settings().set("urgency.tag.security", "5");
+        let model = UrgencyModel::load(manager.settings()).unwrap();
+        let now = Local::now();
+
+        let mut release = manager.get_task("release").unwrap();
+        let mut docs = manager.get_task("docs").unwrap();
+        release.set_priority(Some(Priority::High)).unwrap();
+        release.add_tag("security").unwrap();
+        assert!((model.evaluate(&release, now).unwrap().score() - DEFAULT_HIGH_PRIORITY_COEFFICIENT - 5.0).abs() < 0.01);
+
+        // the release waits for the docs
+        release.add_dependency(&docs).unwrap();
+        assert!(docs.add_dependency(&release).is_err());
+        assert!((model.evaluate(&docs, now).unwrap().score() - DEFAULT_BLOCKING_COEFFICIENT).abs() < 0.01);
+        let score = model.evaluate(&release, now).unwrap().score();
+        assert!((score - DEFAULT_HIGH_PRIORITY_COEFFICIENT - 5.0 - DEFAULT_BLOCKED_COEFFICIENT).abs() < 0.01);
+
+        // until the docs are done
+        docs.set_status(&TaskStatus::Done).unwrap();
+        assert_eq!(0.0, model.evaluate(&docs, now).unwrap().score());
+        assert!(!release.is_blocked().unwrap());
+    }
+
+    #[test]
+    fn test_load_with_invalid_coefficient() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        // e.g. set by an older version, before the values were checked
+        let _ = manager.store().data().set_setting("urgency.age", "very");
+        assert!(UrgencyModel::load(manager.settings()).is_err());
+    }
+}
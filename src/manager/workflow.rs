@@ -0,0 +1,283 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use super::task::TaskStatus;
+use super::TaskQuery;
+use crate::store::{within, TaskStore};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusDefinition {
+    pub status: TaskStatus,
+    pub closed: bool,
+}
+
+impl StatusDefinition {
+    // the statuses every task list starts with, in the order they are listed
+    pub fn built_in() -> Vec<StatusDefinition> {
+        [
+            (TaskStatus::Underway, false),
+            (TaskStatus::Undone, false),
+            (TaskStatus::Done, true),
+            (TaskStatus::Cancelled, true),
+        ]
+        .into_iter()
+        .map(|(status, closed)| StatusDefinition { status, closed })
+        .collect()
+    }
+}
+
+impl Display for StatusDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.closed {
+            true => write!(f, "{} (closed)", self.status),
+            false => write!(f, "{}", self.status),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Workflow<'a> {
+    // the tasks, to know which statuses are in use, and the workflow kept around them
+    store: &'a dyn TaskStore,
+}
+
+impl<'a> Workflow<'a> {
+    pub fn from_store(store: &'a dyn TaskStore) -> Workflow<'a> {
+        Workflow { store }
+    }
+
+    // how many tasks are marked with the status
+    fn count_tasks(&self, status: &TaskStatus) -> Result<usize, Box<dyn Error>> {
+        let query = TaskQuery { status: Some(status.clone()), ..TaskQuery::everything() };
+        Ok(self.store.list(&query)?.len())
+    }
+
+    pub fn get_statuses(&self) -> Result<Vec<StatusDefinition>, Box<dyn Error>> {
+        self.store.data().get_statuses()
+    }
+
+    pub fn get_status(&self, status: &TaskStatus) -> Result<StatusDefinition, Box<dyn Error>> {
+        self.get_statuses()?
+            .into_iter()
+            .find(|definition| definition.status == *status)
+            .ok_or_else(|| Box::<dyn std::error::Error>::from(format!("unknown status '{status}'")))
+    }
+
+    pub fn add_status(&mut self, status: &TaskStatus, closed: bool) -> Result<(), Box<dyn Error>> {
+
+        // checking for name availability
+        if self.get_status(status).is_ok() {
+            return Err(Box::<dyn std::error::Error>::from("a status with the same name already exists"))
+        }
+
+        self.store.data().add_status(&StatusDefinition { status: status.clone(), closed })
+    }
+
+    pub fn set_closed(&mut self, status: &TaskStatus, closed: bool) -> Result<(), Box<dyn Error>> {
+        self.get_status(status)?;
+        self.store.data().set_closed(status, closed)
+    }
+
+    pub fn remove_status(&mut self, status: &TaskStatus) -> Result<(), Box<dyn Error>> {
+        self.get_status(status)?;
+
+        if status.is_built_in() {
+            return Err(Box::<dyn std::error::Error>::from("built-in statuses can't be removed"))
+        }
+
+        let in_use = self.count_tasks(status)?;
+
+        if in_use > 0 {
+            return Err(Box::<dyn std::error::Error>::from(format!("{in_use} task(s) are still marked as '{status}'")))
+        }
+
+        self.store.data().remove_status(status)
+    }
+
+    pub fn get_wip_limit(&self, status: &TaskStatus) -> Result<Option<u32>, Box<dyn Error>> {
+        self.store.data().get_wip_limit(status)
+    }
+
+    pub fn set_wip_limit(&mut self, status: &TaskStatus, limit: Option<u32>) -> Result<(), Box<dyn Error>> {
+        self.get_status(status)?;
+        self.store.data().set_wip_limit(status, limit)
+    }
+
+    pub fn check_wip_limit(&self, status: &TaskStatus) -> Result<Option<(usize, u32)>, Box<dyn Error>> {
+        let Some(limit) = self.get_wip_limit(status)? else {
+            return Ok(None);
+        };
+
+        let count = self.count_tasks(status)?;
+
+        // only going over the limit is a problem, reaching it is fine
+        match count > limit as usize {
+            true => Ok(Some((count, limit))),
+            false => Ok(None),
+        }
+    }
+
+    pub fn get_transitions(&self) -> Result<Vec<(TaskStatus, TaskStatus)>, Box<dyn Error>> {
+        self.store.data().get_transitions()
+    }
+
+    // whether tasks can only move through the allowed transitions
+    pub fn is_restricted(&self) -> Result<bool, Box<dyn Error>> {
+        self.store.data().is_restricted()
+    }
+
+    pub fn allow_transition(&mut self, from: &TaskStatus, to: &TaskStatus) -> Result<(), Box<dyn Error>> {
+        self.get_status(from)?;
+        self.get_status(to)?;
+        self.store.data().add_transition(from, to)?;
+        self.store.data().set_restricted(true)
+    }
+
+    pub fn forbid_transition(&mut self, from: &TaskStatus, to: &TaskStatus) -> Result<(), Box<dyn Error>> {
+        self.get_status(from)?;
+        self.get_status(to)?;
+
+        within(self.store, || {
+            // a workflow letting tasks move freely keeps allowing every other move (cancelled tasks staying put)
+            if !self.is_restricted()? {
+                let statuses = self.get_statuses()?;
+                for source in statuses.iter().filter(|source| source.status != TaskStatus::Cancelled) {
+                    for target in statuses.iter().filter(|target| target.status != source.status) {
+                        self.allow_transition(&source.status, &target.status)?;
+                    }
+                }
+            }
+
+            self.store.data().remove_transition(from, to)?;
+            self.store.data().set_restricted(true)
+        })
+    }
+
+    // letting tasks move freely again, forgetting the allowed transitions
+    pub fn lift_restrictions(&mut self) -> Result<(), Box<dyn Error>> {
+        self.store.data().clear_transitions()?;
+        self.store.data().set_restricted(false)
+    }
+
+    pub fn check_transition(&self, from: &TaskStatus, to: &TaskStatus) -> Result<(), Box<dyn Error>> {
+        self.get_status(to)?;
+
+        let allowed = self.get_transitions()?.iter().any(|(other_from, other_to)| other_from == from && other_to == to);
+
+        if from == to || allowed {
+            Ok(())
+        } else if *from == TaskStatus::Cancelled {
+            // cancelled tasks can only be reopened through an explicitly allowed transition
+            Err(Box::<dyn std::error::Error>::from("a cancelled task can't change its status"))
+        } else if !self.is_restricted()? {
+            // an unrestricted workflow lets tasks move freely
+            Ok(())
+        } else {
+            Err(Box::<dyn std::error::Error>::from(format!("a task can't be moved from '{from}' to '{to}'")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::manager::{task::TaskStatus, TaskManager};
+
+    use super::Workflow;
+
+    fn set_up_manager() -> TaskManager {
+        // the DB is initialized with the workflow's tables
+        TaskManager::open_in_memory()
+            .expect("unable to create in-memory database for testing purpuses")
+    }
+
+    fn review() -> TaskStatus {
+        "review".parse().unwrap()
+    }
+
+    #[test]
+    fn test_get_statuses() {
+        let manager = set_up_manager();
+        let workflow = Workflow::from_store(manager.store());
+        let statuses = workflow.get_statuses().unwrap();
+        assert_eq!(4, statuses.len());
+        assert!(workflow.get_status(&TaskStatus::Done).unwrap().closed);
+    }
+
+    #[test]
+    fn test_add_status() {
+        let manager = set_up_manager();
+        let mut workflow = Workflow::from_store(manager.store());
+        workflow.add_status(&review(), false).unwrap();
+        assert_eq!(review(), workflow.get_statuses().unwrap().last().unwrap().status);
+        assert!(workflow.add_status(&review(), true).is_err());
+    }
+
+    #[test]
+    fn test_remove_status() {
+        let manager = set_up_manager();
+        let mut workflow = Workflow::from_store(manager.store());
+        let _ = workflow.add_status(&review(), false);
+        assert!(workflow.remove_status(&TaskStatus::Done).is_err());
+        assert!(workflow.remove_status(&review()).is_ok());
+        assert!(workflow.get_status(&review()).is_err());
+    }
+
+    #[test]
+    fn test_check_transition() {
+        let manager = set_up_manager();
+        let mut workflow = Workflow::from_store(manager.store());
+        let _ = workflow.add_status(&review(), false);
+
+        // without transitions, everything is allowed
+        assert!(workflow.check_transition(&TaskStatus::Undone, &TaskStatus::Done).is_ok());
+
+        workflow.allow_transition(&TaskStatus::Undone, &review()).unwrap();
+        assert!(workflow.check_transition(&TaskStatus::Undone, &review()).is_ok());
+        assert!(workflow.check_transition(&TaskStatus::Undone, &TaskStatus::Done).is_err());
+
+        // unknown statuses are never allowed
+        let unknown: TaskStatus = "unknown".parse().unwrap();
+        assert!(workflow.check_transition(&TaskStatus::Undone, &unknown).is_err());
+        assert!(workflow.forbid_transition(&TaskStatus::Undone, &unknown).is_err());
+
+        // forbidding the last allowed transition doesn't let tasks move freely again, nor does removing a status
+        workflow.forbid_transition(&TaskStatus::Undone, &review()).unwrap();
+        assert!(workflow.check_transition(&TaskStatus::Undone, &review()).is_err());
+        workflow.allow_transition(&review(), &TaskStatus::Done).unwrap();
+        workflow.remove_status(&review()).unwrap();
+        assert!(workflow.get_transitions().unwrap().is_empty());
+        assert!(workflow.check_transition(&TaskStatus::Undone, &TaskStatus::Done).is_err());
+
+        workflow.lift_restrictions().unwrap();
+        assert!(workflow.check_transition(&TaskStatus::Undone, &TaskStatus::Done).is_ok());
+    }
+
+    #[test]
+    fn test_forbid_transition_in_free_workflow() {
+        let manager = set_up_manager();
+        let mut workflow = Workflow::from_store(manager.store());
+
+        // every other move stays allowed, except reopening cancelled tasks
+        workflow.forbid_transition(&TaskStatus::Done, &TaskStatus::Undone).unwrap();
+        assert!(workflow.is_restricted().unwrap());
+        assert!(workflow.check_transition(&TaskStatus::Done, &TaskStatus::Undone).is_err());
+        assert!(workflow.check_transition(&TaskStatus::Undone, &TaskStatus::Done).is_ok());
+        assert!(workflow.check_transition(&TaskStatus::Done, &TaskStatus::Underway).is_ok());
+        assert!(workflow.check_transition(&TaskStatus::Cancelled, &TaskStatus::Undone).is_err());
+    }
+
+    #[test]
+    fn test_check_wip_limit() {
+        let manager = set_up_manager();
+        let mut workflow = Workflow::from_store(manager.store());
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+
+        assert_eq!(None, workflow.check_wip_limit(&TaskStatus::Undone).unwrap());
+        workflow.set_wip_limit(&TaskStatus::Undone, Some(2)).unwrap();
+        assert_eq!(None, workflow.check_wip_limit(&TaskStatus::Undone).unwrap());
+        workflow.set_wip_limit(&TaskStatus::Undone, Some(1)).unwrap();
+        assert_eq!(Some((2, 1)), workflow.check_wip_limit(&TaskStatus::Undone).unwrap());
+        assert!(workflow.set_wip_limit(&review(), Some(1)).is_err());
+    }
+}
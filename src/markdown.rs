@@ -0,0 +1,220 @@
+use std::error::Error;
+
+use crossterm::style::Stylize;
+
+const FENCE: &str = "```";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecklistItem {
+    pub line: usize,
+    pub checked: bool,
+    pub text: String,
+}
+
+// the `- [ ]` and `- [x]` items of a text, outside of code blocks
+pub fn checklist(text: &str) -> Vec<ChecklistItem> {
+    let mut items = Vec::new();
+    let mut in_code = false;
+
+    for (line, content) in text.lines().enumerate() {
+        if content.trim_start().starts_with(FENCE) {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        if let Some((checked, text)) = parse_checkbox(content) {
+            items.push(ChecklistItem { line, checked, text: text.to_string() });
+        }
+    }
+
+    items
+}
+
+pub fn toggle_item(text: &str, number: usize) -> Result<(String, ChecklistItem), Box<dyn Error>> {
+    let items = checklist(text);
+    let item = number
+        .checked_sub(1)
+        .and_then(|index| items.get(index))
+        .ok_or_else(|| Box::<dyn std::error::Error>::from(format!("there's no item {number}, the checklist has {}", items.len())))?;
+
+    let toggled: Vec<String> = text
+        .lines()
+        .enumerate()
+        .map(|(line, content)| match line == item.line {
+            true => match item.checked {
+                true => content.replacen("[x]", "[ ]", 1).replacen("[X]", "[ ]", 1),
+                false => content.replacen("[ ]", "[x]", 1),
+            },
+            false => content.to_string(),
+        })
+        .collect();
+
+    let toggled_item = ChecklistItem { checked: !item.checked, ..item.clone() };
+    Ok((toggled.join("\n"), toggled_item))
+}
+
+pub fn render(text: &str, styled: bool) -> String {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+    let mut checkbox = 0;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with(FENCE) {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            lines.push(style(&format!("    {line}"), styled, |text| text.dim().to_string()));
+            continue;
+        }
+
+        let indent = " ".repeat(line.len() - trimmed.len());
+        if let Some((checked, item)) = parse_checkbox(line) {
+            checkbox += 1;
+            let mark = match checked {
+                true => "[x]",
+                false => "[ ]",
+            };
+            lines.push(format!("{indent}  {mark} {checkbox}. {}", render_inline(item, styled)));
+        } else if let Some(heading) = parse_heading(trimmed) {
+            let heading = render_inline(heading, false);
+            match styled {
+                true => lines.push(heading.bold().underlined().to_string()),
+                false => {
+                    lines.push(heading.clone());
+                    lines.push("-".repeat(heading.chars().count()));
+                }
+            }
+        } else if let Some(item) = ["- ", "* ", "+ "].iter().find_map(|bullet| trimmed.strip_prefix(bullet)) {
+            lines.push(format!("{indent}  • {}", render_inline(item, styled)));
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            lines.push(format!("{indent}  │ {}", render_inline(quote.trim_start(), styled)));
+        } else if ["---", "***", "___"].contains(&trimmed.trim_end()) {
+            lines.push("─".repeat(40));
+        } else {
+            lines.push(format!("{indent}{}", render_inline(trimmed, styled)));
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn parse_checkbox(line: &str) -> Option<(bool, &str)> {
+    let item = ["- ", "* ", "+ "].iter().find_map(|bullet| line.trim_start().strip_prefix(bullet))?;
+    if let Some(text) = item.strip_prefix("[ ] ") {
+        Some((false, text))
+    } else {
+        item.strip_prefix("[x] ").or_else(|| item.strip_prefix("[X] ")).map(|text| (true, text))
+    }
+}
+
+fn parse_heading(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    match (1..=6).contains(&level) {
+        true => line[level..].strip_prefix(' ').map(str::trim),
+        false => None,
+    }
+}
+
+// the inline elements: **bold**, *italic* or _italic_, `code` and [links](url)
+fn render_inline(text: &str, styled: bool) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let element = match c {
+            '*' if rest.starts_with("**") => closing(rest, "**").map(|(inner, after)| {
+                (style(&render_inline(inner, false), styled, |text| text.bold().to_string()), after)
+            }),
+            '*' | '_' => closing(rest, &c.to_string()).map(|(inner, after)| {
+                (style(inner, styled, |text| text.italic().to_string()), after)
+            }),
+            '`' => closing(rest, "`").map(|(inner, after)| {
+                (style(inner, styled, |text| text.dim().to_string()), after)
+            }),
+            '[' => parse_link(rest).map(|(label, url, after)| {
+                (format!("{} <{url}>", style(label, styled, |text| text.underlined().to_string())), after)
+            }),
+            _ => None,
+        };
+
+        match element {
+            Some((rendered, after)) => {
+                output += &rendered;
+                rest = after;
+            },
+            None => {
+                output.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    output
+}
+
+fn closing<'t>(text: &'t str, delimiter: &str) -> Option<(&'t str, &'t str)> {
+    let inner = &text[delimiter.len()..];
+    let end = inner.find(delimiter).filter(|end| *end > 0)?;
+    Some((&inner[..end], &inner[end + delimiter.len()..]))
+}
+
+fn parse_link(text: &str) -> Option<(&str, &str, &str)> {
+    let end = text.find("](").filter(|end| !text[1..*end].contains(']'))?;
+    let after = &text[end + 2..];
+    let close = after.find(')')?;
+    Some((&text[1..end], &after[..close], &after[close + 1..]))
+}
+
+fn style(text: &str, styled: bool, apply: impl Fn(&str) -> String) -> String {
+    match styled {
+        true => apply(text),
+        false => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checklist, render, toggle_item};
+
+    const TEXT: &str = "# Plan\n\nSome **bold** and a [link](https://example.com).\n\n- [ ] first\n- [x] second\n```\n- [ ] not an item\n```\n* plain item";
+
+    #[test]
+    fn test_checklist() {
+        let items = checklist(TEXT);
+        assert_eq!(2, items.len());
+        assert!(!items[0].checked);
+        assert!(items[1].checked);
+        assert_eq!("second", items[1].text);
+    }
+
+    #[test]
+    fn test_toggle_item() {
+        let (toggled, item) = toggle_item(TEXT, 1).unwrap();
+        assert!(item.checked);
+        assert!(toggled.contains("- [x] first"));
+
+        let (toggled, _) = toggle_item(&toggled, 2).unwrap();
+        assert!(toggled.contains("- [ ] second"));
+        assert!(toggled.contains("- [ ] not an item"));
+
+        assert!(toggle_item(TEXT, 0).is_err());
+        assert!(toggle_item(TEXT, 3).is_err());
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let rendered = render(TEXT, false);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(vec!["Plan", "----"], lines[..2]);
+        assert_eq!("Some bold and a link <https://example.com>.", lines[3]);
+        assert_eq!("  [ ] 1. first", lines[5]);
+        assert_eq!("  [x] 2. second", lines[6]);
+        assert_eq!("    - [ ] not an item", lines[7]);
+        assert_eq!("  • plain item", lines[8]);
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::io::{self, IsTerminal};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::{Frame, TerminalOptions, Viewport};
+
+use crate::manager::{TaskManager, TaskQuery};
+
+const VISIBLE_ITEMS: u16 = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Picked(Vec<String>),
+    Cancelled,
+}
+
+#[derive(Debug, Clone)]
+pub struct Picker {
+    pub prompt: String,
+    pub items: Vec<String>,
+    pub query: String,
+    pub highlighted: usize,
+    pub chosen: BTreeSet<usize>,
+    pub multiple: bool,
+}
+
+impl Picker {
+    pub fn new(prompt: &str, items: Vec<String>, multiple: bool) -> Picker {
+        Picker {
+            prompt: prompt.to_string(),
+            items,
+            query: String::new(),
+            highlighted: 0,
+            chosen: BTreeSet::new(),
+            multiple,
+        }
+    }
+
+    // the indexes of the items matching the query, best matches first
+    pub fn matches(&self) -> Vec<usize> {
+        let mut scored: Vec<(i64, usize)> = self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| fuzzy_score(&self.query, item).map(|score| (score, index)))
+            .collect();
+        scored.sort_by(|(a, _), (b, _)| b.cmp(a));
+        scored.into_iter().map(|(_, index)| index).collect()
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Outcome> {
+        if key.kind != KeyEventKind::Press {
+            return None;
+        }
+
+        let matches = self.matches();
+        match key.code {
+            KeyCode::Esc => return Some(Outcome::Cancelled),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Some(Outcome::Cancelled),
+            KeyCode::Enter => {
+                // without any item chosen, the highlighted one is
+                let picked: Vec<usize> = match self.chosen.is_empty() {
+                    true => matches.get(self.highlighted).copied().into_iter().collect(),
+                    false => self.chosen.iter().copied().collect(),
+                };
+                if !picked.is_empty() {
+                    return Some(Outcome::Picked(picked.into_iter().map(|index| self.items[index].clone()).collect()));
+                }
+            },
+            KeyCode::Tab if self.multiple => {
+                if let Some(index) = matches.get(self.highlighted) {
+                    if !self.chosen.remove(index) {
+                        self.chosen.insert(*index);
+                    }
+                }
+                self.highlighted = (self.highlighted + 1).min(matches.len().saturating_sub(1));
+            },
+            KeyCode::Down => self.highlighted = (self.highlighted + 1).min(matches.len().saturating_sub(1)),
+            KeyCode::Up => self.highlighted = self.highlighted.saturating_sub(1),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.highlighted = 0;
+            },
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.highlighted = 0;
+            },
+            _ => {},
+        }
+        None
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let [input, list_area, help] = Layout::vertical([Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+
+        frame.render_widget(Paragraph::new(format!("{}: {}_", self.prompt, self.query)), input);
+
+        let matches = self.matches();
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|index| {
+                let mark = match (self.multiple, self.chosen.contains(index)) {
+                    (true, true) => "[x] ",
+                    (true, false) => "[ ] ",
+                    (false, _) => "",
+                };
+                ListItem::new(format!("{mark}{}", self.items[*index]))
+            })
+            .collect();
+        let mut state = ListState::default().with_selected(Some(self.highlighted).filter(|_| !matches.is_empty()));
+        frame.render_stateful_widget(
+            List::new(items).highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            list_area,
+            &mut state,
+        );
+
+        let keys = match self.multiple {
+            true => "type to filter  tab choose  enter confirm  esc cancel",
+            false => "type to filter  enter confirm  esc cancel",
+        };
+        frame.render_widget(Line::from(keys).dim(), help);
+    }
+
+    pub fn run(mut self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut terminal = ratatui::try_init_with_options(TerminalOptions {
+            viewport: Viewport::Inline(VISIBLE_ITEMS + 2),
+        })?;
+
+        let outcome = loop {
+            if let Err(error) = terminal.draw(|frame| self.draw(frame)) {
+                break Err(error);
+            }
+            match event::read() {
+                Ok(Event::Key(key)) => if let Some(outcome) = self.handle_key(key) {
+                    break Ok(outcome);
+                },
+                Ok(_) => {},
+                Err(error) => break Err(error),
+            }
+        };
+
+        // leaving the terminal as it was, without the picker on it
+        let _ = terminal.clear();
+        ratatui::restore();
+
+        match outcome? {
+            Outcome::Picked(picked) => Ok(picked),
+            Outcome::Cancelled => Err(Box::<dyn std::error::Error>::from("no task has been selected")),
+        }
+    }
+}
+
+pub fn pick_tasks(manager: &TaskManager, prompt: &str, multiple: bool) -> Result<Vec<String>, Box<dyn Error>> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(Box::<dyn std::error::Error>::from("a target task is needed when not running in a terminal"));
+    }
+
+    let mut titles = Vec::new();
+    for task in manager.query_tasks(&TaskQuery::default())? {
+        titles.push(task.get_title()?);
+    }
+    if titles.is_empty() {
+        return Err(Box::<dyn std::error::Error>::from("there are no tasks to choose from"));
+    }
+
+    Picker::new(prompt, titles, multiple).run()
+}
+
+pub fn resolve_targets(manager: &TaskManager, target: &Option<String>, prompt: &str, multiple: bool) -> Result<Vec<String>, Box<dyn Error>> {
+    match target {
+        Some(target) => Ok(vec![target.clone()]),
+        None => pick_tasks(manager, prompt, multiple),
+    }
+}
+
+// matching the query as a subsequence, rewarding consecutive characters and early matches
+pub fn fuzzy_score(query: &str, item: &str) -> Option<i64> {
+    let item: Vec<char> = item.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for c in query.to_lowercase().chars() {
+        let found = position + item[position..].iter().position(|candidate| *candidate == c)?;
+        score += match previous {
+            Some(previous) if previous + 1 == found => 5,
+            _ => 1 - found.min(10) as i64,
+        };
+        previous = Some(found);
+        position = found + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{fuzzy_score, Outcome, Picker};
+
+    fn press(picker: &mut Picker, code: KeyCode) -> Option<Outcome> {
+        picker.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn items() -> Vec<String> {
+        vec!["write docs".to_string(), "fix the bug".to_string(), "review the docs".to_string()]
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("wd", "write docs").is_some());
+        assert!(fuzzy_score("dw", "write docs").is_none());
+        assert!(fuzzy_score("docs", "write docs") > fuzzy_score("dcs", "write docs"));
+        assert_eq!(Some(0), fuzzy_score("", "anything"));
+    }
+
+    #[test]
+    fn test_pick_one() {
+        let mut picker = Picker::new("Task", items(), false);
+        for c in "bug".chars() {
+            press(&mut picker, KeyCode::Char(c));
+        }
+        assert_eq!(vec![1], picker.matches());
+        assert_eq!(Some(Outcome::Picked(vec!["fix the bug".to_string()])), press(&mut picker, KeyCode::Enter));
+    }
+
+    #[test]
+    fn test_pick_many() {
+        let mut picker = Picker::new("Tasks", items(), true);
+        for c in "docs".chars() {
+            press(&mut picker, KeyCode::Char(c));
+        }
+        press(&mut picker, KeyCode::Tab);
+        press(&mut picker, KeyCode::Tab);
+        let Some(Outcome::Picked(mut picked)) = press(&mut picker, KeyCode::Enter) else {
+            panic!("nothing picked");
+        };
+        picked.sort();
+        assert_eq!(vec!["review the docs", "write docs"], picked);
+        assert_eq!(Some(Outcome::Cancelled), press(&mut picker, KeyCode::Esc));
+    }
+}
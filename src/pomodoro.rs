@@ -0,0 +1,101 @@
+use std::error::Error;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use chrono::{Duration, Local};
+
+use crate::dates;
+use crate::manager::pomodoro::{DEFAULT_BREAK_DURATION, DEFAULT_WORK_DURATION};
+use crate::manager::{settings::Settings, TaskManager};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PomodoroDurations {
+    pub work: Duration,
+    pub rest: Duration,
+}
+
+impl PomodoroDurations {
+    pub fn load(settings: &Settings) -> Result<PomodoroDurations, Box<dyn Error>> {
+        let work = settings.get("pomodoro.work")?.unwrap_or(DEFAULT_WORK_DURATION.to_string());
+        let rest = settings.get("pomodoro.break")?.unwrap_or(DEFAULT_BREAK_DURATION.to_string());
+
+        Ok(PomodoroDurations {
+            work: dates::parse_duration(&work)?,
+            rest: dates::parse_duration(&rest)?,
+        })
+    }
+}
+
+pub fn install_interrupt_handler() -> Result<(), Box<dyn Error>> {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+
+    // Ctrl-C ends the current interval, instead of the whole process
+    match ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst)) {
+        Ok(()) | Err(ctrlc::Error::MultipleHandlers) => Ok(()),
+        Err(error) => Err(Box::new(error)),
+    }
+}
+
+pub fn run_pomodoro(manager: &TaskManager, target: &str, rounds: usize) -> Result<(), Box<dyn Error>> {
+    let durations = PomodoroDurations::load(&manager.settings())?;
+    let mut task = manager.get_task(target)?;
+    let title = task.get_title()?;
+
+    for round in 1..=rounds {
+        let started_at = Local::now();
+        manager.time_tracker().start(&mut task, started_at)?;
+
+        let completed = countdown(&format!("Pomodoro {round}/{rounds} on '{title}'"), durations.work)?;
+
+        // the interval is recorded even when interrupted, as partial
+        let ended_at = Local::now();
+        manager.time_tracker().stop(ended_at)?;
+        manager.pomodoro_log().record(&task, started_at, ended_at, completed)?;
+        ring_bell();
+
+        if !completed {
+            println!("Interrupted, {} recorded on '{title}'", dates::format_duration(ended_at - started_at));
+            return Ok(());
+        }
+
+        println!("Pomodoro completed, time for a break!");
+        if !countdown("Break", durations.rest)? {
+            return Ok(());
+        }
+        ring_bell();
+    }
+
+    Ok(())
+}
+
+fn countdown(label: &str, duration: Duration) -> Result<bool, Box<dyn Error>> {
+    let end = dates::after(Local::now(), duration)?;
+
+    loop {
+        let remaining = end - Local::now();
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            println!();
+            return Ok(false);
+        }
+
+        if remaining <= Duration::zero() {
+            println!("\r{label}: 00:00");
+            return Ok(true);
+        }
+
+        let seconds = (remaining.num_milliseconds() + 999) / 1000;
+        print!("\r{label}: {:02}:{:02} ", seconds / 60, seconds % 60);
+        let _ = io::stdout().flush();
+
+        // waking up often, so that Ctrl-C is handled promptly
+        thread::sleep(remaining.min(Duration::milliseconds(200)).to_std().unwrap_or_default());
+    }
+}
+
+fn ring_bell() {
+    print!("\x07");
+    let _ = io::stdout().flush();
+}
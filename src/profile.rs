@@ -0,0 +1,151 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection};
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub path: PathBuf,
+    pub active: bool,
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let marker = if self.active { "*" } else { " " };
+        write!(f, "{} {}: {}", marker, self.name, self.path.display())
+    }
+}
+
+pub struct ProfileManager<'a> {
+    connection: &'a Connection,
+}
+
+impl<'a> ProfileManager<'a> {
+    pub fn new(connection: &'a Connection) -> ProfileManager<'a> {
+        // initializing, if not present, the working table for ProfileManager
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS profiles (
+                "name" text PRIMARY KEY,
+                "path" text,
+                "active" integer DEFAULT 0
+            )"#, ()
+        );
+
+        ProfileManager { connection }
+    }
+
+    pub fn create_profile(&mut self, name: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+
+        // checking for name validity and availability
+        if name.is_empty() || name == DEFAULT_PROFILE || name.contains(['/', '\\']) {
+            return Err(Box::<dyn std::error::Error>::from("invalid profile name"))
+        }
+
+        if self.get_profile(name).is_ok() {
+            return Err(Box::<dyn std::error::Error>::from("a profile with the same name already exists"))
+        }
+
+        let result = self.connection.execute(
+            r#"INSERT INTO "profiles" ("name", "path") VALUES (?1, ?2)"#,
+            params![name, path.to_string_lossy()],
+        );
+
+        Ok(result.map(|_| ())?)
+    }
+
+    pub fn get_all_profiles(&self) -> Result<Vec<Profile>, Box<dyn Error>> {
+        let mut stmt = self
+            .connection
+            .prepare(r#"SELECT "name", "path", "active" FROM "profiles" ORDER BY "name""#)?;
+        let profiles = stmt
+            .query_map([], |row| {
+                Ok(Profile {
+                    name: row.get("name")?,
+                    path: PathBuf::from(row.get::<_, String>("path")?),
+                    active: row.get("active")?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(profiles)
+    }
+
+    pub fn get_profile(&self, name: &str) -> Result<Profile, Box<dyn Error>> {
+        self.get_all_profiles()?
+            .into_iter()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| Box::<dyn std::error::Error>::from(format!("No profile named '{name}' found")))
+    }
+
+    pub fn get_active_profile(&self) -> Result<Option<Profile>, Box<dyn Error>> {
+        Ok(self.get_all_profiles()?.into_iter().find(|profile| profile.active))
+    }
+
+    pub fn use_profile(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+
+        // the default profile is selected by simply deactivating all the others
+        if name != DEFAULT_PROFILE {
+            self.get_profile(name)?;
+        }
+
+        self.connection.execute(
+            r#"UPDATE "profiles" SET "active" = ("name" = ?1)"#,
+            params![name],
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use rusqlite::Connection;
+
+    use super::{ProfileManager, DEFAULT_PROFILE};
+
+    #[test]
+    fn test_create_profile() {
+        let conn = Connection::open_in_memory().unwrap();
+        let mut manager = ProfileManager::new(&conn);
+        let _ = manager.create_profile("work", Path::new("work.db"));
+        assert_eq!(Path::new("work.db"), manager.get_profile("work").unwrap().path);
+    }
+
+    #[test]
+    fn test_create_profile_with_invalid_name() {
+        let conn = Connection::open_in_memory().unwrap();
+        let mut manager = ProfileManager::new(&conn);
+        assert!(manager.create_profile(DEFAULT_PROFILE, Path::new("other.db")).is_err());
+        assert!(manager.create_profile("a/b", Path::new("other.db")).is_err());
+    }
+
+    #[test]
+    fn test_create_profile_with_already_taken_name() {
+        let conn = Connection::open_in_memory().unwrap();
+        let mut manager = ProfileManager::new(&conn);
+        let _ = manager.create_profile("work", Path::new("work.db"));
+        assert!(manager.create_profile("work", Path::new("other.db")).is_err());
+    }
+
+    #[test]
+    fn test_use_profile() {
+        let conn = Connection::open_in_memory().unwrap();
+        let mut manager = ProfileManager::new(&conn);
+        let _ = manager.create_profile("work", Path::new("work.db"));
+        let _ = manager.create_profile("home", Path::new("home.db"));
+
+        manager.use_profile("work").unwrap();
+        assert_eq!("work", manager.get_active_profile().unwrap().unwrap().name);
+
+        manager.use_profile(DEFAULT_PROFILE).unwrap();
+        assert!(manager.get_active_profile().unwrap().is_none());
+
+        assert!(manager.use_profile("missing").is_err());
+    }
+}
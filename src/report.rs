@@ -0,0 +1,495 @@
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::str::FromStr;
+
+use chrono::{DateTime, Days, Duration, Local, NaiveDate, NaiveTime};
+use serde::Serialize;
+
+use crate::dates;
+use crate::manager::{settings::Settings, task::TaskStatus, TaskManager, TaskQuery};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    Up,
+    Down,
+    Nearest,
+}
+
+impl FromStr for RoundingMode {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(RoundingMode::Up),
+            "down" => Ok(RoundingMode::Down),
+            "nearest" => Ok(RoundingMode::Nearest),
+            _ => Err(Box::<dyn std::error::Error>::from("Invalid rounding mode")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rounding {
+    pub step: Duration,
+    pub mode: RoundingMode,
+}
+
+impl Rounding {
+    pub fn load(settings: Settings) -> Result<Rounding, Box<dyn Error>> {
+        let step = match settings.get("report.rounding")? {
+            Some(step) if step != "0" => dates::parse_duration(&step)?,
+            _ => Duration::zero(),
+        };
+        let mode = settings.get_or("report.rounding.mode", RoundingMode::Nearest)?;
+
+        Ok(Rounding { step, mode })
+    }
+
+    pub fn apply(&self, duration: Duration) -> Duration {
+        let step = self.step.num_seconds();
+        if step <= 0 {
+            return duration;
+        }
+
+        let seconds = duration.num_seconds();
+        let steps = match self.mode {
+            RoundingMode::Up => (seconds + step - 1) / step,
+            RoundingMode::Down => seconds / step,
+            RoundingMode::Nearest => (seconds + step / 2) / step,
+        };
+
+        Duration::seconds(steps * step)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimeReportRow {
+    pub date: NaiveDate,
+    pub task: String,
+    pub project: Option<String>,
+    pub minutes: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimeReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub rows: Vec<TimeReportRow>,
+}
+
+impl TimeReport {
+    pub fn build(manager: &TaskManager, from: NaiveDate, to: NaiveDate, rounding: &Rounding, now: DateTime<Local>) -> Result<TimeReport, Box<dyn Error>> {
+        if to < from {
+            return Err(Box::<dyn std::error::Error>::from("the report can't end before it starts"))
+        }
+
+        let start = dates::from_naive(from.and_time(NaiveTime::MIN))?;
+        let end = dates::from_naive((to + Days::new(1)).and_time(NaiveTime::MIN))?;
+
+        // splitting every entry into the days it spans, within the report
+        let mut totals: BTreeMap<(NaiveDate, String, Option<String>), Duration> = BTreeMap::new();
+        for entry in manager.time_tracker().get_entries_between(start, end)? {
+            let task = manager.get_task_by_id(entry.task_id)?.to_record()?;
+            let mut current = entry.started_at.max(start);
+            let entry_end = entry.ended_at.unwrap_or(now).min(end);
+
+            while current < entry_end {
+                let next_day = dates::from_naive((current.date_naive() + Days::new(1)).and_time(NaiveTime::MIN))?;
+                let segment_end = entry_end.min(next_day);
+                *totals.entry((current.date_naive(), task.title.clone(), task.project.clone())).or_insert(Duration::zero()) += segment_end - current;
+                current = segment_end;
+            }
+        }
+
+        let rows = totals
+            .into_iter()
+            .map(|((date, task, project), duration)| TimeReportRow { date, task, project, minutes: rounding.apply(duration).num_minutes() })
+            .filter(|row| row.minutes > 0)
+            .collect();
+
+        Ok(TimeReport { from, to, rows })
+    }
+
+    pub fn total_by_day(&self) -> Vec<(NaiveDate, i64)> {
+        let mut totals: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+        self.rows.iter().for_each(|row| *totals.entry(row.date).or_default() += row.minutes);
+        totals.into_iter().collect()
+    }
+
+    pub fn total_by_task(&self) -> Vec<(String, i64)> {
+        let mut totals: BTreeMap<String, i64> = BTreeMap::new();
+        self.rows.iter().for_each(|row| *totals.entry(row.task.clone()).or_default() += row.minutes);
+        totals.into_iter().collect()
+    }
+
+    // the time spent on the tasks without a project comes last
+    pub fn total_by_project(&self) -> Vec<(Option<String>, i64)> {
+        let mut totals: BTreeMap<(bool, Option<String>), i64> = BTreeMap::new();
+        self.rows.iter().for_each(|row| *totals.entry((row.project.is_none(), row.project.clone())).or_default() += row.minutes);
+        totals.into_iter().map(|((_, project), minutes)| (project, minutes)).collect()
+    }
+
+    pub fn total(&self) -> i64 {
+        self.rows.iter().map(|row| row.minutes).sum()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Time report from {} to {}\n", self.from.format("%a %Y-%m-%d"), self.to.format("%a %Y-%m-%d"));
+
+        for (date, minutes) in self.total_by_day() {
+            text += &format!("\n{:<40} {:>8}\n", date.format("%a %Y-%m-%d").to_string(), format_minutes(minutes));
+            self.rows.iter()
+                .filter(|row| row.date == date)
+                .for_each(|row| text += &format!("  {:<38} {:>8}\n", row.task, format_minutes(row.minutes)));
+        }
+
+        text += "\nPer project\n";
+        for (project, minutes) in self.total_by_project() {
+            text += &format!("  {:<38} {:>8}\n", project.as_deref().unwrap_or(NO_PROJECT), format_minutes(minutes));
+        }
+
+        text += "\nPer task\n";
+        for (task, minutes) in self.total_by_task() {
+            text += &format!("  {:<38} {:>8}\n", task, format_minutes(minutes));
+        }
+
+        text += &format!("\n{:<40} {:>8}", "Total", format_minutes(self.total()));
+        text
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("date,project,task,minutes,hours\n");
+        for row in &self.rows {
+            let project = csv_field(row.project.as_deref().unwrap_or_default());
+            csv += &format!("{},{project},{},{},{:.2}\n", row.date, csv_field(&row.task), row.minutes, row.minutes as f64 / 60.0);
+        }
+        csv.trim_end().to_string()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("| Date | Project | Task | Time |\n|------|---------|------|-----:|\n");
+        for row in &self.rows {
+            let project = markdown_field(row.project.as_deref().unwrap_or_default());
+            markdown += &format!("| {} | {project} | {} | {} |\n", row.date, markdown_field(&row.task), format_minutes(row.minutes));
+        }
+        markdown += &format!("| | | **Total** | **{}** |", format_minutes(self.total()));
+        markdown
+    }
+
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        #[derive(Serialize)]
+        struct Total<T> {
+            key: T,
+            minutes: i64,
+        }
+
+        #[derive(Serialize)]
+        struct Json<'r> {
+            #[serde(flatten)]
+            report: &'r TimeReport,
+            per_day: Vec<Total<NaiveDate>>,
+            per_project: Vec<Total<Option<String>>>,
+            per_task: Vec<Total<String>>,
+            total_minutes: i64,
+        }
+
+        let json = Json {
+            report: self,
+            per_day: self.total_by_day().into_iter().map(|(key, minutes)| Total { key, minutes }).collect(),
+            per_project: self.total_by_project().into_iter().map(|(key, minutes)| Total { key, minutes }).collect(),
+            per_task: self.total_by_task().into_iter().map(|(key, minutes)| Total { key, minutes }).collect(),
+            total_minutes: self.total(),
+        };
+
+        Ok(serde_json::to_string_pretty(&json)?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EstimateRow {
+    pub task: String,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    pub status: TaskStatus,
+    pub closed: bool,
+    pub estimate: Option<Duration>,
+    pub points: Option<u32>,
+    pub actual: Duration,
+}
+
+impl EstimateRow {
+    pub fn collect(manager: &TaskManager, now: DateTime<Local>) -> Result<Vec<EstimateRow>, Box<dyn Error>> {
+        let workflow = manager.workflow();
+        let tracker = manager.time_tracker();
+        // a snoozed task still has its estimate and its tracked time
+        let query = TaskQuery::everything();
+
+        let mut rows = Vec::new();
+        for task in manager.query_tasks(&query)? {
+            let (estimate, points) = (task.get_estimate()?, task.get_points()?);
+
+            // only the estimated tasks matter here
+            if estimate.is_none() && points.is_none() {
+                continue;
+            }
+
+            let record = task.to_record()?;
+            let status = record.status;
+            rows.push(EstimateRow {
+                task: record.title,
+                project: record.project,
+                tags: record.tags,
+                closed: workflow.get_status(&status).map(|definition| definition.closed).unwrap_or(false),
+                status,
+                estimate,
+                points,
+                actual: tracker.get_total(&task, now)?,
+            });
+        }
+
+        Ok(rows)
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.estimate
+            .map(|estimate| (estimate - self.actual).max(Duration::zero()))
+            .unwrap_or(Duration::zero())
+    }
+}
+
+pub fn effort_to_text(rows: &[EstimateRow]) -> String {
+    let mut text = format!("{:<30} {:>9} {:>9} {:>9} {:>6}\n", "Task", "Estimate", "Tracked", "Remaining", "Points");
+
+    for row in rows {
+        text += &format!(
+            "{:<30} {:>9} {:>9} {:>9} {:>6}\n",
+            row.task,
+            row.estimate.map(dates::format_duration).unwrap_or_default(),
+            dates::format_duration(row.actual),
+            dates::format_duration(row.remaining()),
+            row.points.map(|points| points.to_string()).unwrap_or_default(),
+        );
+    }
+
+    let remaining: Duration = rows.iter().map(EstimateRow::remaining).sum();
+    let points: u32 = rows.iter().filter_map(|row| row.points).sum();
+    text += &format!("{:<30} {:>9} {:>9} {:>9} {:>6}", "Total", "", "", dates::format_duration(remaining), points);
+
+    let projects = per_project(rows);
+    if projects.iter().any(|(project, _)| project != NO_PROJECT) {
+        text += "\n\nPer project";
+        for (project, rows) in projects {
+            let remaining: Duration = rows.iter().map(|row| row.remaining()).sum();
+            let points: u32 = rows.iter().filter_map(|row| row.points).sum();
+            text += &format!("\n  {:<28} {:>9} {:>9} {:>9} {:>6}", project, "", "", dates::format_duration(remaining), points);
+        }
+    }
+    text
+}
+
+pub fn accuracy_to_text(rows: &[EstimateRow]) -> String {
+    let mut text = format!("{:<30} {:>9} {:>9} {:>7}\n", "Task", "Estimate", "Actual", "Ratio");
+
+    let estimated: Vec<&EstimateRow> = rows.iter().filter(|row| row.estimate.is_some()).collect();
+    for row in &estimated {
+        let estimate = row.estimate.unwrap_or_default();
+        text += &format!(
+            "{:<30} {:>9} {:>9} {:>7}\n",
+            row.task,
+            dates::format_duration(estimate),
+            dates::format_duration(row.actual),
+            format_ratio(row.actual, estimate),
+        );
+    }
+
+    let estimate: Duration = estimated.iter().filter_map(|row| row.estimate).sum();
+    let actual: Duration = estimated.iter().map(|row| row.actual).sum();
+    text += &format!(
+        "{:<30} {:>9} {:>9} {:>7}",
+        "Total",
+        dates::format_duration(estimate),
+        dates::format_duration(actual),
+        format_ratio(actual, estimate),
+    );
+
+    // the estimates of some projects, or of some kinds of tasks, may be further off than others
+    let projects = per_project(&estimated);
+    if projects.iter().any(|(project, _)| project != NO_PROJECT) {
+        text += "\n\nPer project";
+        text += &groups_accuracy(&projects);
+    }
+    let tags = per_tag(&estimated);
+    if !tags.is_empty() {
+        text += "\n\nPer tag";
+        text += &groups_accuracy(&tags);
+    }
+
+    // how long a story point has actually taken, on average
+    let pointed: Vec<&EstimateRow> = rows.iter().filter(|row| row.points.is_some_and(|points| points > 0)).collect();
+    let points: i64 = pointed.iter().filter_map(|row| row.points).map(i64::from).sum();
+    if points > 0 {
+        let actual: Duration = pointed.iter().map(|row| row.actual).sum();
+        let average = Duration::milliseconds(actual.num_milliseconds() / points);
+        text += &format!("\n\nOne story point took {} on average", dates::format_duration(average));
+    }
+
+    text
+}
+
+fn groups_accuracy(groups: &[(String, Vec<&EstimateRow>)]) -> String {
+    let mut text = String::new();
+    for (name, rows) in groups {
+        let estimate: Duration = rows.iter().filter_map(|row| row.estimate).sum();
+        let actual: Duration = rows.iter().map(|row| row.actual).sum();
+        text += &format!(
+            "\n  {:<28} {:>9} {:>9} {:>7}",
+            name,
+            dates::format_duration(estimate),
+            dates::format_duration(actual),
+            format_ratio(actual, estimate),
+        );
+    }
+    text
+}
+
+// the rows of each project, the ones without a project last
+fn per_project(rows: &[impl Borrow<EstimateRow>]) -> Vec<(String, Vec<&EstimateRow>)> {
+    let mut groups: BTreeMap<(bool, String), Vec<&EstimateRow>> = BTreeMap::new();
+    for row in rows.iter().map(|row| row.borrow()) {
+        let key = match &row.project {
+            Some(project) => (false, project.clone()),
+            None => (true, NO_PROJECT.to_string()),
+        };
+        groups.entry(key).or_default().push(row);
+    }
+    groups.into_iter().map(|((_, name), rows)| (name, rows)).collect()
+}
+
+// the rows of each tag, a task counting for each of its tags
+fn per_tag(rows: &[impl Borrow<EstimateRow>]) -> Vec<(String, Vec<&EstimateRow>)> {
+    let mut groups: BTreeMap<String, Vec<&EstimateRow>> = BTreeMap::new();
+    for row in rows.iter().map(|row| row.borrow()) {
+        row.tags.iter().for_each(|tag| groups.entry(tag.clone()).or_default().push(row));
+    }
+    groups.into_iter().collect()
+}
+
+fn format_ratio(actual: Duration, estimate: Duration) -> String {
+    match estimate > Duration::zero() {
+        true => format!("{:.2}x", actual.num_seconds() as f64 / estimate.num_seconds() as f64),
+        false => String::new(),
+    }
+}
+
+const NO_PROJECT: &str = "(no project)";
+
+fn format_minutes(minutes: i64) -> String {
+    dates::format_duration(Duration::minutes(minutes))
+}
+
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+fn markdown_field(field: &str) -> String {
+    field.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Days, Duration, Local, NaiveDate, NaiveTime};
+
+    use crate::dates;
+    use crate::manager::TaskManager;
+
+    use super::{accuracy_to_text, effort_to_text, EstimateRow, Rounding, RoundingMode, TimeReport};
+
+    fn no_rounding() -> Rounding {
+        Rounding { step: Duration::zero(), mode: RoundingMode::Nearest }
+    }
+
+    #[test]
+    fn test_rounding() {
+        let quarter = |mode| Rounding { step: Duration::minutes(15), mode };
+        assert_eq!(Duration::minutes(15), quarter(RoundingMode::Up).apply(Duration::minutes(1)));
+        assert_eq!(Duration::zero(), quarter(RoundingMode::Down).apply(Duration::minutes(14)));
+        assert_eq!(Duration::minutes(30), quarter(RoundingMode::Nearest).apply(Duration::minutes(23)));
+        assert_eq!(Duration::minutes(7), no_rounding().apply(Duration::minutes(7)));
+    }
+
+    #[test]
+    fn test_build() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task, A", "desc");
+        let _ = manager.add_new_task("task B", "desc");
+        let day = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let mut tracker = manager.time_tracker();
+
+        // an entry across midnight is split between the two days
+        let task_a = manager.get_task("task, A").unwrap();
+        let mut task_b = manager.get_task("task B").unwrap();
+        task_b.set_project(Some("website")).unwrap();
+        let _ = tracker.track(&task_a, dates::from_naive(day.and_time(NaiveTime::MIN)).unwrap() + Duration::hours(23), Duration::hours(2));
+        let _ = tracker.track(&task_b, dates::from_naive(day.and_time(NaiveTime::MIN)).unwrap() + Duration::hours(9), Duration::minutes(30));
+
+        let report = TimeReport::build(&manager, day, day + Days::new(1), &no_rounding(), Local::now()).unwrap();
+        assert_eq!(3, report.rows.len());
+        assert_eq!(vec![(day, 90), (day + Days::new(1), 60)], report.total_by_day());
+        assert_eq!(150, report.total());
+        assert_eq!(vec![(Some("website".to_string()), 30), (None, 120)], report.total_by_project());
+
+        // the entry outside the report is cut
+        let report = TimeReport::build(&manager, day, day, &no_rounding(), Local::now()).unwrap();
+        assert_eq!(90, report.total());
+
+        assert!(report.to_csv().contains(",\"task, A\",60,1.00"));
+        assert!(report.to_csv().contains(",website,task B,30,0.50"));
+        assert!(report.to_markdown().ends_with("| | | **Total** | **1h30m** |"));
+        assert!(report.to_text().contains("(no project)"));
+        assert!(report.to_json().unwrap().contains("\"total_minutes\": 90"));
+    }
+
+    #[test]
+    fn test_estimate_rows() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc");
+        let _ = manager.add_new_task("task B", "desc");
+        let mut task_a = manager.get_task("task A").unwrap();
+        let _ = task_a.set_estimate(Some(Duration::hours(2)));
+        let _ = task_a.set_points(Some(2));
+        let _ = task_a.set_project(Some("website"));
+        let _ = task_a.add_tag("frontend");
+        let _ = manager.time_tracker().track(&task_a, Local::now() - Duration::hours(4), Duration::hours(3));
+        let _ = task_a.set_status(&crate::manager::task::TaskStatus::Done);
+
+        // only the estimated tasks are collected
+        let rows = EstimateRow::collect(&manager, Local::now()).unwrap();
+        assert_eq!(1, rows.len());
+        assert!(rows[0].closed);
+        assert_eq!(Duration::zero(), rows[0].remaining());
+
+        let text = accuracy_to_text(&rows);
+        assert!(text.contains("1.50x"));
+        assert!(text.contains("Per project\n  website                             2h        3h   1.50x"));
+        assert!(text.contains("Per tag\n  frontend                            2h        3h   1.50x"));
+        assert!(text.contains("One story point took 1h30m on average"));
+
+        // the open tasks of each project still need their remaining estimate
+        let _ = manager.add_new_task("task C", "desc");
+        let mut task_c = manager.get_task("task C").unwrap();
+        let _ = task_c.set_estimate(Some(Duration::hours(1)));
+        let _ = task_c.set_project(Some("website"));
+        let mut task_b = manager.get_task("task B").unwrap();
+        let _ = task_b.set_estimate(Some(Duration::minutes(30)));
+        // snoozed, but still to be done
+        let _ = task_b.set_wait_until(Some(Local::now() + Duration::days(2)));
+        let rows: Vec<EstimateRow> = EstimateRow::collect(&manager, Local::now()).unwrap().into_iter().filter(|row| !row.closed).collect();
+        let text = effort_to_text(&rows);
+        assert!(text.contains("Per project\n  website"));
+        assert!(text.ends_with("(no project)                                           30m      0"));
+    }
+}
@@ -0,0 +1,659 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, NaiveDate};
+use clap::ValueEnum;
+
+use crate::manager::attachments::{Attachment, AttachmentKind};
+use crate::manager::history::{StatusChange, StatusHistory};
+use crate::manager::notes::{check_text, Note};
+use crate::manager::pomodoro::TaskPomodoros;
+use crate::manager::stats::{count_per_period, AverageTimes, Period};
+use crate::manager::task::{parse_project, parse_tag, TaskRecord, TaskStatus};
+use crate::manager::tracking::TimeEntry;
+use crate::manager::workflow::{StatusDefinition, Workflow};
+use crate::manager::TaskQuery;
+
+pub mod file;
+pub mod memory;
+pub mod sqlite;
+
+// the setting recording where the tasks of a database are kept
+pub const STORE_SETTING: &str = "store";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StoreKind {
+    /// In the database, along with everything else
+    Sqlite,
+    /// In a JSON file next to the database, readable and editable by hand
+    Json,
+}
+
+impl Display for StoreKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreKind::Sqlite => write!(f, "sqlite"),
+            StoreKind::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl FromStr for StoreKind {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sqlite" => Ok(StoreKind::Sqlite),
+            "json" => Ok(StoreKind::Json),
+            _ => Err(Box::<dyn std::error::Error>::from(format!("unknown store '{s}'"))),
+        }
+    }
+}
+
+// where the tasks are kept: an SQLite database, the memory or a file meant to be edited by hand
+pub trait TaskStore: Send {
+    // everything kept around the tasks: the workflow, settings, time entries, attachments...
+    fn data(&self) -> &dyn DataStore;
+
+    fn create(&self, title: &str, description: &str) -> Result<TaskRecord, Box<dyn Error>>;
+
+    fn get(&self, id: i32) -> Result<TaskRecord, Box<dyn Error>>;
+
+    fn find(&self, title: &str) -> Result<Option<TaskRecord>, Box<dyn Error>> {
+        Ok(self.list(&TaskQuery::everything())?.into_iter().find(|task| task.title == title))
+    }
+
+    fn list(&self, query: &TaskQuery) -> Result<Vec<TaskRecord>, Box<dyn Error>>;
+
+    // saving every value of the task, unless it has been changed since it was read
+    fn update(&self, task: &TaskRecord) -> Result<TaskRecord, Box<dyn Error>>;
+
+    // deleting the task, unless it has been changed since it was read
+    fn delete(&self, task: &TaskRecord) -> Result<(), Box<dyn Error>>;
+
+    // deleting the closed tasks, the cancelled ones only on request, and giving back their ids
+    fn clear(&self, include_cancelled: bool) -> Result<Vec<i32>, Box<dyn Error>>;
+
+    // how many tasks have each status, leaving out the statuses no task has
+    fn count_by_status(&self) -> Result<Vec<(TaskStatus, usize)>, Box<dyn Error>> {
+        let mut counts: Vec<(TaskStatus, usize)> = Vec::new();
+        for task in self.list(&TaskQuery::everything())? {
+            match counts.iter_mut().find(|(status, _)| *status == task.status) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((task.status, 1)),
+            }
+        }
+        Ok(counts)
+    }
+
+    // how many tasks were created in each period, the earliest period first
+    fn count_created(&self, period: Period) -> Result<Vec<(NaiveDate, usize)>, Box<dyn Error>> {
+        let tasks = self.list(&TaskQuery::everything())?;
+        Ok(count_per_period(tasks.iter().filter_map(|task| task.created_at), period))
+    }
+
+    // how many tasks now in one of the statuses got there in each period, the last time they did
+    fn count_completed(&self, period: Period, statuses: &[TaskStatus]) -> Result<Vec<(NaiveDate, usize)>, Box<dyn Error>> {
+        let completions = completions(self, statuses)?;
+        Ok(count_per_period(completions.iter().map(|completion| completion.completed_at), period))
+    }
+
+    // the average lead and cycle times of the tasks now in one of the statuses
+    fn average_times(&self, statuses: &[TaskStatus]) -> Result<AverageTimes, Box<dyn Error>> {
+        let completions = completions(self, statuses)?;
+        let lead_times: Vec<i64> = completions
+            .iter()
+            .filter_map(|completion| completion.created_at.map(|created_at| (completion.completed_at - created_at).num_seconds()))
+            .collect();
+        let cycle_times: Vec<i64> = completions
+            .iter()
+            .filter_map(|completion| completion.started_at.map(|started_at| (completion.completed_at - started_at).num_seconds()))
+            .filter(|cycle_time| *cycle_time >= 0)
+            .collect();
+
+        let average = |seconds: &[i64]| (!seconds.is_empty()).then(|| seconds.iter().sum::<i64>() as f64 / seconds.len() as f64);
+        Ok(AverageTimes::from_seconds(average(&lead_times), average(&cycle_times)))
+    }
+
+    // keeping every change made by `apply`, or none of them when it fails
+    fn atomically(&self, apply: &mut dyn FnMut() -> Result<(), Box<dyn Error>>) -> Result<(), Box<dyn Error>>;
+}
+
+// everything kept around the tasks, as plain values: the checks are left to the manager
+pub trait DataStore: Send {
+    fn get_setting(&self, key: &str) -> Result<Option<String>, Box<dyn Error>>;
+
+    // every setting, by name
+    fn get_settings(&self) -> Result<Vec<(String, String)>, Box<dyn Error>>;
+
+    fn set_setting(&self, key: &str, value: &str) -> Result<(), Box<dyn Error>>;
+
+    fn unset_setting(&self, key: &str) -> Result<(), Box<dyn Error>>;
+
+    // the statuses, in the order they are listed
+    fn get_statuses(&self) -> Result<Vec<StatusDefinition>, Box<dyn Error>>;
+
+    // adding the status after the others
+    fn add_status(&self, definition: &StatusDefinition) -> Result<(), Box<dyn Error>>;
+
+    fn set_closed(&self, status: &TaskStatus, closed: bool) -> Result<(), Box<dyn Error>>;
+
+    // removing the status along with its transitions
+    fn remove_status(&self, status: &TaskStatus) -> Result<(), Box<dyn Error>>;
+
+    fn get_wip_limit(&self, status: &TaskStatus) -> Result<Option<u32>, Box<dyn Error>>;
+
+    fn set_wip_limit(&self, status: &TaskStatus, limit: Option<u32>) -> Result<(), Box<dyn Error>>;
+
+    // the allowed transitions, in the order of the statuses they start from
+    fn get_transitions(&self) -> Result<Vec<(TaskStatus, TaskStatus)>, Box<dyn Error>>;
+
+    fn add_transition(&self, from: &TaskStatus, to: &TaskStatus) -> Result<(), Box<dyn Error>>;
+
+    fn remove_transition(&self, from: &TaskStatus, to: &TaskStatus) -> Result<(), Box<dyn Error>>;
+
+    fn clear_transitions(&self) -> Result<(), Box<dyn Error>>;
+
+    // whether tasks can only move through the allowed transitions
+    fn is_restricted(&self) -> Result<bool, Box<dyn Error>>;
+
+    fn set_restricted(&self, restricted: bool) -> Result<(), Box<dyn Error>>;
+
+    // the dates are kept to the second, like everything else
+    fn add_time_entry(&self, task_id: i32, started_at: DateTime<Local>, ended_at: Option<DateTime<Local>>) -> Result<(), Box<dyn Error>>;
+
+    fn end_time_entry(&self, id: i64, ended_at: DateTime<Local>) -> Result<(), Box<dyn Error>>;
+
+    // the entry of the timer still running, the latest one if there are several
+    fn get_running_entry(&self) -> Result<Option<TimeEntry>, Box<dyn Error>>;
+
+    // the entries of the task, the earliest first
+    fn get_time_entries(&self, task_id: i32) -> Result<Vec<TimeEntry>, Box<dyn Error>>;
+
+    // the entries overlapping [from, to), the earliest first
+    fn get_time_entries_between(&self, from: DateTime<Local>, to: DateTime<Local>) -> Result<Vec<TimeEntry>, Box<dyn Error>>;
+
+    fn add_pomodoro(&self, task_id: i32, started_at: DateTime<Local>, ended_at: DateTime<Local>, completed: bool) -> Result<(), Box<dyn Error>>;
+
+    fn count_completed_pomodoros(&self, task_id: i32) -> Result<usize, Box<dyn Error>>;
+
+    fn count_pomodoros(&self) -> Result<Vec<TaskPomodoros>, Box<dyn Error>>;
+
+    fn add_status_change(&self, task_id: i32, status: &TaskStatus, changed_at: DateTime<Local>) -> Result<(), Box<dyn Error>>;
+
+    // the changes of the task, or of every task, the earliest first
+    fn get_status_changes(&self, task_id: Option<i32>) -> Result<Vec<StatusChange>, Box<dyn Error>>;
+
+    fn add_attachment(&self, task_id: i32, kind: AttachmentKind, name: &str, location: &str, added_at: DateTime<Local>) -> Result<(), Box<dyn Error>>;
+
+    // the attachments of the task, the earliest first
+    fn get_attachments(&self, task_id: i32) -> Result<Vec<Attachment>, Box<dyn Error>>;
+
+    fn delete_attachments(&self, task_id: i32) -> Result<(), Box<dyn Error>>;
+
+    // whether a task still refers to the copy kept there
+    fn is_copy_attached(&self, location: &str) -> Result<bool, Box<dyn Error>>;
+
+    // removing the time entries, pomodoros and status changes of the task
+    fn forget_task(&self, task_id: i32) -> Result<(), Box<dyn Error>>;
+
+    // the highest task id anything kept refers to, so that a new task never inherits what belonged to a deleted one
+    fn last_task_id(&self) -> Result<i32, Box<dyn Error>>;
+
+    // keeping every change made by `apply`, or none of them when it fails
+    fn atomically(&self, apply: &mut dyn FnMut() -> Result<(), Box<dyn Error>>) -> Result<(), Box<dyn Error>>;
+}
+
+// running `apply` through the store's `atomically`, and handing back its result
+pub(crate) fn within<T>(store: &dyn TaskStore, apply: impl FnOnce() -> Result<T, Box<dyn Error>>) -> Result<T, Box<dyn Error>> {
+    let mut apply = Some(apply);
+    let mut result = None;
+    store.atomically(&mut || {
+        if let Some(apply) = apply.take() {
+            result = Some(apply()?);
+        }
+        Ok(())
+    })?;
+    result.ok_or_else(|| Box::<dyn std::error::Error>::from("the change hasn't been applied"))
+}
+
+fn check_version(stored: &TaskRecord, task: &TaskRecord) -> Result<(), Box<dyn Error>> {
+    match stored.version == task.version {
+        true => Ok(()),
+        false => Err(Box::<dyn std::error::Error>::from("the task has been changed elsewhere since it was read, nothing has been saved")),
+    }
+}
+
+fn check_title(store: &dyn TaskStore, title: &str, id: Option<i32>) -> Result<(), Box<dyn Error>> {
+    match store.find(title)? {
+        Some(other) if Some(other.id) != id => Err(Box::<dyn std::error::Error>::from("a task with the same title already exists")),
+        _ => Ok(()),
+    }
+}
+
+// the task as it will be saved over the stored one, following the same rules in every store
+fn prepare_update(store: &dyn TaskStore, stored: &TaskRecord, task: &TaskRecord) -> Result<TaskRecord, Box<dyn Error>> {
+    check_version(stored, task)?;
+    if stored.title != task.title {
+        check_title(store, &task.title, Some(task.id))?;
+    }
+
+    let mut updated = TaskRecord { version: stored.version + 1, ..task.clone() };
+    updated.tags = normalize_tags(&task.tags)?;
+    updated.project = task.project.as_deref().map(parse_project).transpose()?;
+    updated.notes = normalize_notes(&task.notes)?;
+    updated.depends_on = normalize_dependencies(task);
+    if updated.depends_on != stored.depends_on {
+        check_dependencies(store, &updated)?;
+    }
+    if stored.status != task.status {
+        // checking the workflow allows the change
+        Workflow::from_store(store).check_transition(&stored.status, &task.status)?;
+
+        // a task moving on isn't waiting anymore, nor cancelled unless that's where it goes
+        updated.wait_until = None;
+        if task.status != TaskStatus::Cancelled {
+            updated.cancellation_reason = None;
+        }
+    }
+    Ok(updated)
+}
+
+// the tags checked, sorted and without duplicates
+fn normalize_tags(tags: &[String]) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut tags = tags.iter().map(|tag| parse_tag(tag)).collect::<Result<Vec<_>, _>>()?;
+    tags.sort();
+    tags.dedup();
+    Ok(tags)
+}
+
+fn normalize_dependencies(task: &TaskRecord) -> Vec<i32> {
+    let mut depends_on = task.depends_on.clone();
+    depends_on.sort();
+    depends_on.dedup();
+    depends_on
+}
+
+// the tasks depended on exist, and none of them waits for this one, even through others
+fn check_dependencies(store: &dyn TaskStore, task: &TaskRecord) -> Result<(), Box<dyn Error>> {
+    let mut pending = task.depends_on.clone();
+    let mut seen = Vec::new();
+    while let Some(id) = pending.pop() {
+        if id == task.id {
+            return Err(Box::<dyn std::error::Error>::from(format!("'{}' can't depend on a task that depends on it", task.title)));
+        }
+        if seen.contains(&id) {
+            continue;
+        }
+        let other = store.get(id).map_err(|_| format!("'{}' can't depend on a missing task ({id})", task.title))?;
+        pending.extend(other.depends_on);
+        seen.push(id);
+    }
+    Ok(())
+}
+
+// the notes checked, oldest first
+fn normalize_notes(notes: &[Note]) -> Result<Vec<Note>, Box<dyn Error>> {
+    for note in notes {
+        check_text(&note.text)?;
+    }
+    let mut notes = notes.to_vec();
+    notes.sort_by_key(|note| note.created_at);
+    Ok(notes)
+}
+
+// keeping track of when the task moved, for the statistics
+struct Completion {
+    created_at: Option<DateTime<Local>>,
+    // the first time the task got underway
+    started_at: Option<DateTime<Local>>,
+    // the last time the task got to its status
+    completed_at: DateTime<Local>,
+}
+
+// the tasks in one of the statuses, for the stores that can't count them with a query
+fn completions<S: TaskStore + ?Sized>(store: &S, statuses: &[TaskStatus]) -> Result<Vec<Completion>, Box<dyn Error>> {
+    // the changes are the earliest first
+    let changes = store.data().get_status_changes(None)?;
+    let mut completions = Vec::new();
+    for task in store.list(&TaskQuery::everything())?.into_iter().filter(|task| statuses.contains(&task.status)) {
+        let completed = changes.iter().rev().find(|change| change.task_id == task.id && change.status == task.status);
+        if let Some(completed) = completed {
+            let started = changes.iter().find(|change| change.task_id == task.id && change.status == TaskStatus::Underway);
+            completions.push(Completion {
+                created_at: task.created_at,
+                started_at: started.map(|change| change.changed_at),
+                completed_at: completed.changed_at,
+            });
+        }
+    }
+    Ok(completions)
+}
+
+fn record_status_change(store: &dyn TaskStore, stored: &TaskRecord, updated: &TaskRecord) -> Result<(), Box<dyn Error>> {
+    if stored.status != updated.status {
+        StatusHistory::new(store.data()).record(updated.id, &updated.status, Local::now())?;
+    }
+    Ok(())
+}
+
+// the same selection and order as the SQLite query, for the stores that hold their tasks in memory
+fn select(store: &dyn TaskStore, tasks: &[TaskRecord], query: &TaskQuery, now: DateTime<Local>) -> Result<Vec<TaskRecord>, Box<dyn Error>> {
+    // the statuses missing from the workflow come last, and are open
+    let statuses = Workflow::from_store(store).get_statuses()?;
+    let position = |status: &TaskStatus| statuses.iter().position(|definition| definition.status == *status).unwrap_or(statuses.len());
+    let is_closed = |status: &TaskStatus| statuses.iter().any(|definition| definition.status == *status && definition.closed);
+
+    let mut selected: Vec<TaskRecord> = tasks
+        .iter()
+        .filter(|task| match &query.status {
+            Some(status) => task.status == *status,
+            None => query.include_cancelled || task.status != TaskStatus::Cancelled,
+        })
+        .filter(|task| !query.only_open || !is_closed(&task.status))
+        // due dates are filtered within [from, to)
+        .filter(|task| query.due_from.is_none_or(|from| task.due.is_some_and(|due| due >= from)))
+        .filter(|task| query.due_to.is_none_or(|to| task.due.is_some_and(|due| due < to)))
+        .filter(|task| query.tags.iter().all(|tag| task.tags.contains(tag)))
+        .filter(|task| query.project.is_none() || task.project == query.project)
+        .filter(|task| match (query.waiting, query.include_waiting) {
+            (true, _) => task.wait_until.is_some_and(|wait_until| wait_until > now),
+            (false, true) => true,
+            (false, false) => task.wait_until.is_none_or(|wait_until| wait_until <= now),
+        })
+        .cloned()
+        .collect();
+
+    selected.sort_by_key(|task| (position(&task.status), task.id));
+    Ok(selected)
+}
+
+// the closed tasks `clear` deletes, by the same rules in every store
+fn clearable(store: &dyn TaskStore, tasks: &[TaskRecord], include_cancelled: bool) -> Result<Vec<i32>, Box<dyn Error>> {
+    let closed: Vec<TaskStatus> = Workflow::from_store(store)
+        .get_statuses()?
+        .into_iter()
+        .filter(|definition| definition.closed)
+        .map(|definition| definition.status)
+        .collect();
+
+    Ok(tasks
+        .iter()
+        .filter(|task| closed.contains(&task.status) && (include_cancelled || task.status != TaskStatus::Cancelled))
+        .map(|task| task.id)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use chrono::{Duration, Local};
+    use rusqlite::Connection;
+
+    use crate::dates;
+    use crate::manager::attachments::AttachmentKind;
+    use crate::manager::notes::Note;
+    use crate::manager::stats::{AverageTimes, Period};
+    use crate::manager::workflow::StatusDefinition;
+    use crate::manager::{task::{Priority, TaskStatus}, TaskManager, TaskQuery};
+
+    use super::memory::{MemoryData, MemoryStore};
+    use super::{file::FileStore, sqlite::{SqliteData, SqliteStore}, DataStore, TaskStore};
+
+    // every store must behave the same way
+    fn check_store(store: &dyn TaskStore) {
+        let mut docs = store.create("write the docs", "for the release").unwrap();
+        let bug = store.create("fix the bug", "in the parser").unwrap();
+        store.create("release", "").unwrap();
+        assert!(store.create("release", "again").is_err());
+        assert_eq!(TaskStatus::Undone, docs.status);
+
+        docs.status = TaskStatus::Underway;
+        docs.due = Some(Local::now() - Duration::days(1));
+        docs.estimate = Some(Duration::hours(2));
+        docs.tags = vec!["release".to_string(), "Docs".to_string(), "release".to_string()];
+        let note = |text: &str, timestamp: i64| Note { text: text.to_string(), created_at: dates::from_timestamp(timestamp), edited_at: None };
+        docs.notes = vec![note("second", 1_700_000_060), note("first", 1_700_000_000)];
+        docs.priority = Some(Priority::High);
+        docs.project = Some("website".to_string());
+        docs.depends_on = vec![bug.id, bug.id];
+        let docs = store.update(&docs).unwrap();
+        assert_eq!(docs, store.get(docs.id).unwrap());
+        assert_eq!(vec!["docs", "release"], docs.tags);
+        assert_eq!(vec![note("first", 1_700_000_000), note("second", 1_700_000_060)], docs.notes);
+        assert_eq!(vec![bug.id], docs.depends_on);
+
+        // a task read before the change isn't saved over, nor deleted
+        let mut stale = store.get(bug.id).unwrap();
+        store.update(&bug).unwrap();
+        stale.title = "fix the parser".to_string();
+        assert!(store.update(&stale).is_err());
+        assert!(store.delete(&stale).is_err());
+        assert_eq!("fix the bug", store.get(bug.id).unwrap().title);
+
+        // titles stay unique
+        let mut renamed = store.get(bug.id).unwrap();
+        renamed.title = "release".to_string();
+        assert!(store.update(&renamed).is_err());
+
+        let titles = |query: &TaskQuery| -> Vec<String> {
+            store.list(query).unwrap().into_iter().map(|task| task.title).collect()
+        };
+        assert_eq!(vec!["write the docs", "fix the bug", "release"], titles(&TaskQuery::default()));
+        let overdue = TaskQuery { only_open: true, due_to: Some(Local::now()), ..Default::default() };
+        assert_eq!(vec!["write the docs"], titles(&overdue));
+        let tagged = TaskQuery { tags: vec!["release".to_string()], ..Default::default() };
+        assert_eq!(vec!["write the docs"], titles(&tagged));
+        let in_project = TaskQuery { project: Some("website".to_string()), ..Default::default() };
+        assert_eq!(vec!["write the docs"], titles(&in_project));
+        let mut invalid = store.get(bug.id).unwrap();
+        invalid.tags = vec!["two words".to_string()];
+        assert!(store.update(&invalid).is_err());
+        let mut invalid = store.get(bug.id).unwrap();
+        invalid.notes = vec![note(" ", 1_700_000_000)];
+        assert!(store.update(&invalid).is_err());
+        // dependencies never go round in circles, nor to missing tasks
+        let mut invalid = store.get(bug.id).unwrap();
+        invalid.depends_on = vec![docs.id];
+        assert!(store.update(&invalid).is_err());
+        invalid.depends_on = vec![99];
+        assert!(store.update(&invalid).is_err());
+
+        // the workflow decides which statuses are closed, and which moves are allowed
+        let wontfix: TaskStatus = "wontfix".parse().unwrap();
+        let mut workflow = crate::manager::workflow::Workflow::from_store(store);
+        workflow.add_status(&wontfix, true).unwrap();
+        workflow.allow_transition(&TaskStatus::Undone, &wontfix).unwrap();
+        let mut release = store.find("release").unwrap().unwrap();
+        release.status = TaskStatus::Done;
+        assert!(store.update(&release).is_err());
+        release.status = wontfix;
+        store.update(&release).unwrap();
+        assert_eq!(vec![release.id], store.clear(false).unwrap());
+
+        store.delete(&store.get(bug.id).unwrap()).unwrap();
+        assert!(store.get(bug.id).is_err());
+        assert!(store.get(docs.id).unwrap().depends_on.is_empty());
+        assert_eq!(vec!["write the docs"], titles(&TaskQuery::default()));
+
+        // a failed change leaves everything as it was
+        let result = super::within(store, || {
+            store.create("draft", "")?;
+            store.create("draft", "")
+        });
+        assert!(result.is_err());
+        assert!(store.find("draft").unwrap().is_none());
+
+        // the statistics come out the same whichever way they are counted
+        workflow.allow_transition(&TaskStatus::Undone, &TaskStatus::Underway).unwrap();
+        workflow.allow_transition(&TaskStatus::Underway, &TaskStatus::Done).unwrap();
+        let mut shipped = store.create("ship", "").unwrap();
+        shipped.status = TaskStatus::Underway;
+        let mut shipped = store.update(&shipped).unwrap();
+        shipped.status = TaskStatus::Done;
+        store.update(&shipped).unwrap();
+        let counts = store.count_by_status().unwrap();
+        assert_eq!(2, counts.len());
+        assert!(counts.contains(&(TaskStatus::Underway, 1)) && counts.contains(&(TaskStatus::Done, 1)));
+        let today = Local::now().date_naive();
+        assert_eq!(vec![(Period::Week.start_of(today), 2)], store.count_created(Period::Week).unwrap());
+        assert_eq!(vec![(today, 1)], store.count_completed(Period::Day, &[TaskStatus::Done]).unwrap());
+        assert!(store.count_completed(Period::Day, &[]).unwrap().is_empty());
+        let averages = AverageTimes { lead_time_minutes: Some(0), cycle_time_minutes: Some(0) };
+        assert_eq!(averages, store.average_times(&[TaskStatus::Done]).unwrap());
+    }
+
+    // everything around the tasks must be kept the same way too
+    fn check_data(data: &dyn DataStore) {
+        let now = dates::from_timestamp(1_700_000_000);
+
+        data.set_setting("report.width", "80").unwrap();
+        data.set_setting("report.width", "100").unwrap();
+        data.set_setting("burndown.points", "true").unwrap();
+        assert_eq!(Some("100".to_string()), data.get_setting("report.width").unwrap());
+        assert_eq!("burndown.points", data.get_settings().unwrap()[0].0);
+        data.unset_setting("report.width").unwrap();
+        assert_eq!(None, data.get_setting("report.width").unwrap());
+
+        // the built-in statuses come first, the others after them
+        let review: TaskStatus = "review".parse().unwrap();
+        data.add_status(&StatusDefinition { status: review.clone(), closed: false }).unwrap();
+        assert_eq!(5, data.get_statuses().unwrap().len());
+        assert_eq!(review, data.get_statuses().unwrap()[4].status);
+        data.set_wip_limit(&review, Some(2)).unwrap();
+        assert_eq!(Some(2), data.get_wip_limit(&review).unwrap());
+        data.add_transition(&review, &TaskStatus::Done).unwrap();
+        data.add_transition(&TaskStatus::Underway, &review).unwrap();
+        data.add_transition(&TaskStatus::Underway, &review).unwrap();
+        assert_eq!(vec![(TaskStatus::Underway, review.clone()), (review.clone(), TaskStatus::Done)], data.get_transitions().unwrap());
+        data.remove_status(&review).unwrap();
+        assert!(data.get_transitions().unwrap().is_empty());
+        assert!(!data.is_restricted().unwrap());
+
+        data.add_time_entry(1, now - Duration::hours(3), Some(now - Duration::hours(2))).unwrap();
+        data.add_time_entry(2, now - Duration::minutes(30), None).unwrap();
+        let running = data.get_running_entry().unwrap().unwrap();
+        assert_eq!(2, running.task_id);
+        data.end_time_entry(running.id, now).unwrap();
+        assert!(data.get_running_entry().unwrap().is_none());
+        assert_eq!(1, data.get_time_entries_between(now - Duration::hours(1), now).unwrap().len());
+
+        data.add_pomodoro(1, now, now, true).unwrap();
+        data.add_pomodoro(1, now, now, false).unwrap();
+        assert_eq!(1, data.count_completed_pomodoros(1).unwrap());
+        assert_eq!(vec![(1, 1, 1)], data.count_pomodoros().unwrap());
+
+        data.add_status_change(1, &TaskStatus::Done, now).unwrap();
+        data.add_status_change(1, &TaskStatus::Underway, now - Duration::hours(1)).unwrap();
+        let statuses: Vec<TaskStatus> = data.get_status_changes(Some(1)).unwrap().into_iter().map(|change| change.status).collect();
+        assert_eq!(vec![TaskStatus::Underway, TaskStatus::Done], statuses);
+
+        data.add_attachment(1, AttachmentKind::Copy, "design.txt", "/copies/abc.txt", now).unwrap();
+        data.add_attachment(2, AttachmentKind::Copy, "design.txt", "/copies/abc.txt", now).unwrap();
+        data.delete_attachments(1).unwrap();
+        assert!(data.get_attachments(1).unwrap().is_empty());
+        assert!(data.is_copy_attached("/copies/abc.txt").unwrap());
+
+        data.forget_task(1).unwrap();
+        assert!(data.get_time_entries(1).unwrap().is_empty());
+        assert!(data.get_status_changes(None).unwrap().is_empty());
+        assert_eq!(1, data.get_time_entries(2).unwrap().len());
+
+        // a failed change leaves everything as it was
+        let result = data.atomically(&mut || {
+            data.set_setting("report.width", "120")?;
+            Err(Box::<dyn std::error::Error>::from("failed"))
+        });
+        assert!(result.is_err());
+        assert_eq!(None, data.get_setting("report.width").unwrap());
+    }
+
+    #[test]
+    fn test_sqlite_data() {
+        check_data(&SqliteData::new(Connection::open_in_memory().unwrap()));
+    }
+
+    #[test]
+    fn test_memory_data() {
+        check_data(&MemoryData::default());
+    }
+
+    #[test]
+    fn test_sqlite_store() {
+        check_store(&SqliteStore::new(Connection::open_in_memory().unwrap()));
+    }
+
+    #[test]
+    fn test_memory_store() {
+        check_store(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_file_store() {
+        let path = env::temp_dir().join(format!("peppertodo-store-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        check_store(&FileStore::open(&path, SqliteData::new(Connection::open_in_memory().unwrap())).unwrap());
+
+        // the tasks are read back from the file
+        let store = FileStore::open(&path, SqliteData::new(Connection::open_in_memory().unwrap())).unwrap();
+        assert_eq!(2, store.list(&TaskQuery::default()).unwrap().len());
+        assert_eq!(Some(Duration::hours(2)), store.get(1).unwrap().estimate);
+        assert_eq!(vec!["docs", "release"], store.get(1).unwrap().tags);
+        assert_eq!(2, store.get(1).unwrap().notes.len());
+        assert_eq!(Some(Priority::High), store.get(1).unwrap().priority);
+
+        // tasks added by hand only need a title
+        std::fs::write(&path, r#"[{ "id": 4, "title": "review" }, { "title": "publish", "estimate": "30m" }]"#).unwrap();
+        let store = FileStore::open(&path, SqliteData::new(Connection::open_in_memory().unwrap())).unwrap();
+        let publish = store.get(5).unwrap();
+        assert_eq!((TaskStatus::Undone, Some(Duration::minutes(30))), (publish.status, publish.estimate));
+        assert_eq!(6, store.create("announce", "").unwrap().id);
+
+        std::fs::write(&path, r#"[{ "title": "review", "status": "Done!" }]"#).unwrap();
+        assert!(FileStore::open(&path, SqliteData::new(Connection::open_in_memory().unwrap())).is_err());
+        std::fs::write(&path, r#"[{ "title": "review", "depends_on": [2] }]"#).unwrap();
+        assert!(FileStore::open(&path, SqliteData::new(Connection::open_in_memory().unwrap())).is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_manager_on_memory_store() {
+        let manager = TaskManager::new(MemoryStore::new());
+        manager.add_new_task("task", "desc").unwrap();
+        let task = manager.get_task("task").unwrap();
+
+        // the time tracked goes back along with the tasks when a change fails
+        let result = manager.transaction(|manager| {
+            manager.time_tracker().track(&task, Local::now(), Duration::hours(1))?;
+            manager.add_new_task("task", "again")
+        });
+        assert!(result.is_err());
+        assert!(manager.time_tracker().get_entries(&task).unwrap().is_empty());
+        manager.time_tracker().track(&task, Local::now(), Duration::hours(1)).unwrap();
+        assert_eq!(1, manager.time_tracker().get_entries(&task).unwrap().len());
+    }
+
+    #[test]
+    fn test_manager_on_file_store() {
+        let path = env::temp_dir().join(format!("peppertodo-manager-store-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let manager = TaskManager::new(FileStore::open(&path, SqliteData::new(Connection::open_in_memory().unwrap())).unwrap());
+        manager.add_new_task("task", "desc").unwrap();
+        manager.get_task("task").unwrap().set_status(&TaskStatus::Underway).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains(r#""status": "underway""#));
+
+        // a failed transaction doesn't reach the file
+        let result = manager.transaction(|manager| {
+            manager.delete_task("task")?;
+            manager.get_task("missing").map(|_| ())
+        });
+        assert!(result.is_err());
+        assert!(std::fs::read_to_string(&path).unwrap().contains(r#""title": "task""#));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
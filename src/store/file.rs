@@ -0,0 +1,215 @@
+use std::cell::Cell;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::dates;
+use crate::manager::notes::Note;
+use crate::manager::task::{parse_project, TaskRecord};
+use crate::manager::TaskQuery;
+
+use super::memory::MemoryStore;
+use super::{normalize_notes, normalize_tags, within, DataStore, TaskStore};
+
+// a task as written in the file: plain values, and nothing but the title is needed when adding one by hand
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileTask {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<i32>,
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default = "undone")]
+    status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cancellation_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wait_until: Option<DateTime<Local>>,
+    // written like on the command line (e.g. "1h30m")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimate: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    points: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    // the ids of the tasks to close first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    notes: Vec<Note>,
+    #[serde(default)]
+    version: i64,
+}
+
+fn undone() -> String {
+    "undone".to_string()
+}
+
+impl From<&TaskRecord> for FileTask {
+    fn from(task: &TaskRecord) -> Self {
+        FileTask {
+            id: Some(task.id),
+            title: task.title.clone(),
+            description: task.description.clone(),
+            status: task.status.to_string(),
+            cancellation_reason: task.cancellation_reason.clone(),
+            created_at: task.created_at,
+            due: task.due,
+            wait_until: task.wait_until,
+            estimate: task.estimate.map(dates::format_duration),
+            points: task.points,
+            priority: task.priority.map(|priority| priority.to_string()),
+            project: task.project.clone(),
+            tags: task.tags.clone(),
+            depends_on: task.depends_on.clone(),
+            notes: task.notes.clone(),
+            version: task.version,
+        }
+    }
+}
+
+// tasks kept in a JSON file, readable and editable by hand (e.g. to keep them in git along with a project),
+// while everything around them is kept elsewhere (e.g. in an SQLite database)
+pub struct FileStore {
+    path: PathBuf,
+    tasks: MemoryStore,
+    // how many changes are under way, the file being written when the outermost one succeeds
+    depth: Cell<usize>,
+}
+
+impl FileStore {
+    // reading the tasks from the file, which is created with the first change when missing
+    pub fn open<P: AsRef<Path>>(path: P, data: impl DataStore + 'static) -> Result<FileStore, Box<dyn Error>> {
+        let path = path.as_ref().to_path_buf();
+        let tasks = MemoryStore::with_data(data);
+        tasks.replace(read(&path, tasks.data().last_task_id()?)?);
+        Ok(FileStore { path, tasks, depth: Cell::new(0) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl TaskStore for FileStore {
+    fn data(&self) -> &dyn DataStore {
+        self.tasks.data()
+    }
+
+    fn create(&self, title: &str, description: &str) -> Result<TaskRecord, Box<dyn Error>> {
+        within(self, || self.tasks.create(title, description))
+    }
+
+    fn get(&self, id: i32) -> Result<TaskRecord, Box<dyn Error>> {
+        self.tasks.get(id)
+    }
+
+    fn list(&self, query: &TaskQuery) -> Result<Vec<TaskRecord>, Box<dyn Error>> {
+        self.tasks.list(query)
+    }
+
+    fn update(&self, task: &TaskRecord) -> Result<TaskRecord, Box<dyn Error>> {
+        within(self, || self.tasks.update(task))
+    }
+
+    fn delete(&self, task: &TaskRecord) -> Result<(), Box<dyn Error>> {
+        within(self, || self.tasks.delete(task))
+    }
+
+    fn clear(&self, include_cancelled: bool) -> Result<Vec<i32>, Box<dyn Error>> {
+        within(self, || self.tasks.clear(include_cancelled))
+    }
+
+    // changing the tasks as they are in the file now, which may have been edited by hand since
+    fn atomically(&self, apply: &mut dyn FnMut() -> Result<(), Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+        let depth = self.depth.get();
+        if depth == 0 {
+            self.tasks.replace(read(&self.path, self.tasks.data().last_task_id()?)?);
+        }
+
+        self.depth.set(depth + 1);
+        let result = self.tasks.atomically(&mut || {
+            apply()?;
+            match depth {
+                0 => write(&self.path, &self.tasks.records()),
+                _ => Ok(()),
+            }
+        });
+        self.depth.set(depth);
+        result
+    }
+}
+
+// the tasks added by hand get ids above `last_id`, the last one anything kept around the tasks refers to
+pub(crate) fn read(path: &Path, last_id: i32) -> Result<Vec<TaskRecord>, Box<dyn Error>> {
+    let file_tasks: Vec<FileTask> = match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|error| format!("unable to read the tasks in {}: {error}", path.display()))?,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(error) => return Err(Box::<dyn std::error::Error>::from(format!("unable to read {}: {error}", path.display()))),
+    };
+
+    // the tasks added by hand without an id get one after the others
+    let mut last_id = file_tasks.iter().filter_map(|task| task.id).max().unwrap_or_default().max(last_id);
+    let mut tasks = Vec::new();
+    for task in file_tasks {
+        let id = task.id.unwrap_or_else(|| {
+            last_id += 1;
+            last_id
+        });
+        if tasks.iter().any(|other: &TaskRecord| other.id == id || other.title == task.title) {
+            return Err(Box::<dyn std::error::Error>::from(format!("the task '{}' appears twice in {}", task.title, path.display())));
+        }
+
+        tasks.push(TaskRecord {
+            id,
+            status: task.status.parse().map_err(|_| format!("'{}' isn't a valid status, in task '{}'", task.status, task.title))?,
+            estimate: task.estimate.as_deref().map(dates::parse_duration).transpose()?,
+            priority: task.priority.as_deref().map(str::parse).transpose().map_err(|error| format!("{error}, in task '{}'", task.title))?,
+            project: task.project.as_deref().map(parse_project).transpose().map_err(|error| format!("{error}, in task '{}'", task.title))?,
+            depends_on: BTreeSet::from_iter(task.depends_on).into_iter().collect(),
+            tags: normalize_tags(&task.tags).map_err(|error| format!("{error}, in task '{}'", task.title))?,
+            notes: normalize_notes(&task.notes).map_err(|error| format!("{error}, in task '{}'", task.title))?,
+            title: task.title,
+            description: task.description,
+            cancellation_reason: task.cancellation_reason,
+            created_at: task.created_at,
+            due: task.due,
+            wait_until: task.wait_until,
+            points: task.points,
+            version: task.version,
+        });
+    }
+
+    // the tasks depended on must be in the file too
+    for task in &tasks {
+        if let Some(id) = task.depends_on.iter().find(|id| !tasks.iter().any(|other| other.id == **id)) {
+            return Err(Box::<dyn std::error::Error>::from(format!("the task '{}' depends on a missing task ({id}) in {}", task.title, path.display())));
+        }
+    }
+
+    Ok(tasks)
+}
+
+// writing next to the file first, so that a failure never leaves it half written
+fn write(path: &Path, tasks: &[TaskRecord]) -> Result<(), Box<dyn Error>> {
+    let file_tasks: Vec<FileTask> = tasks.iter().map(FileTask::from).collect();
+    let content = serde_json::to_string_pretty(&file_tasks)? + "\n";
+
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, content).map_err(|error| format!("unable to write {}: {error}", temporary.display()))?;
+    fs::rename(&temporary, path)?;
+    Ok(())
+}
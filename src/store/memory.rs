@@ -0,0 +1,424 @@
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+use chrono::{DateTime, Local};
+
+use crate::dates;
+use crate::manager::attachments::{Attachment, AttachmentKind};
+use crate::manager::history::StatusChange;
+use crate::manager::pomodoro::TaskPomodoros;
+use crate::manager::task::{TaskRecord, TaskStatus};
+use crate::manager::tracking::TimeEntry;
+use crate::manager::workflow::StatusDefinition;
+use crate::manager::TaskQuery;
+
+use super::{check_title, check_version, clearable, prepare_update, record_status_change, select, within, DataStore, TaskStore};
+
+#[derive(Debug, Clone)]
+struct Pomodoro {
+    task_id: i32,
+    completed: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Data {
+    settings: BTreeMap<String, String>,
+    // in the order they are listed, along with their WIP limit
+    statuses: Vec<(StatusDefinition, Option<u32>)>,
+    transitions: Vec<(TaskStatus, TaskStatus)>,
+    restricted: bool,
+    time_entries: Vec<TimeEntry>,
+    pomodoros: Vec<Pomodoro>,
+    status_changes: Vec<StatusChange>,
+    attachments: Vec<Attachment>,
+    // the id given to the last time entry or attachment
+    last_id: i32,
+}
+
+// everything around the tasks, kept in memory as long as the store
+#[derive(Debug)]
+pub struct MemoryData {
+    data: RefCell<Data>,
+}
+
+impl Default for MemoryData {
+    fn default() -> Self {
+        let statuses = StatusDefinition::built_in().into_iter().map(|definition| (definition, None)).collect();
+        MemoryData { data: RefCell::new(Data { statuses, ..Default::default() }) }
+    }
+}
+
+impl MemoryData {
+    fn next_id(&self) -> i32 {
+        let mut data = self.data.borrow_mut();
+        data.last_id += 1;
+        data.last_id
+    }
+
+    fn status_position(&self, status: &TaskStatus) -> Option<usize> {
+        self.data.borrow().statuses.iter().position(|(definition, _)| definition.status == *status)
+    }
+}
+
+// the dates kept to the second, like in the database
+fn to_the_second(date: DateTime<Local>) -> DateTime<Local> {
+    dates::from_timestamp(date.timestamp())
+}
+
+impl DataStore for MemoryData {
+    fn get_setting(&self, key: &str) -> Result<Option<String>, Box<dyn Error>> {
+        Ok(self.data.borrow().settings.get(key).cloned())
+    }
+
+    fn get_settings(&self) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        Ok(self.data.borrow().settings.clone().into_iter().collect())
+    }
+
+    fn set_setting(&self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        self.data.borrow_mut().settings.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn unset_setting(&self, key: &str) -> Result<(), Box<dyn Error>> {
+        self.data.borrow_mut().settings.remove(key);
+        Ok(())
+    }
+
+    fn get_statuses(&self) -> Result<Vec<StatusDefinition>, Box<dyn Error>> {
+        Ok(self.data.borrow().statuses.iter().map(|(definition, _)| definition.clone()).collect())
+    }
+
+    fn add_status(&self, definition: &StatusDefinition) -> Result<(), Box<dyn Error>> {
+        if self.status_position(&definition.status).is_some() {
+            return Err(Box::<dyn std::error::Error>::from("a status with the same name already exists"))
+        }
+        self.data.borrow_mut().statuses.push((definition.clone(), None));
+        Ok(())
+    }
+
+    fn set_closed(&self, status: &TaskStatus, closed: bool) -> Result<(), Box<dyn Error>> {
+        if let Some(position) = self.status_position(status) {
+            self.data.borrow_mut().statuses[position].0.closed = closed;
+        }
+        Ok(())
+    }
+
+    fn remove_status(&self, status: &TaskStatus) -> Result<(), Box<dyn Error>> {
+        let mut data = self.data.borrow_mut();
+        data.transitions.retain(|(from, to)| from != status && to != status);
+        data.statuses.retain(|(definition, _)| definition.status != *status);
+        Ok(())
+    }
+
+    fn get_wip_limit(&self, status: &TaskStatus) -> Result<Option<u32>, Box<dyn Error>> {
+        Ok(self.status_position(status).and_then(|position| self.data.borrow().statuses[position].1))
+    }
+
+    fn set_wip_limit(&self, status: &TaskStatus, limit: Option<u32>) -> Result<(), Box<dyn Error>> {
+        if let Some(position) = self.status_position(status) {
+            self.data.borrow_mut().statuses[position].1 = limit;
+        }
+        Ok(())
+    }
+
+    fn get_transitions(&self) -> Result<Vec<(TaskStatus, TaskStatus)>, Box<dyn Error>> {
+        let mut transitions: Vec<(usize, TaskStatus, TaskStatus)> = self
+            .data
+            .borrow()
+            .transitions
+            .iter()
+            .filter_map(|(from, to)| Some((self.status_position(from)?, from.clone(), to.clone())))
+            .collect();
+        transitions.sort_by_key(|(position, _, to)| (*position, to.to_string()));
+        Ok(transitions.into_iter().map(|(_, from, to)| (from, to)).collect())
+    }
+
+    fn add_transition(&self, from: &TaskStatus, to: &TaskStatus) -> Result<(), Box<dyn Error>> {
+        let mut data = self.data.borrow_mut();
+        if !data.transitions.iter().any(|(other_from, other_to)| other_from == from && other_to == to) {
+            data.transitions.push((from.clone(), to.clone()));
+        }
+        Ok(())
+    }
+
+    fn remove_transition(&self, from: &TaskStatus, to: &TaskStatus) -> Result<(), Box<dyn Error>> {
+        self.data.borrow_mut().transitions.retain(|(other_from, other_to)| other_from != from || other_to != to);
+        Ok(())
+    }
+
+    fn clear_transitions(&self) -> Result<(), Box<dyn Error>> {
+        self.data.borrow_mut().transitions.clear();
+        Ok(())
+    }
+
+    fn is_restricted(&self) -> Result<bool, Box<dyn Error>> {
+        Ok(self.data.borrow().restricted)
+    }
+
+    fn set_restricted(&self, restricted: bool) -> Result<(), Box<dyn Error>> {
+        self.data.borrow_mut().restricted = restricted;
+        Ok(())
+    }
+
+    fn add_time_entry(&self, task_id: i32, started_at: DateTime<Local>, ended_at: Option<DateTime<Local>>) -> Result<(), Box<dyn Error>> {
+        let id = self.next_id() as i64;
+        let entry = TimeEntry { id, task_id, started_at: to_the_second(started_at), ended_at: ended_at.map(to_the_second) };
+        self.data.borrow_mut().time_entries.push(entry);
+        Ok(())
+    }
+
+    fn end_time_entry(&self, id: i64, ended_at: DateTime<Local>) -> Result<(), Box<dyn Error>> {
+        for entry in self.data.borrow_mut().time_entries.iter_mut().filter(|entry| entry.id == id) {
+            entry.ended_at = Some(to_the_second(ended_at));
+        }
+        Ok(())
+    }
+
+    fn get_running_entry(&self) -> Result<Option<TimeEntry>, Box<dyn Error>> {
+        Ok(self.data.borrow().time_entries.iter().filter(|entry| entry.ended_at.is_none()).max_by_key(|entry| entry.started_at).cloned())
+    }
+
+    fn get_time_entries(&self, task_id: i32) -> Result<Vec<TimeEntry>, Box<dyn Error>> {
+        let mut entries: Vec<TimeEntry> = self.data.borrow().time_entries.iter().filter(|entry| entry.task_id == task_id).cloned().collect();
+        entries.sort_by_key(|entry| entry.started_at);
+        Ok(entries)
+    }
+
+    fn get_time_entries_between(&self, from: DateTime<Local>, to: DateTime<Local>) -> Result<Vec<TimeEntry>, Box<dyn Error>> {
+        let (from, to) = (to_the_second(from), to_the_second(to));
+        let mut entries: Vec<TimeEntry> = self
+            .data
+            .borrow()
+            .time_entries
+            .iter()
+            .filter(|entry| entry.started_at < to && entry.ended_at.is_none_or(|ended_at| ended_at > from))
+            .cloned()
+            .collect();
+        entries.sort_by_key(|entry| entry.started_at);
+        Ok(entries)
+    }
+
+    fn add_pomodoro(&self, task_id: i32, _started_at: DateTime<Local>, _ended_at: DateTime<Local>, completed: bool) -> Result<(), Box<dyn Error>> {
+        self.data.borrow_mut().pomodoros.push(Pomodoro { task_id, completed });
+        Ok(())
+    }
+
+    fn count_completed_pomodoros(&self, task_id: i32) -> Result<usize, Box<dyn Error>> {
+        Ok(self.data.borrow().pomodoros.iter().filter(|pomodoro| pomodoro.task_id == task_id && pomodoro.completed).count())
+    }
+
+    fn count_pomodoros(&self) -> Result<Vec<TaskPomodoros>, Box<dyn Error>> {
+        let mut counts: BTreeMap<i32, (usize, usize)> = BTreeMap::new();
+        for pomodoro in &self.data.borrow().pomodoros {
+            let (completed, interrupted) = counts.entry(pomodoro.task_id).or_default();
+            match pomodoro.completed {
+                true => *completed += 1,
+                false => *interrupted += 1,
+            }
+        }
+        Ok(counts.into_iter().map(|(task_id, (completed, interrupted))| (task_id, completed, interrupted)).collect())
+    }
+
+    fn add_status_change(&self, task_id: i32, status: &TaskStatus, changed_at: DateTime<Local>) -> Result<(), Box<dyn Error>> {
+        let change = StatusChange { task_id, status: status.clone(), changed_at: to_the_second(changed_at) };
+        self.data.borrow_mut().status_changes.push(change);
+        Ok(())
+    }
+
+    fn get_status_changes(&self, task_id: Option<i32>) -> Result<Vec<StatusChange>, Box<dyn Error>> {
+        let mut changes: Vec<StatusChange> = self
+            .data
+            .borrow()
+            .status_changes
+            .iter()
+            .filter(|change| task_id.is_none_or(|task_id| change.task_id == task_id))
+            .cloned()
+            .collect();
+        // keeping the order they were made in between changes made the same second
+        changes.sort_by_key(|change| change.changed_at);
+        Ok(changes)
+    }
+
+    fn add_attachment(&self, task_id: i32, kind: AttachmentKind, name: &str, location: &str, added_at: DateTime<Local>) -> Result<(), Box<dyn Error>> {
+        let id = self.next_id();
+        let attachment = Attachment { id, task_id, kind, name: name.to_string(), location: location.to_string(), added_at: to_the_second(added_at) };
+        self.data.borrow_mut().attachments.push(attachment);
+        Ok(())
+    }
+
+    fn get_attachments(&self, task_id: i32) -> Result<Vec<Attachment>, Box<dyn Error>> {
+        let mut attachments: Vec<Attachment> = self.data.borrow().attachments.iter().filter(|attachment| attachment.task_id == task_id).cloned().collect();
+        attachments.sort_by_key(|attachment| attachment.added_at);
+        Ok(attachments)
+    }
+
+    fn delete_attachments(&self, task_id: i32) -> Result<(), Box<dyn Error>> {
+        self.data.borrow_mut().attachments.retain(|attachment| attachment.task_id != task_id);
+        Ok(())
+    }
+
+    fn is_copy_attached(&self, location: &str) -> Result<bool, Box<dyn Error>> {
+        Ok(self.data.borrow().attachments.iter().any(|attachment| attachment.kind == AttachmentKind::Copy && attachment.location == location))
+    }
+
+    fn last_task_id(&self) -> Result<i32, Box<dyn Error>> {
+        let data = self.data.borrow();
+        Ok(data.time_entries.iter().map(|entry| entry.task_id)
+            .chain(data.pomodoros.iter().map(|pomodoro| pomodoro.task_id))
+            .chain(data.status_changes.iter().map(|change| change.task_id))
+            .chain(data.attachments.iter().map(|attachment| attachment.task_id))
+            .max()
+            .unwrap_or_default())
+    }
+
+    fn forget_task(&self, task_id: i32) -> Result<(), Box<dyn Error>> {
+        let mut data = self.data.borrow_mut();
+        data.time_entries.retain(|entry| entry.task_id != task_id);
+        data.pomodoros.retain(|pomodoro| pomodoro.task_id != task_id);
+        data.status_changes.retain(|change| change.task_id != task_id);
+        Ok(())
+    }
+
+    // putting everything back as it was when the change fails
+    fn atomically(&self, apply: &mut dyn FnMut() -> Result<(), Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+        let data = self.data.borrow().clone();
+        let result = apply();
+        if result.is_err() {
+            self.data.replace(data);
+        }
+        result
+    }
+}
+
+// tasks that only live as long as the store, for tests and for programs keeping their own state
+pub struct MemoryStore {
+    data: Box<dyn DataStore>,
+    tasks: RefCell<Vec<TaskRecord>>,
+    last_id: Cell<i32>,
+}
+
+impl MemoryStore {
+    // everything around the tasks lives in memory too
+    pub fn new() -> MemoryStore {
+        MemoryStore::with_data(MemoryData::default())
+    }
+
+    // keeping everything around the tasks somewhere else (e.g. in a database)
+    pub fn with_data(data: impl DataStore + 'static) -> MemoryStore {
+        MemoryStore { data: Box::new(data), tasks: RefCell::new(Vec::new()), last_id: Cell::new(0) }
+    }
+
+    pub fn records(&self) -> Vec<TaskRecord> {
+        self.tasks.borrow().clone()
+    }
+
+    // taking the place of the tasks held so far
+    pub fn replace(&self, tasks: Vec<TaskRecord>) {
+        self.last_id.set(tasks.iter().map(|task| task.id).max().unwrap_or_default());
+        self.tasks.replace(tasks);
+    }
+
+    // the deleted tasks don't hold back the others anymore
+    fn forget(&self, ids: &[i32]) {
+        for task in self.tasks.borrow_mut().iter_mut() {
+            task.depends_on.retain(|id| !ids.contains(id));
+        }
+    }
+
+    fn position(&self, id: i32) -> Result<usize, Box<dyn Error>> {
+        self.tasks
+            .borrow()
+            .iter()
+            .position(|task| task.id == id)
+            .ok_or_else(|| Box::<dyn std::error::Error>::from("No task found"))
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        MemoryStore::new()
+    }
+}
+
+impl TaskStore for MemoryStore {
+    fn data(&self) -> &dyn DataStore {
+        self.data.as_ref()
+    }
+
+    fn create(&self, title: &str, description: &str) -> Result<TaskRecord, Box<dyn Error>> {
+        check_title(self, title, None)?;
+
+        let id = self.last_id.get().max(self.data.last_task_id()?) + 1;
+        self.last_id.set(id);
+        let task = TaskRecord {
+            id,
+            title: title.to_string(),
+            description: description.to_string(),
+            status: TaskStatus::Undone,
+            cancellation_reason: None,
+            created_at: Some(Local::now()),
+            due: None,
+            wait_until: None,
+            estimate: None,
+            points: None,
+            priority: None,
+            project: None,
+            tags: Vec::new(),
+            depends_on: Vec::new(),
+            notes: Vec::new(),
+            version: 0,
+        };
+        self.tasks.borrow_mut().push(task.clone());
+        Ok(task)
+    }
+
+    fn get(&self, id: i32) -> Result<TaskRecord, Box<dyn Error>> {
+        let position = self.position(id)?;
+        Ok(self.tasks.borrow()[position].clone())
+    }
+
+    fn list(&self, query: &TaskQuery) -> Result<Vec<TaskRecord>, Box<dyn Error>> {
+        select(self, &self.tasks.borrow(), query, Local::now())
+    }
+
+    fn update(&self, task: &TaskRecord) -> Result<TaskRecord, Box<dyn Error>> {
+        within(self, || {
+            let stored = self.get(task.id)?;
+            let updated = prepare_update(self, &stored, task)?;
+
+            record_status_change(self, &stored, &updated)?;
+            let position = self.position(task.id)?;
+            self.tasks.borrow_mut()[position] = updated.clone();
+            Ok(updated)
+        })
+    }
+
+    fn delete(&self, task: &TaskRecord) -> Result<(), Box<dyn Error>> {
+        check_version(&self.get(task.id)?, task)?;
+        let position = self.position(task.id)?;
+        self.tasks.borrow_mut().remove(position);
+        self.forget(&[task.id]);
+        Ok(())
+    }
+
+    fn clear(&self, include_cancelled: bool) -> Result<Vec<i32>, Box<dyn Error>> {
+        let ids = clearable(self, &self.tasks.borrow(), include_cancelled)?;
+        self.tasks.borrow_mut().retain(|task| !ids.contains(&task.id));
+        self.forget(&ids);
+        Ok(ids)
+    }
+
+    // the tasks are put back as they were when the change fails, along with everything around them
+    fn atomically(&self, apply: &mut dyn FnMut() -> Result<(), Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+        let tasks = self.records();
+        let last_id = self.last_id.get();
+
+        let result = self.data.atomically(apply);
+        if result.is_err() {
+            self.tasks.replace(tasks);
+            self.last_id.set(last_id);
+        }
+        result
+    }
+}
@@ -0,0 +1,821 @@
+use std::error::Error;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row, ToSql};
+
+use crate::dates;
+use crate::manager::attachments::{Attachment, AttachmentKind};
+use crate::manager::history::StatusChange;
+use crate::manager::pomodoro::TaskPomodoros;
+use crate::manager::notes::Note;
+use crate::manager::stats::{AverageTimes, Period};
+use crate::manager::task::{TaskRecord, TaskStatus};
+use crate::manager::tracking::TimeEntry;
+use crate::manager::workflow::StatusDefinition;
+use crate::manager::TaskQuery;
+
+use super::{check_title, check_version, prepare_update, record_status_change, within, DataStore, TaskStore};
+
+// everything around the tasks, in the tables of an SQLite database
+pub struct SqliteData {
+    connection: Connection,
+}
+
+impl SqliteData {
+    pub fn new(connection: Connection) -> SqliteData {
+        // initializing, if not present, the working tables for the statuses and their transitions
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS statuses (
+                "name" text PRIMARY KEY,
+                "position" integer,
+                "closed" integer DEFAULT 0
+            )"#, ()
+        );
+
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS transitions (
+                "from" text,
+                "to" text,
+                PRIMARY KEY ("from", "to")
+            )"#, ()
+        );
+
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS workflow (
+                "restricted" integer NOT NULL
+            )"#, ()
+        );
+
+        // adding the columns introduced after the first release to older databases
+        let _ = connection.execute(r#"ALTER TABLE "statuses" ADD COLUMN "wip_limit" integer"#, ());
+
+        // older databases were restricted as long as they had transitions
+        let _ = connection.execute(
+            r#"INSERT INTO "workflow" ("restricted")
+                SELECT EXISTS (SELECT 1 FROM "transitions") WHERE NOT EXISTS (SELECT 1 FROM "workflow")"#, ()
+        );
+
+        // seeding the built-in statuses, in the order they are listed
+        for (position, definition) in StatusDefinition::built_in().iter().enumerate() {
+            let _ = connection.execute(
+                r#"INSERT OR IGNORE INTO "statuses" ("name", "position", "closed") VALUES (?1, ?2, ?3)"#,
+                params![definition.status, position, definition.closed],
+            );
+        }
+
+        // the settings of the task list
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS settings (
+                "key" text PRIMARY KEY,
+                "value" text
+            )"#, ()
+        );
+
+        // the time tracked on tasks
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS time_entries (
+                "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+                "task_id" integer,
+                "started_at" integer,
+                "ended_at" integer
+            )"#, ()
+        );
+
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS pomodoros (
+                "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+                "task_id" integer,
+                "started_at" integer,
+                "ended_at" integer,
+                "completed" integer
+            )"#, ()
+        );
+
+        // the history of the status changes
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS status_changes (
+                "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+                "task_id" integer,
+                "status" text,
+                "changed_at" integer
+            )"#, ()
+        );
+
+        // the attachments of tasks
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS attachments (
+                "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+                "task_id" integer,
+                "kind" text,
+                "name" text,
+                "location" text,
+                "added_at" integer
+            )"#, ()
+        );
+
+        SqliteData { connection }
+    }
+
+    // how many tasks the "tasks" table holds, none when it doesn't exist
+    pub(crate) fn count_database_tasks(&self) -> i64 {
+        self.connection.query_row(r#"SELECT COUNT(*) FROM "tasks""#, [], |row| row.get(0)).unwrap_or(0)
+    }
+}
+
+fn time_entry_from_row(row: &Row) -> rusqlite::Result<TimeEntry> {
+    Ok(TimeEntry {
+        id: row.get("id")?,
+        task_id: row.get("task_id")?,
+        started_at: dates::from_timestamp(row.get("started_at")?),
+        ended_at: row.get::<_, Option<i64>>("ended_at")?.map(dates::from_timestamp),
+    })
+}
+
+fn status_change_from_row(row: &Row) -> rusqlite::Result<StatusChange> {
+    Ok(StatusChange {
+        task_id: row.get("task_id")?,
+        status: row.get("status")?,
+        changed_at: dates::from_timestamp(row.get("changed_at")?),
+    })
+}
+
+fn attachment_from_row(row: &Row) -> rusqlite::Result<Attachment> {
+    Ok(Attachment {
+        id: row.get("id")?,
+        task_id: row.get("task_id")?,
+        kind: row.get("kind")?,
+        name: row.get("name")?,
+        location: row.get("location")?,
+        added_at: dates::from_timestamp(row.get("added_at")?),
+    })
+}
+
+impl DataStore for SqliteData {
+    fn get_setting(&self, key: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let value = self
+            .connection
+            .query_row(r#"SELECT "value" FROM "settings" WHERE "key"=?1"#, params![key], |row| row.get("value"))
+            .optional()?;
+        Ok(value)
+    }
+
+    fn get_settings(&self) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let mut stmt = self.connection.prepare(r#"SELECT "key", "value" FROM "settings" ORDER BY "key""#)?;
+        let settings = stmt
+            .query_map([], |row| Ok((row.get("key")?, row.get("value")?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(settings)
+    }
+
+    fn set_setting(&self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(r#"INSERT OR REPLACE INTO "settings" ("key", "value") VALUES (?1, ?2)"#, params![key, value])?;
+        Ok(())
+    }
+
+    fn unset_setting(&self, key: &str) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(r#"DELETE FROM "settings" WHERE "key" = ?1"#, params![key])?;
+        Ok(())
+    }
+
+    fn get_statuses(&self) -> Result<Vec<StatusDefinition>, Box<dyn Error>> {
+        let mut stmt = self.connection.prepare(r#"SELECT "name", "closed" FROM "statuses" ORDER BY "position""#)?;
+        let statuses = stmt
+            .query_map([], |row| Ok(StatusDefinition { status: row.get("name")?, closed: row.get("closed")? }))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(statuses)
+    }
+
+    fn add_status(&self, definition: &StatusDefinition) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(
+            r#"INSERT INTO "statuses" ("name", "position", "closed")
+                VALUES (?1, (SELECT IFNULL(MAX("position"), -1) + 1 FROM "statuses"), ?2)"#,
+            params![definition.status, definition.closed],
+        )?;
+        Ok(())
+    }
+
+    fn set_closed(&self, status: &TaskStatus, closed: bool) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(r#"UPDATE "statuses" SET "closed" = ?1 WHERE "name" = ?2"#, params![closed, status])?;
+        Ok(())
+    }
+
+    fn remove_status(&self, status: &TaskStatus) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(r#"DELETE FROM "transitions" WHERE "from" = ?1 OR "to" = ?1"#, params![status])?;
+        self.connection.execute(r#"DELETE FROM "statuses" WHERE "name" = ?1"#, params![status])?;
+        Ok(())
+    }
+
+    fn get_wip_limit(&self, status: &TaskStatus) -> Result<Option<u32>, Box<dyn Error>> {
+        let limit: Option<Option<u32>> = self
+            .connection
+            .query_row(r#"SELECT "wip_limit" FROM "statuses" WHERE "name"=?1"#, params![status], |row| row.get("wip_limit"))
+            .optional()?;
+        Ok(limit.flatten())
+    }
+
+    fn set_wip_limit(&self, status: &TaskStatus, limit: Option<u32>) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(r#"UPDATE "statuses" SET "wip_limit" = ?1 WHERE "name" = ?2"#, params![limit, status])?;
+        Ok(())
+    }
+
+    fn get_transitions(&self) -> Result<Vec<(TaskStatus, TaskStatus)>, Box<dyn Error>> {
+        let mut stmt = self.connection.prepare(
+            r#"SELECT "from", "to" FROM "transitions"
+                JOIN "statuses" ON "statuses"."name" = "transitions"."from"
+                ORDER BY "statuses"."position", "to""#,
+        )?;
+        let transitions = stmt
+            .query_map([], |row| Ok((row.get("from")?, row.get("to")?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(transitions)
+    }
+
+    fn add_transition(&self, from: &TaskStatus, to: &TaskStatus) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(r#"INSERT OR IGNORE INTO "transitions" ("from", "to") VALUES (?1, ?2)"#, params![from, to])?;
+        Ok(())
+    }
+
+    fn remove_transition(&self, from: &TaskStatus, to: &TaskStatus) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(r#"DELETE FROM "transitions" WHERE "from" = ?1 AND "to" = ?2"#, params![from, to])?;
+        Ok(())
+    }
+
+    fn clear_transitions(&self) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(r#"DELETE FROM "transitions""#, [])?;
+        Ok(())
+    }
+
+    fn is_restricted(&self) -> Result<bool, Box<dyn Error>> {
+        Ok(self.connection.query_row(r#"SELECT "restricted" FROM "workflow""#, [], |row| row.get(0))?)
+    }
+
+    fn set_restricted(&self, restricted: bool) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(r#"UPDATE "workflow" SET "restricted" = ?1"#, params![restricted])?;
+        Ok(())
+    }
+
+    fn add_time_entry(&self, task_id: i32, started_at: DateTime<Local>, ended_at: Option<DateTime<Local>>) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(
+            r#"INSERT INTO "time_entries" ("task_id", "started_at", "ended_at") VALUES (?1, ?2, ?3)"#,
+            params![task_id, started_at.timestamp(), ended_at.map(|date| date.timestamp())],
+        )?;
+        Ok(())
+    }
+
+    fn end_time_entry(&self, id: i64, ended_at: DateTime<Local>) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(r#"UPDATE "time_entries" SET "ended_at" = ?1 WHERE "id" = ?2"#, params![ended_at.timestamp(), id])?;
+        Ok(())
+    }
+
+    fn get_running_entry(&self) -> Result<Option<TimeEntry>, Box<dyn Error>> {
+        let entry = self
+            .connection
+            .query_row(
+                r#"SELECT * FROM "time_entries" WHERE "ended_at" IS NULL ORDER BY "started_at" DESC"#,
+                [],
+                time_entry_from_row,
+            )
+            .optional()?;
+        Ok(entry)
+    }
+
+    fn get_time_entries(&self, task_id: i32) -> Result<Vec<TimeEntry>, Box<dyn Error>> {
+        let mut stmt = self.connection.prepare(r#"SELECT * FROM "time_entries" WHERE "task_id"=?1 ORDER BY "started_at""#)?;
+        let entries = stmt
+            .query_map(params![task_id], time_entry_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    fn get_time_entries_between(&self, from: DateTime<Local>, to: DateTime<Local>) -> Result<Vec<TimeEntry>, Box<dyn Error>> {
+        let mut stmt = self.connection.prepare(
+            r#"SELECT * FROM "time_entries"
+                WHERE "started_at" < ?2 AND ("ended_at" IS NULL OR "ended_at" > ?1)
+                ORDER BY "started_at""#,
+        )?;
+        let entries = stmt
+            .query_map(params![from.timestamp(), to.timestamp()], time_entry_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    fn add_pomodoro(&self, task_id: i32, started_at: DateTime<Local>, ended_at: DateTime<Local>, completed: bool) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(
+            r#"INSERT INTO "pomodoros" ("task_id", "started_at", "ended_at", "completed") VALUES (?1, ?2, ?3, ?4)"#,
+            params![task_id, started_at.timestamp(), ended_at.timestamp(), completed],
+        )?;
+        Ok(())
+    }
+
+    fn count_completed_pomodoros(&self, task_id: i32) -> Result<usize, Box<dyn Error>> {
+        let count = self.connection.query_row(
+            r#"SELECT COUNT(*) FROM "pomodoros" WHERE "task_id" = ?1 AND "completed""#,
+            params![task_id],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    fn count_pomodoros(&self) -> Result<Vec<TaskPomodoros>, Box<dyn Error>> {
+        let mut stmt = self.connection.prepare(
+            r#"SELECT "task_id",
+                    SUM("completed") AS "completed",
+                    SUM(NOT "completed") AS "interrupted"
+                FROM "pomodoros"
+                GROUP BY "task_id""#,
+        )?;
+        let counts = stmt
+            .query_map([], |row| Ok((row.get("task_id")?, row.get("completed")?, row.get("interrupted")?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(counts)
+    }
+
+    fn add_status_change(&self, task_id: i32, status: &TaskStatus, changed_at: DateTime<Local>) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(
+            r#"INSERT INTO "status_changes" ("task_id", "status", "changed_at") VALUES (?1, ?2, ?3)"#,
+            params![task_id, status, changed_at.timestamp()],
+        )?;
+        Ok(())
+    }
+
+    fn get_status_changes(&self, task_id: Option<i32>) -> Result<Vec<StatusChange>, Box<dyn Error>> {
+        let mut stmt = self.connection.prepare(
+            r#"SELECT * FROM "status_changes" WHERE ?1 IS NULL OR "task_id"=?1 ORDER BY "changed_at", "id""#)?;
+        let changes = stmt
+            .query_map(params![task_id], status_change_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(changes)
+    }
+
+    fn add_attachment(&self, task_id: i32, kind: AttachmentKind, name: &str, location: &str, added_at: DateTime<Local>) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(
+            r#"INSERT INTO "attachments" ("task_id", "kind", "name", "location", "added_at") VALUES (?1, ?2, ?3, ?4, ?5)"#,
+            params![task_id, kind, name, location, added_at.timestamp()],
+        )?;
+        Ok(())
+    }
+
+    fn get_attachments(&self, task_id: i32) -> Result<Vec<Attachment>, Box<dyn Error>> {
+        let mut stmt = self.connection.prepare(r#"SELECT * FROM "attachments" WHERE "task_id"=?1 ORDER BY "added_at", "id""#)?;
+        let attachments = stmt
+            .query_map(params![task_id], attachment_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(attachments)
+    }
+
+    fn delete_attachments(&self, task_id: i32) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(r#"DELETE FROM "attachments" WHERE "task_id"=?1"#, params![task_id])?;
+        Ok(())
+    }
+
+    fn is_copy_attached(&self, location: &str) -> Result<bool, Box<dyn Error>> {
+        let attached = self.connection.query_row(
+            r#"SELECT EXISTS (SELECT 1 FROM "attachments" WHERE "kind"=?1 AND "location"=?2)"#,
+            params![AttachmentKind::Copy, location],
+            |row| row.get(0),
+        )?;
+        Ok(attached)
+    }
+
+    fn forget_task(&self, task_id: i32) -> Result<(), Box<dyn Error>> {
+        for table in ["time_entries", "pomodoros", "status_changes"] {
+            self.connection.execute(&format!(r#"DELETE FROM "{table}" WHERE "task_id" = ?1"#), params![task_id])?;
+        }
+        Ok(())
+    }
+
+    // including the tasks the database has held, wherever they are kept now
+    fn last_task_id(&self) -> Result<i32, Box<dyn Error>> {
+        let last_id = self.connection.query_row(
+            r#"SELECT MAX(
+                IFNULL((SELECT "seq" FROM "sqlite_sequence" WHERE "name" = 'tasks'), 0),
+                IFNULL((SELECT MAX("task_id") FROM "time_entries"), 0),
+                IFNULL((SELECT MAX("task_id") FROM "pomodoros"), 0),
+                IFNULL((SELECT MAX("task_id") FROM "status_changes"), 0),
+                IFNULL((SELECT MAX("task_id") FROM "attachments"), 0))"#,
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(last_id)
+    }
+
+    // running `apply` in a transaction, or in a savepoint when one is already open
+    fn atomically(&self, apply: &mut dyn FnMut() -> Result<(), Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+        // taking the write lock up front, as another process writing in between would make it fail halfway
+        let (begin, commit, rollback) = match self.connection.is_autocommit() {
+            true => ("BEGIN IMMEDIATE", "COMMIT", "ROLLBACK"),
+            false => ("SAVEPOINT atomically", "RELEASE atomically", "ROLLBACK TO atomically; RELEASE atomically"),
+        };
+
+        self.connection.execute_batch(begin)?;
+        match apply() {
+            Ok(()) => {
+                self.connection.execute_batch(commit)?;
+                Ok(())
+            },
+            Err(error) => {
+                let _ = self.connection.execute_batch(rollback);
+                Err(error)
+            }
+        }
+    }
+}
+
+// the tasks in the "tasks" table of the same database as everything else
+pub struct SqliteStore {
+    data: SqliteData,
+}
+
+impl SqliteStore {
+    pub fn new(connection: Connection) -> SqliteStore {
+        SqliteStore::from_data(SqliteData::new(connection))
+    }
+
+    // the tasks in the same database as everything else
+    pub fn from_data(data: SqliteData) -> SqliteStore {
+        let connection = &data.connection;
+
+        // initializing, if not present, the working table for the tasks
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS tasks (
+                "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+                "title" text,
+                "description" text,
+                "status" text
+            )"#, ()
+        );
+
+        // adding the columns introduced after the first release to older databases
+        let _ = connection.execute(r#"ALTER TABLE "tasks" ADD COLUMN "cancellation_reason" text"#, ());
+        let _ = connection.execute(r#"ALTER TABLE "tasks" ADD COLUMN "wait_until" integer"#, ());
+        let _ = connection.execute(r#"ALTER TABLE "tasks" ADD COLUMN "created_at" integer"#, ());
+        let _ = connection.execute(r#"ALTER TABLE "tasks" ADD COLUMN "estimate" integer"#, ());
+        let _ = connection.execute(r#"ALTER TABLE "tasks" ADD COLUMN "points" integer"#, ());
+        let _ = connection.execute(r#"ALTER TABLE "tasks" ADD COLUMN "due" integer"#, ());
+        let _ = connection.execute(r#"ALTER TABLE "tasks" ADD COLUMN "version" integer NOT NULL DEFAULT 0"#, ());
+        let _ = connection.execute(r#"ALTER TABLE "tasks" ADD COLUMN "priority" text"#, ());
+        let _ = connection.execute(r#"ALTER TABLE "tasks" ADD COLUMN "project" text"#, ());
+
+        // the tags of the tasks
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS task_tags (
+                "task_id" integer,
+                "tag" text,
+                PRIMARY KEY ("task_id", "tag")
+            )"#, ()
+        );
+
+        // the tasks each task depends on
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS task_dependencies (
+                "task_id" integer,
+                "depends_on" integer,
+                PRIMARY KEY ("task_id", "depends_on")
+            )"#, ()
+        );
+
+        // the notes of the tasks
+        let _ = connection.execute(
+            r#"CREATE TABLE IF NOT EXISTS task_notes (
+                "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+                "task_id" integer,
+                "text" text,
+                "created_at" integer,
+                "edited_at" integer
+            )"#, ()
+        );
+
+        SqliteStore { data }
+    }
+
+    fn connection(&self) -> &Connection {
+        &self.data.connection
+    }
+}
+
+// the tasks along with their tags and dependencies, each gathered in a single column
+const SELECT_TASKS: &str = r#"SELECT "tasks".*,
+    (SELECT GROUP_CONCAT("tag", ' ') FROM "task_tags" WHERE "task_tags"."task_id" = "tasks"."id") AS "tags",
+    (SELECT GROUP_CONCAT("depends_on", ' ') FROM "task_dependencies" WHERE "task_dependencies"."task_id" = "tasks"."id") AS "depends_on"
+    FROM "tasks""#;
+
+fn split_tags(tags: Option<String>) -> Vec<String> {
+    let mut tags: Vec<String> = tags.unwrap_or_default().split_whitespace().map(str::to_string).collect();
+    tags.sort();
+    tags
+}
+
+fn split_ids(ids: Option<String>) -> Vec<i32> {
+    let mut ids: Vec<i32> = ids.unwrap_or_default().split_whitespace().filter_map(|id| id.parse().ok()).collect();
+    ids.sort();
+    ids
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<TaskRecord> {
+    Ok(TaskRecord {
+        id: row.get("id")?,
+        title: row.get("title")?,
+        description: row.get("description")?,
+        status: row.get("status")?,
+        cancellation_reason: row.get("cancellation_reason")?,
+        created_at: row.get::<_, Option<i64>>("created_at")?.map(dates::from_timestamp),
+        due: row.get::<_, Option<i64>>("due")?.map(dates::from_timestamp),
+        wait_until: row.get::<_, Option<i64>>("wait_until")?.map(dates::from_timestamp),
+        estimate: row.get::<_, Option<i64>>("estimate")?.map(Duration::seconds),
+        points: row.get("points")?,
+        priority: row.get("priority")?,
+        project: row.get("project")?,
+        tags: split_tags(row.get("tags")?),
+        depends_on: split_ids(row.get("depends_on")?),
+        // read afterwards, by `with_notes`
+        notes: Vec::new(),
+        version: row.get("version")?,
+    })
+}
+
+fn note_from_row(row: &Row) -> rusqlite::Result<Note> {
+    Ok(Note {
+        text: row.get("text")?,
+        created_at: dates::from_timestamp(row.get("created_at")?),
+        edited_at: row.get::<_, Option<i64>>("edited_at")?.map(dates::from_timestamp),
+    })
+}
+
+impl SqliteStore {
+    fn with_notes(&self, mut task: TaskRecord) -> Result<TaskRecord, Box<dyn Error>> {
+        let mut stmt = self.connection().prepare(r#"SELECT * FROM "task_notes" WHERE "task_id"=?1 ORDER BY "created_at", "id""#)?;
+        task.notes = stmt
+            .query_map(params![task.id], note_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(task)
+    }
+}
+
+impl TaskStore for SqliteStore {
+    fn data(&self) -> &dyn DataStore {
+        &self.data
+    }
+
+    fn create(&self, title: &str, description: &str) -> Result<TaskRecord, Box<dyn Error>> {
+        within(self, || {
+            check_title(self, title, None)?;
+            let id = self.data.last_task_id()? + 1;
+            self.connection().execute(
+                r#"INSERT INTO "tasks" ("id", "title", "description", "status", "created_at") VALUES (?1, ?2, ?3, ?4, ?5)"#,
+                params![id, title, description, TaskStatus::Undone, Utc::now().timestamp()],
+            )?;
+            self.get(id)
+        })
+    }
+
+    fn get(&self, id: i32) -> Result<TaskRecord, Box<dyn Error>> {
+        self.connection()
+            .query_row(&format!(r#"{SELECT_TASKS} WHERE "id"=?1"#), params![id], from_row)
+            .optional()?
+            .ok_or_else(|| Box::<dyn std::error::Error>::from("No task found"))
+            .and_then(|task| self.with_notes(task))
+    }
+
+    fn find(&self, title: &str) -> Result<Option<TaskRecord>, Box<dyn Error>> {
+        self.connection()
+            .query_row(&format!(r#"{SELECT_TASKS} WHERE "title"=?1"#), params![title], from_row)
+            .optional()?
+            .map(|task| self.with_notes(task))
+            .transpose()
+    }
+
+    fn list(&self, query: &TaskQuery) -> Result<Vec<TaskRecord>, Box<dyn Error>> {
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut values: Vec<&dyn ToSql> = Vec::new();
+        let now = Utc::now().timestamp();
+        let due_from = query.due_from.map(|date| date.timestamp());
+        let due_to = query.due_to.map(|date| date.timestamp());
+
+        match &query.status {
+            Some(status) => {
+                conditions.push(r#""tasks"."status" = ?"#);
+                values.push(status);
+            },
+            // cancelled tasks are only listed on request
+            None if !query.include_cancelled => {
+                conditions.push(r#""tasks"."status" <> ?"#);
+                values.push(&TaskStatus::Cancelled);
+            },
+            None => {}
+        }
+
+        if query.only_open {
+            conditions.push(r#"NOT IFNULL("statuses"."closed", 0)"#);
+        }
+
+        // due dates are filtered within [from, to)
+        if let Some(due_from) = &due_from {
+            conditions.push(r#""tasks"."due" >= ?"#);
+            values.push(due_from);
+        }
+        if let Some(due_to) = &due_to {
+            conditions.push(r#""tasks"."due" < ?"#);
+            values.push(due_to);
+        }
+
+        // snoozed tasks are hidden until their waiting date has passed
+        match (query.waiting, query.include_waiting) {
+            (true, _) => {
+                conditions.push(r#""tasks"."wait_until" > ?"#);
+                values.push(&now);
+            },
+            (false, true) => {},
+            (false, false) => {
+                conditions.push(r#"("tasks"."wait_until" IS NULL OR "tasks"."wait_until" <= ?)"#);
+                values.push(&now);
+            },
+        }
+
+        if let Some(project) = &query.project {
+            conditions.push(r#""tasks"."project" = ?"#);
+            values.push(project);
+        }
+
+        for tag in &query.tags {
+            conditions.push(r#"EXISTS (SELECT 1 FROM "task_tags" WHERE "task_tags"."task_id" = "tasks"."id" AND "task_tags"."tag" = ?)"#);
+            values.push(tag);
+        }
+
+        let where_clause = match conditions.is_empty() {
+            true => String::new(),
+            false => format!("WHERE {}", conditions.join(" AND ")),
+        };
+
+        let mut stmt = self.connection().prepare(&format!(
+            r#"{SELECT_TASKS}
+                LEFT JOIN "statuses" ON "statuses"."name" = "tasks"."status"
+                {where_clause}
+                ORDER BY "statuses"."position" IS NULL, "statuses"."position", "tasks"."id";"#))?;
+        let tasks = stmt
+            .query_map(params_from_iter(values), from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        tasks.into_iter().map(|task| self.with_notes(task)).collect()
+    }
+
+    fn update(&self, task: &TaskRecord) -> Result<TaskRecord, Box<dyn Error>> {
+        within(self, || {
+            let stored = self.get(task.id)?;
+            let updated = prepare_update(self, &stored, task)?;
+
+            self.connection().execute(
+                r#"UPDATE "tasks" SET "title" = ?1, "description" = ?2, "status" = ?3, "cancellation_reason" = ?4,
+                    "created_at" = ?5, "due" = ?6, "wait_until" = ?7, "estimate" = ?8, "points" = ?9, "priority" = ?10,
+                    "project" = ?11, "version" = ?12 WHERE "id" = ?13"#,
+                params![
+                    updated.title,
+                    updated.description,
+                    updated.status,
+                    updated.cancellation_reason,
+                    updated.created_at.map(|date| date.timestamp()),
+                    updated.due.map(|date| date.timestamp()),
+                    updated.wait_until.map(|date| date.timestamp()),
+                    updated.estimate.map(|estimate| estimate.num_seconds()),
+                    updated.points,
+                    updated.priority,
+                    updated.project,
+                    updated.version,
+                    updated.id,
+                ],
+            )?;
+
+            self.connection().execute(r#"DELETE FROM "task_tags" WHERE "task_id" = ?1"#, params![updated.id])?;
+            for tag in &updated.tags {
+                self.connection().execute(r#"INSERT INTO "task_tags" ("task_id", "tag") VALUES (?1, ?2)"#, params![updated.id, tag])?;
+            }
+
+            self.connection().execute(r#"DELETE FROM "task_dependencies" WHERE "task_id" = ?1"#, params![updated.id])?;
+            for id in &updated.depends_on {
+                self.connection().execute(r#"INSERT INTO "task_dependencies" ("task_id", "depends_on") VALUES (?1, ?2)"#, params![updated.id, id])?;
+            }
+
+            if updated.notes != stored.notes {
+                self.connection().execute(r#"DELETE FROM "task_notes" WHERE "task_id" = ?1"#, params![updated.id])?;
+                for note in &updated.notes {
+                    self.connection().execute(
+                        r#"INSERT INTO "task_notes" ("task_id", "text", "created_at", "edited_at") VALUES (?1, ?2, ?3, ?4)"#,
+                        params![updated.id, note.text, note.created_at.timestamp(), note.edited_at.map(|date| date.timestamp())],
+                    )?;
+                }
+            }
+
+            record_status_change(self, &stored, &updated)?;
+            // as saved, dates keeping whole seconds
+            self.get(updated.id)
+        })
+    }
+
+    fn delete(&self, task: &TaskRecord) -> Result<(), Box<dyn Error>> {
+        within(self, || {
+            check_version(&self.get(task.id)?, task)?;
+            self.connection().execute(r#"DELETE FROM "tasks" WHERE "id"=?1"#, params![task.id])?;
+            self.connection().execute(r#"DELETE FROM "task_tags" WHERE "task_id"=?1"#, params![task.id])?;
+            self.connection().execute(r#"DELETE FROM "task_notes" WHERE "task_id"=?1"#, params![task.id])?;
+            self.connection().execute(r#"DELETE FROM "task_dependencies" WHERE "task_id"=?1 OR "depends_on"=?1"#, params![task.id])?;
+            Ok(())
+        })
+    }
+
+    fn clear(&self, include_cancelled: bool) -> Result<Vec<i32>, Box<dyn Error>> {
+        within(self, || {
+            let mut stmt = self.connection().prepare(
+                r#"SELECT "id" FROM "tasks"
+                    WHERE "status" IN (SELECT "name" FROM "statuses" WHERE "closed")
+                    AND ("status" <> ?1 OR ?2)"#)?;
+            let ids = stmt
+                .query_map(params![TaskStatus::Cancelled, include_cancelled], |row| row.get(0))?
+                .collect::<Result<Vec<i32>, _>>()?;
+
+            for id in &ids {
+                self.connection().execute(r#"DELETE FROM "tasks" WHERE "id"=?1"#, params![id])?;
+                self.connection().execute(r#"DELETE FROM "task_tags" WHERE "task_id"=?1"#, params![id])?;
+                self.connection().execute(r#"DELETE FROM "task_notes" WHERE "task_id"=?1"#, params![id])?;
+                self.connection().execute(r#"DELETE FROM "task_dependencies" WHERE "task_id"=?1 OR "depends_on"=?1"#, params![id])?;
+            }
+            Ok(ids)
+        })
+    }
+
+    fn count_by_status(&self) -> Result<Vec<(TaskStatus, usize)>, Box<dyn Error>> {
+        let mut stmt = self.connection().prepare(r#"SELECT "status", COUNT(*) FROM "tasks" GROUP BY "status""#)?;
+        let counts = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(counts)
+    }
+
+    fn count_created(&self, period: Period) -> Result<Vec<(NaiveDate, usize)>, Box<dyn Error>> {
+        let start = period_start(period, r#""created_at""#);
+        let mut stmt = self.connection().prepare(&format!(
+            r#"SELECT {start} AS "start", COUNT(*) FROM "tasks" WHERE "created_at" IS NOT NULL GROUP BY "start" ORDER BY "start""#))?;
+        let counts = stmt
+            .query_map([], period_count)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(counts)
+    }
+
+    fn count_completed(&self, period: Period, statuses: &[TaskStatus]) -> Result<Vec<(NaiveDate, usize)>, Box<dyn Error>> {
+        let start = period_start(period, r#""completed_at""#);
+        let mut stmt = self.connection().prepare(&format!(
+            r#"{} SELECT {start} AS "start", COUNT(*) FROM "completions" GROUP BY "start" ORDER BY "start""#,
+            completions(statuses)))?;
+        let counts = stmt
+            .query_map(params_from_iter(statuses), period_count)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(counts)
+    }
+
+    fn average_times(&self, statuses: &[TaskStatus]) -> Result<AverageTimes, Box<dyn Error>> {
+        // the work on a task started the first time it got underway
+        let underway = statuses.len() + 1;
+        let (lead_time, cycle_time) = self.connection().query_row(
+            &format!(
+                r#"{},
+                "starts" AS (SELECT "task_id", MIN("changed_at") AS "started_at" FROM "status_changes" WHERE "status" = ?{underway} GROUP BY "task_id")
+                SELECT
+                    (SELECT AVG("completed_at" - "created_at") FROM "completions" WHERE "created_at" IS NOT NULL),
+                    (SELECT AVG("completed_at" - "started_at") FROM "completions" JOIN "starts" ON "starts"."task_id" = "completions"."id"
+                        WHERE "completed_at" >= "started_at")"#,
+                completions(statuses)),
+            params_from_iter(statuses.iter().chain([&TaskStatus::Underway])),
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok(AverageTimes::from_seconds(lead_time, cycle_time))
+    }
+
+    fn atomically(&self, apply: &mut dyn FnMut() -> Result<(), Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+        self.data.atomically(apply)
+    }
+}
+
+// the tasks now in one of the statuses (bound from ?1 on), with the last time they got there
+fn completions(statuses: &[TaskStatus]) -> String {
+    let placeholders: Vec<String> = (1..=statuses.len()).map(|index| format!("?{index}")).collect();
+    format!(
+        r#"WITH "completions" AS (
+            SELECT "tasks"."id", "tasks"."created_at", MAX("status_changes"."changed_at") AS "completed_at"
+            FROM "tasks" JOIN "status_changes" ON "status_changes"."task_id" = "tasks"."id" AND "status_changes"."status" = "tasks"."status"
+            WHERE "tasks"."status" IN ({})
+            GROUP BY "tasks"."id")"#,
+        placeholders.join(", "))
+}
+
+fn period_count(row: &Row) -> rusqlite::Result<(NaiveDate, usize)> {
+    let start: String = row.get(0)?;
+    let start = NaiveDate::parse_from_str(&start, "%Y-%m-%d")
+        .map_err(|error| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(error)))?;
+    Ok((start, row.get(1)?))
+}
+
+// the local date starting the period of a timestamp column, weeks starting on Monday
+fn period_start(period: Period, column: &str) -> String {
+    match period {
+        Period::Day => format!("date({column}, 'unixepoch', 'localtime')"),
+        Period::Week => format!("date({column}, 'unixepoch', 'localtime', 'weekday 0', '-6 days')"),
+    }
+}
@@ -0,0 +1,512 @@
+use std::error::Error;
+
+use chrono::Local;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::dates;
+use crate::manager::task::TaskStatus;
+use crate::manager::{TaskManager, TaskQuery};
+
+const HELP: &str = "j/k move  space status  a add  e edit  d delete  f filter  t tag  q quit";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskRow {
+    pub id: i32,
+    pub title: String,
+    pub status: TaskStatus,
+    // the version of the task when listed, so that a change made elsewhere since isn't overridden
+    pub version: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Title,
+    Description,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Form {
+    // the id and version of the task being edited
+    pub editing: Option<(i32, i64)>,
+    pub title: String,
+    pub description: String,
+    pub focus: Field,
+}
+
+impl Form {
+    fn focused(&mut self) -> &mut String {
+        match self.focus {
+            Field::Title => &mut self.title,
+            Field::Description => &mut self.description,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Browse,
+    Form(Form),
+    ConfirmDelete,
+}
+
+#[derive(Debug, Clone)]
+pub struct App {
+    pub rows: Vec<TaskRow>,
+    pub selected: usize,
+    pub filter: Option<TaskStatus>,
+    pub tag_filter: Option<String>,
+    pub mode: Mode,
+    pub message: Option<String>,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(manager: &TaskManager) -> Result<App, Box<dyn Error>> {
+        let mut app = App {
+            rows: Vec::new(),
+            selected: 0,
+            filter: None,
+            tag_filter: None,
+            mode: Mode::Browse,
+            message: None,
+            quit: false,
+        };
+        app.refresh(manager)?;
+        Ok(app)
+    }
+
+    pub fn refresh(&mut self, manager: &TaskManager) -> Result<(), Box<dyn Error>> {
+        let query = TaskQuery { status: self.filter.clone(), tags: self.tag_filter.iter().cloned().collect(), ..Default::default() };
+
+        self.rows = Vec::new();
+        for task in manager.get_records(&query)? {
+            self.rows.push(TaskRow { id: task.id, title: task.title, status: task.status, version: task.version });
+        }
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+        Ok(())
+    }
+
+    pub fn selected_row(&self) -> Option<&TaskRow> {
+        self.rows.get(self.selected)
+    }
+
+    pub fn handle_key(&mut self, manager: &TaskManager, key: KeyEvent) -> Result<(), Box<dyn Error>> {
+        if key.kind != KeyEventKind::Press {
+            return Ok(());
+        }
+
+        // whatever goes wrong (e.g. a transition the workflow forbids) is shown, not fatal
+        let mode = std::mem::replace(&mut self.mode, Mode::Browse);
+        let result = match mode {
+            Mode::Browse => self.browse(manager, key.code),
+            Mode::Form(form) => self.fill_form(manager, form, key.code),
+            Mode::ConfirmDelete => self.confirm_delete(manager, key.code),
+        };
+        if let Err(error) = result {
+            self.message = Some(error.to_string());
+        }
+
+        self.refresh(manager)
+    }
+
+    fn browse(&mut self, manager: &TaskManager, code: KeyCode) -> Result<(), Box<dyn Error>> {
+        self.message = None;
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('j') | KeyCode::Down => self.selected = (self.selected + 1).min(self.rows.len().saturating_sub(1)),
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char('g') | KeyCode::Home => self.selected = 0,
+            KeyCode::Char('G') | KeyCode::End => self.selected = self.rows.len().saturating_sub(1),
+            KeyCode::Char(' ') => self.cycle_status(manager)?,
+            KeyCode::Char('f') => self.cycle_filter(manager)?,
+            KeyCode::Char('t') => self.cycle_tag_filter(manager)?,
+            KeyCode::Char('a') => {
+                self.mode = Mode::Form(Form { editing: None, title: String::new(), description: String::new(), focus: Field::Title });
+            },
+            KeyCode::Char('e') => {
+                if let Some(row) = self.selected_row() {
+                    let task = manager.get_task_as_read(row.id, row.version)?;
+                    self.mode = Mode::Form(Form {
+                        editing: Some((row.id, row.version)),
+                        title: task.get_title()?,
+                        description: task.get_description()?,
+                        focus: Field::Title,
+                    });
+                }
+            },
+            KeyCode::Char('d') if self.selected_row().is_some() => self.mode = Mode::ConfirmDelete,
+            _ => {},
+        }
+        Ok(())
+    }
+
+    fn fill_form(&mut self, manager: &TaskManager, mut form: Form, code: KeyCode) -> Result<(), Box<dyn Error>> {
+        match code {
+            KeyCode::Esc => return Ok(()),
+            KeyCode::Tab | KeyCode::BackTab => {
+                form.focus = match form.focus {
+                    Field::Title => Field::Description,
+                    Field::Description => Field::Title,
+                };
+            },
+            KeyCode::Backspace => {
+                form.focused().pop();
+            },
+            KeyCode::Char(c) => form.focused().push(c),
+            KeyCode::Enter => return self.save_form(manager, form),
+            _ => {},
+        }
+
+        self.mode = Mode::Form(form);
+        Ok(())
+    }
+
+    fn save_form(&mut self, manager: &TaskManager, form: Form) -> Result<(), Box<dyn Error>> {
+        if form.title.trim().is_empty() {
+            self.mode = Mode::Form(form);
+            return Err(Box::<dyn std::error::Error>::from("a task needs a title"));
+        }
+
+        manager.transaction(|manager| match form.editing {
+            Some((id, version)) => {
+                let mut task = manager.get_task_as_read(id, version)?;
+                task.set_title(&form.title)?;
+                task.set_description(&form.description)
+            },
+            None => manager.add_new_task(&form.title, &form.description),
+        })?;
+
+        // keeping the saved task selected
+        self.refresh(manager)?;
+        if let Some(index) = self.rows.iter().position(|row| row.title == form.title) {
+            self.selected = index;
+        }
+        Ok(())
+    }
+
+    fn confirm_delete(&mut self, manager: &TaskManager, code: KeyCode) -> Result<(), Box<dyn Error>> {
+        if let (KeyCode::Char('y'), Some(row)) = (code, self.selected_row()) {
+            let title = row.title.clone();
+            manager.delete(&manager.get_task_as_read(row.id, row.version)?)?;
+            self.message = Some(format!("Deleted '{title}'"));
+        }
+        Ok(())
+    }
+
+    fn cycle_status(&mut self, manager: &TaskManager) -> Result<(), Box<dyn Error>> {
+        let Some(row) = self.selected_row() else {
+            return Ok(());
+        };
+
+        // the next status of the workflow the task is allowed to move to, cancelling apart
+        let workflow = manager.workflow();
+        let statuses: Vec<TaskStatus> = workflow.get_statuses()?
+            .into_iter()
+            .map(|definition| definition.status)
+            .filter(|status| *status != TaskStatus::Cancelled)
+            .collect();
+        let current = statuses.iter().position(|status| *status == row.status).unwrap_or(statuses.len() - 1);
+        let next = (1..statuses.len())
+            .map(|offset| &statuses[(current + offset) % statuses.len()])
+            .find(|status| workflow.check_transition(&row.status, status).is_ok())
+            .ok_or_else(|| Box::<dyn std::error::Error>::from(format!("'{}' can't move to any other status", row.title)))?;
+
+        manager.get_task_as_read(row.id, row.version)?.set_status(next)?;
+        if let Some((count, limit)) = workflow.check_wip_limit(next)? {
+            self.message = Some(format!("{count} tasks are '{next}', over its WIP limit of {limit}"));
+        }
+        Ok(())
+    }
+
+    fn cycle_filter(&mut self, manager: &TaskManager) -> Result<(), Box<dyn Error>> {
+        let statuses: Vec<TaskStatus> = manager.workflow().get_statuses()?
+            .into_iter()
+            .map(|definition| definition.status)
+            .collect();
+
+        // all the tasks, then the ones of each status in turn
+        self.filter = match &self.filter {
+            None => statuses.first().cloned(),
+            Some(filter) => statuses
+                .iter()
+                .position(|status| status == filter)
+                .and_then(|index| statuses.get(index + 1).cloned()),
+        };
+        self.selected = 0;
+        Ok(())
+    }
+
+    fn cycle_tag_filter(&mut self, manager: &TaskManager) -> Result<(), Box<dyn Error>> {
+        let mut tags: Vec<String> = manager.get_records(&TaskQuery::everything())?
+            .into_iter()
+            .flat_map(|task| task.tags)
+            .collect();
+        tags.sort();
+        tags.dedup();
+
+        // all the tasks, then the ones with each tag in turn
+        self.tag_filter = match &self.tag_filter {
+            None => tags.first().cloned(),
+            Some(filter) => tags
+                .iter()
+                .position(|tag| tag == filter)
+                .and_then(|index| tags.get(index + 1).cloned()),
+        };
+        self.selected = 0;
+        Ok(())
+    }
+
+    pub fn draw(&self, frame: &mut Frame, manager: &TaskManager) {
+        let [main, footer] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, details_area] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(main);
+
+        let filters: Vec<String> = self.filter.iter().map(TaskStatus::to_string)
+            .chain(self.tag_filter.iter().map(|tag| format!("#{tag}")))
+            .collect();
+        let title = match filters.is_empty() {
+            true => " Tasks ".to_string(),
+            false => format!(" Tasks ({}) ", filters.join(", ")),
+        };
+        let items: Vec<ListItem> = self.rows
+            .iter()
+            .map(|row| ListItem::new(Line::from(vec![Span::raw(format!("[{}] ", row.status)).dim(), Span::raw(row.title.clone())])))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(self.selected_row().map(|_| self.selected));
+        frame.render_stateful_widget(list, list_area, &mut state);
+
+        let details = Paragraph::new(self.details(manager).unwrap_or_else(|error| vec![Line::from(error.to_string())]))
+            .block(Block::bordered().title(" Details "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(details, details_area);
+
+        let status = match (&self.mode, &self.message) {
+            (Mode::ConfirmDelete, _) => "Delete the selected task? (y/n)".to_string(),
+            (_, Some(message)) => message.clone(),
+            (Mode::Form(_), None) => "tab next field  enter save  esc cancel".to_string(),
+            (Mode::Browse, None) => HELP.to_string(),
+        };
+        frame.render_widget(Paragraph::new(status).dim(), footer);
+
+        if let Mode::Form(form) = &self.mode {
+            draw_form(frame, form, main);
+        }
+    }
+
+    fn details(&self, manager: &TaskManager) -> Result<Vec<Line<'static>>, Box<dyn Error>> {
+        let Some(row) = self.selected_row() else {
+            return Ok(vec![Line::from("No tasks")]);
+        };
+        let task = manager.get_task_by_id(row.id)?;
+
+        let mut lines = vec![
+            Line::from(task.get_title()?).bold(),
+            Line::from(format!("status: {}", task.get_status()?)),
+        ];
+        if let Some(due) = task.get_due()? {
+            lines.push(Line::from(format!("due: {}", dates::format_timestamp(due.timestamp()))));
+        }
+        if let Some(estimate) = task.get_estimate()? {
+            lines.push(Line::from(format!("estimate: {}", dates::format_duration(estimate))));
+        }
+        let tags = task.get_tags()?;
+        if !tags.is_empty() {
+            lines.push(Line::from(format!("tags: {}", tags.join(", "))));
+        }
+        let tracked = manager.time_tracker().get_total(&task, Local::now())?;
+        if tracked.num_minutes() > 0 {
+            lines.push(Line::from(format!("tracked: {}", dates::format_duration(tracked))));
+        }
+        lines.push(Line::from(""));
+        lines.extend(task.get_description()?.lines().map(|line| Line::from(line.to_string())));
+
+        Ok(lines)
+    }
+}
+
+fn draw_form(frame: &mut Frame, form: &Form, area: Rect) {
+    let [_, row, _] = Layout::vertical([Constraint::Fill(1), Constraint::Length(8), Constraint::Fill(1)]).areas(area);
+    let [_, area, _] = Layout::horizontal([Constraint::Fill(1), Constraint::Percentage(60), Constraint::Fill(1)]).areas(row);
+
+    let title = match form.editing {
+        Some(_) => " Edit task ",
+        None => " New task ",
+    };
+    let field = |label: &str, value: &str, focused: bool| {
+        let cursor = if focused { "_" } else { "" };
+        let line = Line::from(vec![Span::raw(format!("{label}: ")).bold(), Span::raw(format!("{value}{cursor}"))]);
+        match focused {
+            true => line,
+            false => line.dim(),
+        }
+    };
+
+    let lines = vec![
+        field("Title", &form.title, form.focus == Field::Title),
+        Line::from(""),
+        field("Description", &form.description, form.focus == Field::Description),
+    ];
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(title)).wrap(Wrap { trim: false }), area);
+}
+
+pub fn run_tui(manager: &TaskManager) -> Result<(), Box<dyn Error>> {
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, manager);
+
+    // giving the terminal back, whatever happened
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, manager: &TaskManager) -> Result<(), Box<dyn Error>> {
+    let mut app = App::new(manager)?;
+
+    while !app.quit {
+        terminal.draw(|frame| app.draw(frame, manager))?;
+        if let Event::Key(key) = event::read()? {
+            app.handle_key(manager, key)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::Terminal;
+
+    use crate::manager::{task::TaskStatus, TaskManager};
+
+    use super::{App, Mode};
+
+    fn press(app: &mut App, manager: &TaskManager, codes: &[KeyCode]) {
+        for code in codes {
+            app.handle_key(manager, KeyEvent::new(*code, KeyModifiers::NONE)).unwrap();
+        }
+    }
+
+    fn type_text(app: &mut App, manager: &TaskManager, text: &str) {
+        let codes: Vec<KeyCode> = text.chars().map(KeyCode::Char).collect();
+        press(app, manager, &codes);
+    }
+
+    #[test]
+    fn test_add_and_edit() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let mut app = App::new(&manager).unwrap();
+
+        press(&mut app, &manager, &[KeyCode::Char('a')]);
+        type_text(&mut app, &manager, "task");
+        press(&mut app, &manager, &[KeyCode::Tab]);
+        type_text(&mut app, &manager, "desc");
+        press(&mut app, &manager, &[KeyCode::Enter]);
+        assert_eq!("desc", manager.get_task("task").unwrap().get_description().unwrap());
+
+        press(&mut app, &manager, &[KeyCode::Char('e'), KeyCode::Backspace]);
+        type_text(&mut app, &manager, "ks");
+        press(&mut app, &manager, &[KeyCode::Enter]);
+        assert!(manager.get_task("tasks").is_ok());
+        assert_eq!(Mode::Browse, app.mode);
+    }
+
+    #[test]
+    fn test_cycle_status_and_delete() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task", "desc");
+        let mut app = App::new(&manager).unwrap();
+
+        // undone comes right before done in the workflow
+        press(&mut app, &manager, &[KeyCode::Char(' ')]);
+        assert_eq!(TaskStatus::Done, manager.get_task("task").unwrap().get_status().unwrap());
+
+        // nothing happens without a confirmation
+        press(&mut app, &manager, &[KeyCode::Char('d'), KeyCode::Char('n')]);
+        assert!(manager.get_task("task").is_ok());
+        press(&mut app, &manager, &[KeyCode::Char('d'), KeyCode::Char('y')]);
+        assert!(manager.get_task("task").is_err());
+    }
+
+    #[test]
+    fn test_changed_elsewhere() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task", "desc");
+        let mut app = App::new(&manager).unwrap();
+
+        // the listed task is left alone once changed by someone else, here while asking for a confirmation
+        press(&mut app, &manager, &[KeyCode::Char('d')]);
+        manager.get_task("task").unwrap().set_description("changed elsewhere").unwrap();
+        press(&mut app, &manager, &[KeyCode::Char('y')]);
+        assert!(manager.get_task("task").is_ok());
+        assert!(app.message.as_ref().unwrap().contains("changed elsewhere"));
+
+        // and can be deleted once listed again
+        press(&mut app, &manager, &[KeyCode::Char('d'), KeyCode::Char('y')]);
+        assert!(manager.get_task("task").is_err());
+    }
+
+    #[test]
+    fn test_filter() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.get_task("task A").unwrap().set_status(&TaskStatus::Underway);
+        let mut app = App::new(&manager).unwrap();
+        assert_eq!(2, app.rows.len());
+
+        // underway is the first status of the workflow
+        press(&mut app, &manager, &[KeyCode::Char('f')]);
+        assert_eq!(Some(TaskStatus::Underway), app.filter);
+        assert_eq!(1, app.rows.len());
+    }
+
+    #[test]
+    fn test_tag_filter() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task A", "desc A");
+        let _ = manager.add_new_task("task B", "desc B");
+        let _ = manager.get_task("task A").unwrap().add_tag("release");
+        let _ = manager.get_task("task B").unwrap().add_tag("docs");
+        let mut app = App::new(&manager).unwrap();
+
+        // the tags come in order, then all the tasks again
+        press(&mut app, &manager, &[KeyCode::Char('t')]);
+        assert_eq!(Some("docs".to_string()), app.tag_filter);
+        assert_eq!("task B", app.rows[0].title);
+        press(&mut app, &manager, &[KeyCode::Char('t')]);
+        assert_eq!(vec!["task A"], app.rows.iter().map(|row| row.title.as_str()).collect::<Vec<_>>());
+
+        // along with the status filter
+        press(&mut app, &manager, &[KeyCode::Char('f')]);
+        assert_eq!(0, app.rows.len());
+        press(&mut app, &manager, &[KeyCode::Char('t')]);
+        assert_eq!(None, app.tag_filter);
+    }
+
+    #[test]
+    fn test_draw() {
+        let manager = TaskManager::open_in_memory().unwrap();
+        let _ = manager.add_new_task("task", "a description");
+        let mut app = App::new(&manager).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+
+        terminal.draw(|frame| app.draw(frame, &manager)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("[undone] task"));
+        assert!(screen.contains("a description"));
+
+        press(&mut app, &manager, &[KeyCode::Char('a')]);
+        terminal.draw(|frame| app.draw(frame, &manager)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("New task"));
+    }
+}
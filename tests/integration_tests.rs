@@ -1,26 +1,39 @@
-use std::error::Error;
+use std::{error::Error, path::{Path, PathBuf}};
 
-use peppertodo::{cli::{Cli, Commands}, manager::{task::{Task, TaskStatus}, TaskManager}};
+use peppertodo::{cli::{BulkArgs, ChartCommands, Cli, Commands, ConfigCommands, ProfileCommands, ReportCommands, ReportFormat, SortOrder, StatsFormat, WorkflowCommands}, location::{locate_database, DatabaseSource}, manager::{task::{Priority, TaskStatus}, TaskManager}, profile::ProfileManager};
 use rusqlite::Connection;
 
+fn cli(command: Commands) -> Cli {
+    Cli { database: None, profile: None, store: None, command }
+}
 
 #[test]
 fn test_run_add() -> Result<(), Box<dyn Error>> {
     // setting up the manager
-    let conn = Connection::open_in_memory()?;
-    let mut manager = TaskManager::new(&conn);
+    let manager = TaskManager::open_in_memory()?;
 
     // setting up the cli
-    let cli = Cli::from(Cli { 
-        command: Commands::Add { 
-            title: "task title".to_string(), 
-            description: "task description".to_string() 
-        } 
+    let cli = cli(Commands::Add { 
+        title: Some("task title".to_string()), 
+        description: Some("task description".to_string()),
+        estimate: Some("2h".to_string()),
+        points: None,
+        due: Some("tomorrow".to_string()),
+        priority: Some(Priority::High),
+        project: Some("website".to_string()),
+        tags: vec!["release".to_string()],
+        depends_on: vec![],
+        editor: false,
     });
 
-    let _ = peppertodo::run(&cli, &mut manager)?;
+    peppertodo::run(&cli, &manager)?;
 
     assert!(!manager.get_all_tasks(None).unwrap().is_empty());
+    assert_eq!(Some(chrono::Duration::hours(2)), manager.get_task("task title")?.get_estimate()?);
+    assert!(manager.get_task("task title")?.get_due()?.is_some());
+    assert_eq!(vec!["release"], manager.get_task("task title")?.get_tags()?);
+    assert_eq!(Some(Priority::High), manager.get_task("task title")?.get_priority()?);
+    assert_eq!(Some("website".to_string()), manager.get_task("task title")?.get_project()?);
 
     // Well, nothing bad has happened
     Ok(())
@@ -29,15 +42,12 @@ fn test_run_add() -> Result<(), Box<dyn Error>> {
 #[test]
 fn test_run_list() -> Result<(), Box<dyn Error>> {
     // setting up the manager
-    let conn = Connection::open_in_memory()?;
-    let mut manager = TaskManager::new(&conn);
+    let manager = TaskManager::open_in_memory()?;
 
     // setting up the cli
-    let cli = Cli::from(Cli {
-        command: Commands::List { filter: None }
-    });
+    let cli = cli(Commands::List { filter: None, all: false, waiting: false, sort: SortOrder::Status });
 
-    let _ = peppertodo::run(&cli, &mut manager)?;
+    peppertodo::run(&cli, &manager)?;
 
     Ok(())
 }
@@ -45,21 +55,20 @@ fn test_run_list() -> Result<(), Box<dyn Error>> {
 #[test]
 fn test_run_mark() -> Result<(), Box<dyn Error>> {
     // setting up the manager
-    let conn = Connection::open_in_memory()?;
-    let mut manager = TaskManager::new(&conn);
+    let manager = TaskManager::open_in_memory()?;
 
     // adding a task 
     manager.add_new_task("task title", "task description")?;
 
     // setting up the cli
-    let cli = Cli::from(Cli {
-        command: Commands::Mark { 
-            target: "task title".to_string(), 
-            status: TaskStatus::Done }
+    let cli = cli(Commands::Mark { 
+        target: Some("task title".to_string()), 
+        status: TaskStatus::Done,
+        bulk: BulkArgs::default(),
     });
 
     // setting up the cli
-    let _ = peppertodo::run(&cli, &mut manager);
+    let _ = peppertodo::run(&cli, &manager);
 
     assert!(!manager.get_all_tasks(Some(&TaskStatus::Done)).unwrap().is_empty());
 
@@ -69,49 +78,96 @@ fn test_run_mark() -> Result<(), Box<dyn Error>> {
 #[test]
 fn test_run_edit() -> Result<(), Box<dyn Error>> {
     // setting up the manager
-    let conn = Connection::open_in_memory()?;
-    let mut manager = TaskManager::new(&conn);
+    let manager = TaskManager::open_in_memory()?;
 
-    // adding a task
+    // adding a task, and another to close first
     manager.add_new_task("task title", "task description")?;
+    manager.add_new_task("other", "task description")?;
 
     // setting up the cli
-    let cli = Cli::from(Cli {
-        command: Commands::Edit { 
-            target: "task title".to_string(), 
-            title: Some("new title".to_string()), 
-            description: Some("new description".to_string())  
-        }
+    let cli = cli(Commands::Edit { 
+        target: Some("task title".to_string()), 
+        title: Some("new title".to_string()), 
+        description: Some("new description".to_string()),
+        estimate: None,
+        points: Some(3),
+        due: None,
+        priority: Some("low".to_string()),
+        project: None,
+        add_tags: vec![],
+        remove_tags: vec![],
+        add_dependencies: vec!["other".to_string()],
+        remove_dependencies: vec![],
+        editor: false,
+        bulk: BulkArgs::default(),
     });
 
     // running the code to test
-    peppertodo::run(&cli, &mut manager)?;
+    peppertodo::run(&cli, &manager)?;
 
     // assetions
-    let task = Task::from(&conn, 1); // manually instantiated task
+    let task = manager.get_task_by_id(1)?;
     assert_eq!("new title", task.get_title().unwrap());
     assert_eq!("new description", task.get_description().unwrap());
+    assert_eq!(Some(3), task.get_points().unwrap());
+    assert_eq!(Some(Priority::Low), task.get_priority().unwrap());
+    assert!(task.is_blocked().unwrap());
 
     Ok(())
 }
 
+#[test]
+fn test_run_edit_is_atomic() -> Result<(), Box<dyn Error>> {
+    // setting up the manager, on a database another connection can reach
+    let path = std::env::temp_dir().join(format!("peppertodo-atomic-edit-{}.db", std::process::id()));
+    let manager = TaskManager::open(&path)?;
+
+    // adding a task, whose description can't change
+    manager.add_new_task("task title", "task description")?;
+    Connection::open(&path)?.execute_batch(r#"CREATE TRIGGER "fixed_description" BEFORE UPDATE OF "description" ON "tasks" BEGIN SELECT RAISE(ABORT, 'fixed'); END"#)?;
+
+    // the title doesn't change either
+    let cli = cli(Commands::Edit {
+        target: Some("task title".to_string()),
+        title: Some("new title".to_string()),
+        description: Some("new description".to_string()),
+        estimate: None,
+        points: None,
+        due: None,
+        priority: None,
+        project: None,
+        add_tags: vec![],
+        remove_tags: vec![],
+        add_dependencies: vec![],
+        remove_dependencies: vec![],
+        editor: false,
+        bulk: BulkArgs::default(),
+    });
+    assert!(peppertodo::run(&cli, &manager).is_err());
+    assert!(manager.get_task("task title").is_ok());
+
+    drop(manager);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+    Ok(())
+}
+
 #[test]
 fn test_run_delete() -> Result<(), Box<dyn Error>> {
     // setting up the manager
-    let conn = Connection::open_in_memory()?;
-    let mut manager = TaskManager::new(&conn);
+    let manager = TaskManager::open_in_memory()?;
 
     // populating the db 
     manager.add_new_task("task title", "task description")?;
 
     // setting up the cli
-    let cli = Cli::from(Cli { 
-        command: Commands::Delete { 
-            target: "task title".to_string(), 
-        } 
+    let cli = cli(Commands::Delete { 
+        target: Some("task title".to_string()), 
+        bulk: BulkArgs::default(),
     });
 
-    let _ = peppertodo::run(&cli, &mut manager)?;
+    peppertodo::run(&cli, &manager)?;
 
     assert!(manager.get_all_tasks(None).unwrap().is_empty());
 
@@ -119,25 +175,515 @@ fn test_run_delete() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_run_bulk() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("write the docs", "desc")?;
+    manager.add_new_task("review the docs", "desc")?;
+    manager.add_new_task("fix the bug", "desc")?;
+
+    let bulk = |filter: &str, dry_run: bool| BulkArgs { filter: Some(filter.to_string()), dry_run, yes: true };
+
+    // a dry run doesn't change anything
+    peppertodo::run(&cli(Commands::Mark { target: None, status: TaskStatus::Done, bulk: bulk("title:docs", true) }), &manager)?;
+    assert!(manager.get_all_tasks(Some(&TaskStatus::Done))?.is_empty());
+
+    // neither does a change refused by the workflow for one of the tasks
+    manager.workflow().allow_transition(&TaskStatus::Undone, &TaskStatus::Underway)?;
+    manager.workflow().allow_transition(&TaskStatus::Underway, &TaskStatus::Done)?;
+    manager.get_task("write the docs")?.set_status(&TaskStatus::Underway)?;
+    assert!(peppertodo::run(&cli(Commands::Mark { target: None, status: TaskStatus::Done, bulk: bulk("title:docs", false) }), &manager).is_err());
+    assert!(manager.get_all_tasks(Some(&TaskStatus::Done))?.is_empty());
+
+    // otherwise every matching task changes
+    peppertodo::run(&cli(Commands::Edit {
+        target: None,
+        title: None,
+        description: None,
+        estimate: Some("2h".to_string()),
+        points: None,
+        due: None,
+        priority: None,
+        project: None,
+        add_tags: vec!["release".to_string()],
+        remove_tags: vec![],
+        add_dependencies: vec![],
+        remove_dependencies: vec![],
+        editor: false,
+        bulk: bulk("title:docs", false),
+    }), &manager)?;
+    assert!(manager.get_task("review the docs")?.get_estimate()?.is_some());
+    assert!(manager.get_task("review the docs")?.has_tag("release")?);
+    assert!(manager.get_task("fix the bug")?.get_estimate()?.is_none());
+
+    peppertodo::run(&cli(Commands::Mark { target: None, status: TaskStatus::Underway, bulk: bulk("tag:release", false) }), &manager)?;
+    assert_eq!(2, manager.get_all_tasks(Some(&TaskStatus::Underway))?.len());
+    peppertodo::run(&cli(Commands::Delete { target: None, bulk: bulk("tag:release", false) }), &manager)?;
+    assert_eq!(1, manager.get_all_tasks(None)?.len());
+
+    // outside a terminal, the changes must be confirmed with --yes
+    let unconfirmed = BulkArgs { yes: false, ..bulk("title:bug", false) };
+    assert!(peppertodo::run(&cli(Commands::Delete { target: None, bulk: unconfirmed }), &manager).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_run_without_target() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task title", "task description")?;
+
+    // tests don't run in a terminal, where the task would be picked interactively
+    assert!(peppertodo::run(&cli(Commands::Delete { target: None, bulk: BulkArgs::default() }), &manager).is_err());
+    assert!(manager.get_task("task title").is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn test_run_show_and_check() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task", "# Steps\n\n- [ ] write the code\n- [ ] test it")?;
+
+    // showing the task and checking its items
+    peppertodo::run(&cli(Commands::Show { target: "task".to_string() }), &manager)?;
+    peppertodo::run(&cli(Commands::Check { target: "task".to_string(), number: 2 }), &manager)?;
+    assert_eq!("# Steps\n\n- [ ] write the code\n- [x] test it", manager.get_task("task")?.get_description()?);
+    assert!(manager.get_task("task")?.summary(chrono::Local::now())?.to_string().ends_with("# Steps (checklist 1/2)"));
+    assert!(peppertodo::run(&cli(Commands::Check { target: "task".to_string(), number: 3 }), &manager).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_run_note() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+
+    // writing, editing and deleting notes
+    let note = |text: Option<&str>, edit: Option<usize>, delete: Option<usize>| cli(Commands::Note {
+        target: "task".to_string(),
+        text: text.map(str::to_string),
+        edit,
+        delete,
+    });
+    peppertodo::run(&note(Some("tried X, failed"), None, None), &manager)?;
+    peppertodo::run(&note(Some("tried Y"), None, None), &manager)?;
+    peppertodo::run(&note(Some("tried Y, it works"), Some(2), None), &manager)?;
+    peppertodo::run(&note(None, None, Some(1)), &manager)?;
+    peppertodo::run(&cli(Commands::Show { target: "task".to_string() }), &manager)?;
+    assert!(peppertodo::run(&note(None, None, Some(2)), &manager).is_err());
+
+    let task = manager.get_task("task")?;
+    let texts: Vec<String> = task.get_notes()?.into_iter().map(|note| note.text).collect();
+    assert_eq!(vec!["tried Y, it works"], texts);
+
+    Ok(())
+}
+
+#[test]
+fn test_run_attach() -> Result<(), Box<dyn Error>> {
+    // setting up the manager, with a folder for the copies
+    let folder = std::env::temp_dir().join(format!("peppertodo-app-{}", std::process::id()));
+    let manager = TaskManager::builder().attachments_dir(&folder).build()?;
+    let file = std::env::temp_dir().join(format!("peppertodo-spec-{}.md", std::process::id()));
+    std::fs::write(&file, "# Spec")?;
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+    assert!(peppertodo::run(&cli(Commands::Open { target: "task".to_string(), number: None }), &manager).is_err());
+
+    // attaching a copy of a file, and a URL
+    let attach = |file: Option<PathBuf>, url: Option<&str>| Commands::Attach { target: "task".to_string(), file, url: url.map(str::to_string), link: false };
+    peppertodo::run(&cli(attach(Some(file.clone()), None)), &manager)?;
+    peppertodo::run(&cli(attach(None, Some("https://example.com/spec"))), &manager)?;
+    assert!(peppertodo::run(&cli(attach(None, Some("example.com"))), &manager).is_err());
+    peppertodo::run(&cli(Commands::Attachments { target: "task".to_string() }), &manager)?;
+    assert!(peppertodo::run(&cli(Commands::Open { target: "task".to_string(), number: None }), &manager).is_err());
+    assert!(peppertodo::run(&cli(Commands::Open { target: "task".to_string(), number: Some(3) }), &manager).is_err());
+
+    // the copy goes away with the task
+    assert_eq!(1, std::fs::read_dir(&folder)?.count());
+    peppertodo::run(&cli(Commands::Delete { target: Some("task".to_string()), bulk: BulkArgs::default() }), &manager)?;
+    assert_eq!(0, std::fs::read_dir(&folder)?.count());
+
+    // a task list only kept in memory has nowhere to copy files to
+    let manager = TaskManager::open_in_memory()?;
+    manager.add_new_task("task", "desc")?;
+    assert!(peppertodo::run(&cli(attach(Some(file.clone()), None)), &manager).is_err());
+
+    std::fs::remove_file(&file)?;
+    std::fs::remove_dir_all(&folder)?;
+    Ok(())
+}
+
 #[test]
 fn test_run_clear() -> Result<(), Box<dyn Error>> {
     // setting up the manager
-    let conn = Connection::open_in_memory()?;
-    let mut manager = TaskManager::new(&conn);
+    let manager = TaskManager::open_in_memory()?;
 
     // populating the db 
     manager.add_new_task("task", "desc")?;
     manager.get_task("task").unwrap().set_status(&TaskStatus::Done)?;
 
     // setting up the cli
-    let cli = Cli::from(Cli { 
-        command: Commands::Clear
-    });
+    let cli = cli(Commands::Clear { cancelled: false });
 
-    let _ = peppertodo::run(&cli, &mut manager)?;
+    peppertodo::run(&cli, &manager)?;
 
     assert!(manager.get_all_tasks(None).unwrap().is_empty());
 
     // Well, nothing bad has happened
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_run_cancel() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+
+    // setting up the cli
+    let cli = cli(Commands::Cancel {
+        target: "task".to_string(),
+        reason: Some("not needed anymore".to_string()),
+    });
+
+    peppertodo::run(&cli, &manager)?;
+
+    let task = manager.get_task("task")?;
+    assert_eq!(TaskStatus::Cancelled, task.get_status()?);
+    assert_eq!(Some("not needed anymore".to_string()), task.get_cancellation_reason()?);
+    assert!(manager.get_all_tasks(None)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_run_snooze() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+    manager.add_new_task("another task", "desc")?;
+
+    peppertodo::run(&cli(Commands::Snooze {
+        target: "task".to_string(),
+        until: vec!["until".to_string(), "tomorrow".to_string()],
+        wait: None,
+    }), &manager)?;
+    peppertodo::run(&cli(Commands::Snooze {
+        target: "another task".to_string(),
+        until: Vec::new(),
+        wait: Some("3d".to_string()),
+    }), &manager)?;
+
+    assert!(manager.get_all_tasks(None)?.is_empty());
+
+    // dates in the past are refused
+    assert!(peppertodo::run(&cli(Commands::Snooze {
+        target: "task".to_string(),
+        until: vec!["yesterday".to_string()],
+        wait: None,
+    }), &manager).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_run_urgency() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+    manager.add_new_task("another task", "desc")?;
+    manager.get_task("another task")?.set_status(&TaskStatus::Done)?;
+
+    // configuring the model
+    peppertodo::run(&cli(Commands::Config { command: ConfigCommands::Set {
+        key: "urgency.status.undone".to_string(),
+        value: "1.5".to_string(),
+    } }), &manager)?;
+    assert_eq!(Some("1.5".to_string()), manager.settings().get("urgency.status.undone")?);
+
+    peppertodo::run(&cli(Commands::Next { limit: 3 }), &manager)?;
+    peppertodo::run(&cli(Commands::Urgency { target: "task".to_string() }), &manager)?;
+    peppertodo::run(&cli(Commands::List { filter: None, all: false, waiting: false, sort: SortOrder::Urgency }), &manager)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_run_time_tracking() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+
+    // tracking time with a timer
+    peppertodo::run(&cli(Commands::Start { target: "task".to_string() }), &manager)?;
+    peppertodo::run(&cli(Commands::Status), &manager)?;
+    assert_eq!(TaskStatus::Underway, manager.get_task("task")?.get_status()?);
+    peppertodo::run(&cli(Commands::Stop), &manager)?;
+    assert!(peppertodo::run(&cli(Commands::Stop), &manager).is_err());
+
+    // and manually
+    peppertodo::run(&cli(Commands::Track {
+        target: "task".to_string(),
+        duration: "1h30m".to_string(),
+        at: Some("yesterday".to_string()),
+    }), &manager)?;
+
+    let task = manager.get_task("task")?;
+    let total = manager.time_tracker().get_total(&task, chrono::Local::now())?;
+    assert!(total >= chrono::Duration::minutes(90));
+    assert!(task.summary(chrono::Local::now())?.to_string().contains("tracked 1h30m"));
+
+    Ok(())
+}
+
+#[test]
+fn test_run_report_time() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+    let task = manager.get_task("task")?;
+    manager.time_tracker().track(&task, chrono::Local::now() - chrono::Duration::hours(2), chrono::Duration::minutes(50))?;
+    manager.settings().set("report.rounding", "15m")?;
+
+    for format in [ReportFormat::Text, ReportFormat::Csv, ReportFormat::Json, ReportFormat::Markdown] {
+        peppertodo::run(&cli(Commands::Report { command: ReportCommands::Time {
+            from: Some("yesterday".to_string()),
+            to: None,
+            format,
+        } }), &manager)?;
+    }
+
+    // a report can't end before it starts
+    assert!(peppertodo::run(&cli(Commands::Report { command: ReportCommands::Time {
+        from: Some("today".to_string()),
+        to: Some("yesterday".to_string()),
+        format: ReportFormat::Text,
+    } }), &manager).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_run_pomodoro() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db, with (very) short pomodoros
+    manager.add_new_task("task", "desc")?;
+    manager.settings().set("pomodoro.work", "1s")?;
+    manager.settings().set("pomodoro.break", "0s")?;
+
+    peppertodo::run(&cli(Commands::Pomodoro { target: "task".to_string(), rounds: 2 }), &manager)?;
+    peppertodo::run(&cli(Commands::Report { command: ReportCommands::Pomodoro }), &manager)?;
+
+    let task = manager.get_task("task")?;
+    assert_eq!(2, manager.pomodoro_log().count_completed(&task)?);
+    assert_eq!(2, manager.time_tracker().get_entries(&task)?.len());
+    assert!(manager.time_tracker().get_running()?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_run_report_estimates() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+    manager.add_new_task("another task", "desc")?;
+    manager.get_task("task")?.set_estimate(Some(chrono::Duration::hours(1)))?;
+    manager.get_task("another task")?.set_points(Some(5))?;
+    manager.get_task("task")?.set_status(&TaskStatus::Done)?;
+
+    peppertodo::run(&cli(Commands::Report { command: ReportCommands::Effort }), &manager)?;
+    peppertodo::run(&cli(Commands::Report { command: ReportCommands::Accuracy }), &manager)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_run_summary() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+    manager.add_new_task("another task", "desc")?;
+    manager.get_task("task")?.set_status(&TaskStatus::Done)?;
+    manager.get_task("another task")?.cancel(None)?;
+
+    peppertodo::run(&cli(Commands::Summary), &manager)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_run_stats() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+    manager.add_new_task("another task", "desc")?;
+    manager.get_task("task")?.set_status(&TaskStatus::Done)?;
+
+    peppertodo::run(&cli(Commands::Stats { weekly: false, periods: 7, format: StatsFormat::Text }), &manager)?;
+    peppertodo::run(&cli(Commands::Stats { weekly: true, periods: 4, format: StatsFormat::Json }), &manager)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_run_chart() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+    let svg = std::env::temp_dir().join(format!("peppertodo-chart-{}.svg", std::process::id()));
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+    manager.add_new_task("another task", "desc")?;
+    manager.get_task("task")?.set_status(&TaskStatus::Done)?;
+
+    manager.get_task("task")?.set_project(Some("website"))?;
+
+    let burndown = |project: Option<&str>| cli(Commands::Chart {
+        command: ChartCommands::Burndown { project: project.map(str::to_string), from: None, to: None, ascii: false, svg: None },
+    });
+    peppertodo::run(&burndown(None), &manager)?;
+    peppertodo::run(&burndown(Some("website")), &manager)?;
+    peppertodo::run(&cli(Commands::Chart { command: ChartCommands::Cfd { project: None, from: Some("yesterday".to_string()), to: None, ascii: true, svg: Some(svg.clone()) } }), &manager)?;
+
+    assert!(std::fs::read_to_string(&svg)?.contains("<polygon"));
+    std::fs::remove_file(svg)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_run_calendar_and_agenda() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+    manager.get_task("task")?.set_due(Some(chrono::Local::now()))?;
+
+    peppertodo::run(&cli(Commands::Calendar { month: None, titles: true }), &manager)?;
+    peppertodo::run(&cli(Commands::Calendar { month: Some("2026-11".to_string()), titles: false }), &manager)?;
+    assert!(peppertodo::run(&cli(Commands::Calendar { month: Some("november".to_string()), titles: false }), &manager).is_err());
+    peppertodo::run(&cli(Commands::Agenda { days: 7 }), &manager)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_run_board() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+
+    // populating the db
+    manager.add_new_task("task", "desc")?;
+    manager.add_new_task("another task", "desc")?;
+    peppertodo::run(&cli(Commands::Workflow { command: WorkflowCommands::Limit { status: TaskStatus::Underway, limit: 1 } }), &manager)?;
+    assert_eq!(Some(1), manager.workflow().get_wip_limit(&TaskStatus::Underway)?);
+
+    // going over the limit is only a warning
+    peppertodo::run(&cli(Commands::Mark { target: Some("task".to_string()), status: TaskStatus::Underway, bulk: BulkArgs::default() }), &manager)?;
+    peppertodo::run(&cli(Commands::Mark { target: Some("another task".to_string()), status: TaskStatus::Underway, bulk: BulkArgs::default() }), &manager)?;
+
+    peppertodo::run(&cli(Commands::Board { all: false, width: Some(60) }), &manager)?;
+    peppertodo::run(&cli(Commands::Board { all: true, width: None }), &manager)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_run_workflow() -> Result<(), Box<dyn Error>> {
+    // setting up the manager
+    let manager = TaskManager::open_in_memory()?;
+    let review: TaskStatus = "review".parse()?;
+
+    // adding a status and restricting the transitions
+    peppertodo::run(&cli(Commands::Workflow { command: WorkflowCommands::Add { status: review.clone(), closed: false } }), &manager)?;
+    peppertodo::run(&cli(Commands::Workflow { command: WorkflowCommands::Allow { from: TaskStatus::Undone, to: review.clone() } }), &manager)?;
+    peppertodo::run(&cli(Commands::Workflow { command: WorkflowCommands::Show }), &manager)?;
+
+    // only the allowed transitions can be applied
+    manager.add_new_task("task", "desc")?;
+    assert!(peppertodo::run(&cli(Commands::Mark { target: Some("task".to_string()), status: TaskStatus::Done, bulk: BulkArgs::default() }), &manager).is_err());
+    peppertodo::run(&cli(Commands::Mark { target: Some("task".to_string()), status: review.clone(), bulk: BulkArgs::default() }), &manager)?;
+    assert_eq!(1, manager.get_all_tasks(Some(&review))?.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_run_profile() -> Result<(), Box<dyn Error>> {
+    // setting up the profile manager
+    let conn = Connection::open_in_memory()?;
+    let mut profiles = ProfileManager::new(&conn);
+    let app_home = Path::new("app_home");
+
+    // creating and selecting a profile
+    peppertodo::run_profile(&ProfileCommands::Create { name: "work".to_string(), path: None }, &mut profiles, app_home)?;
+    peppertodo::run_profile(&ProfileCommands::Use { name: "work".to_string() }, &mut profiles, app_home)?;
+    peppertodo::run_profile(&ProfileCommands::List, &mut profiles, app_home)?;
+
+    let profile = profiles.get_active_profile()?.unwrap();
+    assert_eq!(app_home.join("profiles").join("work.db"), profile.path);
+
+    Ok(())
+}
+
+#[test]
+fn test_locate_database() -> Result<(), Box<dyn Error>> {
+    // setting up the profile manager
+    let conn = Connection::open_in_memory()?;
+    let mut profiles = ProfileManager::new(&conn);
+    let app_home = Path::new("app_home");
+    profiles.create_profile("work", Path::new("work.db"))?;
+
+    // an explicit path wins over everything else
+    let mut cli = cli(Commands::Clear { cancelled: false });
+    cli.database = Some(PathBuf::from("explicit.db"));
+    let location = locate_database(&cli, &profiles, app_home, app_home)?;
+    assert_eq!((PathBuf::from("explicit.db"), DatabaseSource::Option), (location.path, location.source));
+
+    // then the requested profile
+    cli.database = None;
+    cli.profile = Some("work".to_string());
+    let location = locate_database(&cli, &profiles, app_home, app_home)?;
+    assert_eq!((PathBuf::from("work.db"), DatabaseSource::Profile("work".to_string())), (location.path, location.source));
+
+    // an unknown profile is an error
+    cli.profile = Some("missing".to_string());
+    assert!(locate_database(&cli, &profiles, app_home, app_home).is_err());
+
+    Ok(())
+}